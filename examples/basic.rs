@@ -3,7 +3,7 @@ extern crate argparse;
 use std::collections::HashMap;
 
 use argparse::{ArgParser, ArgType, hashmap_parser, vec_parser};
-const LONG_STR: &'static str = r#"Check your proxy settings or contact your network administrator to make sure the proxy server is working. If you don't believe you should be using a proxy server: Go to the Chromium menu > Settings > Show advanced settings... > Change proxy settings... and make sure your configuration is set to "no proxy" or "direct.""#;
+const LONG_STR: &str = r#"Check your proxy settings or contact your network administrator to make sure the proxy server is working. If you don't believe you should be using a proxy server: Go to the Chromium menu > Settings > Show advanced settings... > Change proxy settings... and make sure your configuration is set to "no proxy" or "direct.""#;
 
 fn main() {
     let mut parser = ArgParser::new("argparse".into());
@@ -39,6 +39,7 @@ fn main() {
         Some(vec![1,2,3,4,5]));
     assert_eq!(p_res.get_with("frequencies", vec_parser), 
         Some(vec![1,2,3,4,5]));
+    assert_eq!(p_res.get_list("frequencies"), Some(vec![1,2,3,4,5]));
     assert_eq!(p_res.get("mao"), Some(true));
     
     let h = [("Monday", true), ("Friday", false)]
@@ -0,0 +1,246 @@
+//! This module defines `App`, which runs the whole life of a command
+//! line program around its parser: reading the arguments it was given,
+//! parsing them, answering `--help` and `--version`, reporting errors
+//! and turning the outcome into an exit code.
+//!
+//! # Example
+//! ```no_run
+//! use std::process::ExitCode;
+//! use argparse::{ArgParser, ArgType};
+//! use argparse::app::App;
+//!
+//! fn main() -> ExitCode {
+//!     let mut parser = ArgParser::from_argv0();
+//!     parser.add_opt("name", None, 'n', true,
+//!         "Name of user", ArgType::Option);
+//!
+//!     App::new(parser).version("1.0.0").run(|p_res| {
+//!         let name: String = p_res.get("name").ok_or("no name")?;
+//!         println!("Hello, {}!", name);
+//!         Ok::<(), &str>(())
+//!     })
+//! }
+//! ```
+
+use std::convert::TryFrom;
+use std::env;
+use std::fmt;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use argparser::{ArgParseResults, ArgParser, ArgType};
+
+/// The exit code when the arguments fail to parse, as is usual
+pub const USAGE_ERROR: i32 = 2;
+
+/// The exit code when the program's main function fails, unless
+/// `App::exit_code` says otherwise
+pub const FAILURE: i32 = 1;
+
+type Hook = Box<dyn Fn(&ArgParseResults)>;
+
+/// A parser together with what to do around parsing, see the module
+/// documentation
+pub struct App<E> {
+    parser: ArgParser,
+    version: Option<String>,
+    hooks: Vec<Hook>,
+    exit_code: Box<dyn Fn(&E) -> i32>,
+}
+
+impl<E> fmt::Debug for App<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("App")
+            .field("parser", &self.parser)
+            .field("version", &self.version)
+            .finish()
+    }
+}
+
+impl<E: fmt::Display> App<E> {
+    /// Creates an app around the parser. Its `help` flag is made to
+    /// end parsing, so that help can be asked for without giving the
+    /// required arguments.
+    pub fn new(mut parser: ArgParser) -> App<E> {
+        let _ = parser.terminates("help");
+        
+        App {
+            parser,
+            version: None,
+            hooks: Vec::new(),
+            exit_code: Box::new(|_| FAILURE),
+        }
+    }
+    
+    /// Set the version printed by `--version` (`-V`), adding that flag
+    /// unless the parser already has a `version` argument
+    pub fn version(&mut self, version: &str) -> &mut App<E> {
+        if self.parser.terminates("version").is_err() {
+            let help = self.parser.catalog().version.clone();
+            self.parser.add_opt("version", Some("false"), 'V', false, &help, ArgType::Flag);
+            let _ = self.parser.terminates("version");
+        }
+        
+        self.version = Some(version.into());
+        self
+    }
+    
+    /// Adds a hook run with the results after they parse and before the
+    /// main function, e.g. to set up logging from a `--verbose` flag.
+    /// Hooks run in the order they were added.
+    pub fn before_run<F: Fn(&ArgParseResults) + 'static>(&mut self, hook: F) -> &mut App<E> {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+    
+    /// Set the exit code for each error of the main function, which is
+    /// `FAILURE` by default
+    pub fn exit_code<F: Fn(&E) -> i32 + 'static>(&mut self, map: F) -> &mut App<E> {
+        self.exit_code = Box::new(map);
+        self
+    }
+    
+    /// The parser the app runs with
+    pub fn parser(&self) -> &ArgParser {
+        &self.parser
+    }
+    
+    /// Runs the app with the program's arguments, returning the code
+    /// `run_with` returns for `main` to exit with. Codes that don't fit
+    /// in a byte become `ExitCode::FAILURE`.
+    pub fn run<F>(&self, main: F) -> ExitCode
+        where F: FnOnce(&ArgParseResults) -> Result<(), E> {
+        
+        let args: Vec<String> = env::args().collect();
+        let code = self.run_with(&args, main, &mut io::stdout(), &mut io::stderr());
+        u8::try_from(code).map_or(ExitCode::FAILURE, ExitCode::from)
+    }
+    
+    /// Parses the arguments, the first being the program name, and
+    /// calls `main` with the results after the `before_run` hooks. Help
+    /// and the version are written to `out` instead, and errors to
+    /// `err`. Returns the exit code: `0` on success and after help or
+    /// the version, `USAGE_ERROR` when the arguments fail to parse, and
+    /// what `exit_code` maps the error to when `main` fails.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, ColorChoice};
+    /// use argparse::app::{App, USAGE_ERROR};
+    ///
+    /// let mut parser = ArgParser::new("greet".into());
+    /// parser.color(ColorChoice::Never);
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of user", ArgType::Option);
+    ///
+    /// let mut app = App::new(parser);
+    /// app.version("1.0.0").exit_code(|_: &String| 3);
+    ///
+    /// let args = |line: &str| line.split_whitespace().map(|s| s.into()).collect::<Vec<String>>();
+    /// let (mut out, mut err) = (Vec::new(), Vec::new());
+    ///
+    /// assert_eq!(app.run_with(&args("./greet -V"), |_| Ok(()), &mut out, &mut err), 0);
+    /// assert_eq!(out, b"greet 1.0.0\n");
+    /// assert_eq!(app.run_with(&args("./greet"), |_| Ok(()), &mut out, &mut err), USAGE_ERROR);
+    /// assert_eq!(app.run_with(&args("./greet -n x"), |_| Err("no".into()), &mut out, &mut err), 3);
+    /// ```
+    pub fn run_with<F, O, W>(&self, args: &[String], main: F, out: &mut O, err: &mut W) -> i32
+        where F: FnOnce(&ArgParseResults) -> Result<(), E>, O: Write, W: Write {
+        
+        let p_res = match self.parser.parse(args.iter()) {
+            Ok(p_res) => p_res,
+            Err(e) => {
+                let _ = write!(err, "{}", e.render());
+                return USAGE_ERROR;
+            }
+        };
+        
+        match (p_res.terminated_by(), &self.version) {
+            (Some("help"), _) => {
+                let _ = self.parser.write_help(out);
+                return 0;
+            }
+            (Some("version"), Some(version)) => {
+                let _ = writeln!(out, "{} {}", self.parser.program(), version);
+                return 0;
+            }
+            _ => {}
+        }
+        
+        for hook in self.hooks.iter() {
+            hook(&p_res);
+        }
+        
+        match main(&p_res) {
+            Ok(()) => 0,
+            Err(e) => {
+                let _ = writeln!(err, "{}: {}", self.parser.catalog().error, e);
+                (self.exit_code)(&e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    
+    use super::{App, FAILURE, USAGE_ERROR};
+    use argparser::{ArgParser, ArgType};
+    use color::ColorChoice;
+    
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|s| s.into()).collect()
+    }
+    
+    fn setup() -> App<String> {
+        let mut parser = ArgParser::new("greet".into());
+        parser.color(ColorChoice::Never);
+        parser.add_opt("name", None, 'n', true, "Name of user", ArgType::Option);
+        parser.add_opt("verbose", Some("false"), 'v', false, "Verbose output", ArgType::Flag);
+        App::new(parser)
+    }
+    
+    #[test]
+    fn test_run_with() {
+        let mut app = setup();
+        let verbose = Rc::new(Cell::new(false));
+        let seen = verbose.clone();
+        app.before_run(move |p_res| seen.set(p_res.get("verbose").unwrap_or(false)));
+        
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let code = app.run_with(&args("./greet -v -n Johnny"), |p_res| {
+            match p_res.get::<String>("name") {
+                Some(ref name) if name == "Johnny" => Ok(()),
+                other => Err(format!("unexpected {:?}", other)),
+            }
+        }, &mut out, &mut err);
+        assert_eq!(code, 0);
+        assert!(verbose.get());
+        assert!(out.is_empty() && err.is_empty());
+        
+        let code = app.run_with(&args("./greet -n x"), |_| Err("it broke".into()), &mut out, &mut err);
+        assert_eq!(code, FAILURE);
+        assert_eq!(String::from_utf8_lossy(&err), "error: it broke\n");
+    }
+    
+    #[test]
+    fn test_help_and_version() {
+        let mut app = setup();
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let unreachable = |_: &_| Err("main ran".to_string());
+        
+        assert_eq!(app.run_with(&args("./greet --help"), unreachable, &mut out, &mut err), 0);
+        assert!(String::from_utf8_lossy(&out).starts_with("Usage:\t./greet"));
+        
+        // Without a version there is no flag for it
+        assert_eq!(app.run_with(&args("./greet -V"), unreachable, &mut out, &mut err), USAGE_ERROR);
+        assert!(String::from_utf8_lossy(&err).starts_with("error: "));
+        
+        app.version("2.1");
+        out.clear();
+        assert_eq!(app.run_with(&args("./greet --version"), unreachable, &mut out, &mut err), 0);
+        assert_eq!(String::from_utf8_lossy(&out), "greet 2.1\n");
+        assert!(app.parser().help_text().contains("--version (-V)"));
+    }
+}
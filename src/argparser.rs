@@ -3,22 +3,55 @@
 //! and functions are re-exported at the top-level of
 //! the crate.
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::env;
+use std::any::{self, TypeId};
+use std::error::Error;
+use std::ffi::OsStr;
 use std::fmt;
 use std::hash::{Hash};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
-use slide::{Slider};
+use color::{self, ColorChoice, Stream};
+use std::iter::{FromIterator, Peekable};
+
+use lexer::{self, Flag, Lexer, Token};
+use messages::Messages;
+
+#[cfg(feature = "url")]
+use url::Url;
+#[cfg(feature = "json")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Duration as Days, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime};
 
 /// This enum represents the different types of arguments supported
 #[derive(Debug, Clone, PartialEq)]
 pub enum ArgType {
-    /// An argument that takes a value, as in `./go --pic lol.jpg`
+    /// An argument that takes a value, as in `./go --pic lol.jpg`. A
+    /// value that looks like a flag can be given attached with `=`, as
+    /// in `./go --pic=-lol.jpg`; attached values are always values.
     Option,
     /// An argument that is a simple flag, as in `rustc --version`
     Flag,
+    /// A flag that counts how many times it is given, as in `ssh -vvv`.
+    /// `get::<u32>` returns the count, which is 0 if it isn't given
+    /// and has no default.
+    Counter,
     /// Like an `Option`, but takes multiple values, as in 
-    /// `./go --pics 1.png 2.png 3.png`
+    /// `./go --pics 1.png 2.png 3.png`. When its values run to the end
+    /// of the command line, the last ones are left to the `Positional`
+    /// arguments without a default that weren't given yet, as in
+    /// `./go --pics 1.png 2.png out.pdf`, though it always keeps at
+    /// least one.
     List,
     /// Like a `List` but takes colon-split key-value pairs, as in
     /// `./go --pics Monday:1.jpg Tuesday:2.jpg`
@@ -27,25 +60,273 @@ pub enum ArgType {
     /// The relative position of the position argument (i.e. `Positional(0)`
     /// indicates that this is the first positional argument
     Positional(u8),
+    /// Every positional argument in a segment of the command line that
+    /// isn't taken by a `Positional`, as in `tool <inputs>... -- <outputs>...`.
+    /// The u8 is the segment: 0 for the arguments before a `--`, 1 for
+    /// those after it.
+    PositionalList(u8),
+    /// A command to run, taking every argument after a `--`, or after the
+    /// first positional argument no `Positional` takes, exactly as they
+    /// were given, as in `timeout 5s sleep 10` or `cargo run -- --help`.
+    /// It can also be given by its flag, which takes every argument
+    /// after it. Read it with `get_command`, or `get_list` for the
+    /// arguments.
+    Command,
+    /// Every argument from the first one the parser doesn't recognize,
+    /// an unknown flag or a positional argument no `Positional` takes,
+    /// as in `ssh host -t top`. It can also be given by its flag, which
+    /// takes every argument after it. Read it with `values` or
+    /// `get_list`.
+    Remainder,
+    /// Like an `Option`, but its value is a path, read with
+    /// `get::<PathBuf>`. See `ArgParser::path_options` for normalizing it.
+    Path,
+    /// Like an `Option`, but its value must be valid JSON, as in
+    /// `./go --payload '{"id": 1}'`. Read it with `get_json`. Requires
+    /// the `json` feature; without it, giving the argument fails to
+    /// parse, as its value can't be checked.
+    Json,
+}
+
+/// What to do when a `Dict` argument is given the same key more than
+/// once, whether in one occurrence or across several, as in
+/// `-D a:1 -D a:2`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    /// Fail to parse
+    Error,
+    /// Keep the first value given
+    FirstWins,
+    /// Keep the last value given
+    #[default]
+    LastWins,
+    /// Keep every pair given, so that `get_multimap` sees each value of
+    /// a repeated key
+    Keep,
+}
+
+/// What to do when an argument is given more often than
+/// `ArgParser::max_occurrences` allows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TooMany {
+    /// Fail to parse
+    #[default]
+    Error,
+    /// Add a warning to the results, and read the argument as usual
+    Warn,
+}
+
+/// How many of the values following a `List` or `Dict` argument it
+/// takes each time it is given
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListValues {
+    /// All of them, up to the next flag. Each occurrence of a `List`
+    /// replaces the values of the one before.
+    #[default]
+    Greedy,
+    /// No more than this many, leaving the rest for positional
+    /// arguments. Occurrences add to each other's values, so `AtMost(1)`
+    /// reads `-f a -f b` as `a b`.
+    AtMost(usize),
+}
+
+/// How the value of a `Path` argument is normalized while parsing, so
+/// that every consumer sees the same path. Nothing is done by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PathOptions {
+    /// Replace a leading `~` with the user's home directory, also in
+    /// values attached as `--path=~/x`, which shells leave alone. The
+    /// home directories of other users can't be looked up reliably, so
+    /// `~user` is an error, as is `~` when the home directory is unknown.
+    pub expand_tilde: bool,
+    /// Make relative paths absolute, against the current directory
+    pub absolute: bool,
+    /// Resolve the path on the file system, following symbolic links.
+    /// The path must exist.
+    pub canonicalize: bool,
+}
+
+/// How one call to `ArgParser::parse_with` reads its arguments, so that
+/// the same definitions can be parsed strictly in one place and
+/// leniently in another. The default is what `parse` does, unless
+/// changed with `ArgParser::options`.
+/// # Example
+/// ```
+/// use argparse::{ArgParser, ArgType, ParseOptions};
+///
+/// let mut parser = ArgParser::new("runner".into());
+/// parser.add_opt("verbose", Some("false"), 'v', false,
+///     "Whether to produce verbose output", ArgType::Flag);
+///
+/// let test_1 = "./runner --VERB".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// assert_eq!(parser.parse(test_1.iter()).unwrap().get("verbose"), Some(false));
+///
+/// let opts = ParseOptions { abbreviations: true, case_sensitive: false, ..ParseOptions::default() };
+/// assert_eq!(parser.parse_with(test_1.iter(), opts).unwrap().get("verbose"), Some(true));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Fail on flags that no argument has, instead of skipping them
+    /// with a warning
+    pub strict: bool,
+    /// Accept any unambiguous start of a long name, as in `--verb` for
+    /// `--verbose`. An ambiguous one, such as `--ver` when there is also
+    /// `--version`, fails with the flags it could be.
+    pub abbreviations: bool,
+    /// Whether flags must match the case they were defined with. Even
+    /// when not, a flag matching exactly wins over one matching in
+    /// another case.
+    pub case_sensitive: bool,
+    /// Whether flags may come after positional arguments. When not, the
+    /// first positional ends the flags, and everything from it on is
+    /// positional, as POSIX `getopt` does.
+    pub permute: bool,
+    /// Colors errors by this choice instead of the parser's
+    pub color: Option<ColorChoice>,
+    /// Whether arguments like `-60` are numbers or flags
+    pub negative_numbers: NegativeNumbers,
+    /// Remove quotes the arguments kept, with `lexer::unquote`, before
+    /// reading them. Only `parse` and `parse_with` do so: `parse_line`
+    /// already removes quotes while splitting the line, and
+    /// `parse_borrowed` can't change the arguments it borrows.
+    pub unquote: bool,
+    /// Which values have `${VAR}` replaced with environment variables
+    pub interpolate: Interpolate,
+    /// Whether the underscores of names like `max_retries` become
+    /// dashes in their long flags, as in `--max-retries`
+    pub kebab_case: KebabCase,
 }
 
-impl ArgType {
-    fn is_positional(&self) -> bool {
+/// Which values have `${VAR}` replaced with the value of the environment
+/// variable `VAR`, as in a default of `${HOME}/.cache/tool`. `$${`
+/// stands for a literal `${`. A variable that isn't set fails the parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolate {
+    /// None of them
+    #[default]
+    Never,
+    /// Defaults only, as those are written by the program rather than
+    /// typed by the user
+    Defaults,
+    /// Defaults, and the arguments given to `parse` or `parse_with`
+    /// (like `unquote`, not those given to `parse_line` or
+    /// `parse_borrowed`)
+    All,
+}
+
+/// Whether arguments that look like negative numbers, such as `-60` or
+/// `-6001.45e-2`, are values or short flags (`-6`, `-0`). Either way,
+/// values attached with `=` and arguments after `--` are always values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NegativeNumbers {
+    /// They are always values
+    #[default]
+    Values,
+    /// They are always flags
+    Flags,
+    /// They are values for the arguments marked with
+    /// `ArgParser::negative_numbers`, which would take them, and flags
+    /// everywhere else
+    PerArg,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            strict: false,
+            abbreviations: false,
+            case_sensitive: true,
+            permute: true,
+            color: None,
+            negative_numbers: NegativeNumbers::Values,
+            unquote: false,
+            interpolate: Interpolate::Never,
+            kebab_case: KebabCase::Off,
+        }
+    }
+}
+
+/// How the long flags of arguments are derived from their names, so that
+/// arguments can be named like Rust identifiers, `max_retries`, and given
+/// the way command lines usually are, `--max-retries`. The parser's own
+/// setting also decides how help and the other generated text show
+/// long flags.
+/// # Example
+/// ```
+/// use argparse::{ArgParser, ArgType, KebabCase, ParseOptions};
+///
+/// let mut parser = ArgParser::new("fetch".into());
+/// parser.add_opt("max_retries", Some("3"), 'r', false,
+///     "How often to retry", ArgType::Option);
+/// parser.options(ParseOptions { kebab_case: KebabCase::Both, ..ParseOptions::default() });
+///
+/// assert_eq!(parser.parse_line("--max-retries 5").unwrap().get("max_retries"), Some(5));
+/// assert_eq!(parser.parse_line("--max_retries 6").unwrap().get("max_retries"), Some(6));
+/// assert!(parser.help_text().contains("--max-retries (-r)"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KebabCase {
+    /// Long flags are the names as they were given
+    #[default]
+    Off,
+    /// Long flags have dashes for underscores, and only those are
+    /// accepted
+    On,
+    /// Long flags have dashes for underscores, but the names as they
+    /// were given are accepted too
+    Both,
+}
+
+impl KebabCase {
+    /// The long flag shown for an argument, without the leading dashes
+    fn flag(self, name: &str) -> Cow<'_, str> {
         match self {
-            &ArgType::Positional(_) => true,
-            _ => false,
+            KebabCase::Off => Cow::Borrowed(name),
+            KebabCase::On | KebabCase::Both => Cow::Owned(name.replace('_', "-")),
         }
     }
 }
 
+/// Which settings `ArgParser::inherit` copies from a parent parser, such
+/// as the one dispatching to a subcommand's parser. All of them by
+/// default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Inherit {
+    /// The `ColorChoice`
+    pub color: bool,
+    /// The `Messages` catalog
+    pub messages: bool,
+    /// Whether help is paged
+    pub pager: bool,
+    /// The width help is wrapped to and the column descriptions start at
+    pub layout: bool,
+    /// The `ParseOptions` arguments are read with
+    pub options: bool,
+}
+
+impl Default for Inherit {
+    fn default() -> Inherit {
+        Inherit { color: true, messages: true, pager: true, layout: true, options: true }
+    }
+}
+
 impl fmt::Display for ArgType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let msg = match self {
-            &ArgType::Option => "Option",
-            &ArgType::Flag => "Flag",
-            &ArgType::List => "List",
-            &ArgType::Dict => "Dict",
-            &ArgType::Positional(_) => "Positional"
+        let msg = match *self {
+            ArgType::Option => "Option",
+            ArgType::Flag => "Flag",
+            ArgType::Counter => "Counter",
+            ArgType::List => "List",
+            ArgType::Dict => "Dict",
+            ArgType::Positional(_) => "Positional",
+            ArgType::PositionalList(_) => "PositionalList",
+            ArgType::Command => "Command",
+            ArgType::Remainder => "Remainder",
+            ArgType::Path => "Path",
+            ArgType::Json => "Json",
         };
         
         write!(f, "{}", msg)
@@ -54,44 +335,753 @@ impl fmt::Display for ArgType {
 
 #[derive(Debug, Clone)]
 struct Arg {
-    val: Option<String>,
-    count: u16,
+    name: String,
+    default: Option<String>,
     required: bool,
     flag: char,
     help: String,
+    long_help: Option<String>,
+    order: Option<u32>,
+    visible: Option<Condition>,
+    tag: Option<TypeTag>,
+    deprecated: Option<String>,
+    exclusive: bool,
+    terminates: bool,
+    overrides: Vec<String>,
+    max_occurrences: Option<(u16, TooMany)>,
+    choices: Vec<String>,
+    ignore_case: bool,
+    terminator: Option<String>,
+    list_values: ListValues,
+    negative: bool,
+    joiner: Option<String>,
     type_: ArgType,
+    validator: Option<Validator>,
+    duplicates: DuplicateKeys,
+    paths: PathOptions,
+    #[cfg(feature = "glob")]
+    globs: bool,
+}
+
+/// A check every value given for an argument must pass, returning why
+/// a value is rejected
+#[derive(Clone)]
+struct Validator(Arc<CheckFn>);
+
+type CheckFn = dyn Fn(&str) -> Result<(), String> + Send + Sync;
+
+impl fmt::Debug for Validator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Validator")
+    }
+}
+
+/// The type an argument's values are declared to be read as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TypeTag {
+    id: TypeId,
+    name: &'static str,
+}
+
+impl TypeTag {
+    fn of<T: 'static>() -> TypeTag {
+        TypeTag { id: TypeId::of::<T>(), name: any::type_name::<T>() }
+    }
+}
+
+/// A condition checked each time help is generated, such as whether an
+/// environment variable is set
+#[derive(Clone)]
+struct Condition(Arc<dyn Fn() -> bool + Send + Sync>);
+
+impl fmt::Debug for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Condition")
+    }
+}
+
+/// Middleware rewriting the arguments before they are read, see
+/// `ArgParser::before_parse`
+#[derive(Clone)]
+struct Rewrite(Arc<RewriteFn>);
+
+type RewriteFn = dyn Fn(&mut Vec<String>) + Send + Sync;
+
+impl fmt::Debug for Rewrite {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Rewrite")
+    }
+}
+
+/// Middleware checking or adding to the results, see
+/// `ArgParser::after_parse`
+#[derive(Clone)]
+struct Finish(Arc<FinishFn>);
+
+type FinishFn = dyn Fn(&mut ArgParseResults) -> Result<(), String> + Send + Sync;
+
+impl fmt::Debug for Finish {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Finish")
+    }
+}
+
+/// A map from names or short flags to the index of their argument,
+/// with a faster hasher than the default with the `rustc-hash` feature
+#[cfg(feature = "rustc-hash")]
+type Index<K> = HashMap<K, usize, rustc_hash::FxBuildHasher>;
+#[cfg(not(feature = "rustc-hash"))]
+type Index<K> = HashMap<K, usize>;
+
+/// The registered arguments, kept in the order they were added. They
+/// are shared between a parser and the results it produces, so that
+/// parsing doesn't need to copy them.
+#[derive(Debug, Clone, Default)]
+struct Definitions {
+    args: Vec<Arg>,
+    names: Index<String>,
+    shorts: Index<char>,
+    /// The names that have underscores, with dashes in their place, for
+    /// `KebabCase`
+    kebab: Index<String>,
+}
+
+impl Definitions {
+    /// Adds an argument, replacing any existing one of the same name in
+    /// its place
+    fn insert(&mut self, arg: Arg) {
+        if let Some(&idx) = self.names.get(&arg.name) {
+            // The old short flag may have been this argument's alone, and
+            // the new one goes to it over any other
+            let flag = short(&arg);
+            self.args[idx] = arg;
+            self.reindex();
+            if let Some(flag) = flag {
+                self.shorts.insert(flag, idx);
+            }
+            return;
+        }
+        
+        let idx = self.args.len();
+        self.names.insert(arg.name.clone(), idx);
+        if let Some(flag) = short(&arg) {
+            self.shorts.insert(flag, idx);
+        }
+        if arg.name.contains('_') {
+            self.kebab.insert(arg.name.replace('_', "-"), idx);
+        }
+        self.args.push(arg);
+    }
+    
+    fn remove(&mut self, name: &str) -> Option<Arg> {
+        let idx = self.names.remove(name)?;
+        let arg = self.args.remove(idx);
+        self.reindex();
+        
+        Some(arg)
+    }
+    
+    /// Rebuilds the maps from the arguments, after they were moved
+    fn reindex(&mut self) {
+        self.names.clear();
+        self.shorts.clear();
+        self.kebab.clear();
+        for (i, a) in self.args.iter().enumerate() {
+            self.names.insert(a.name.clone(), i);
+            if let Some(flag) = short(a) {
+                self.shorts.insert(flag, i);
+            }
+            if a.name.contains('_') {
+                self.kebab.insert(a.name.replace('_', "-"), i);
+            }
+        }
+    }
+}
+
+/// What parsing found for one argument. `List` and `Dict` arguments
+/// keep each of their values separately, and `origin` is the position
+/// of the argument the first of them was read from.
+#[derive(Debug, Clone, Default)]
+struct Value {
+    vals: Option<Vec<String>>,
+    pairs: Option<Vec<(String, String)>>,
+    count: u16,
+    origin: Option<usize>,
+}
+
+/// Like `Value`, but borrowing from the arguments where it can. Values
+/// rewritten while parsing, such as expanded globs, are owned.
+#[derive(Debug, Clone, Default)]
+struct Found<'a> {
+    vals: Option<Vec<Cow<'a, str>>>,
+    pairs: Option<Vec<(&'a str, &'a str)>>,
+    count: u16,
+    origin: Option<usize>,
+}
+
+/// The value as it is handed to `get` and `get_with`, which for `List`
+/// and `Dict` arguments is each value followed by a space
+fn joined<'s, S: AsRef<str>>(vals: &'s [S], type_: &ArgType) -> Cow<'s, str> {
+    match *type_ {
+        ArgType::List | ArgType::Dict | ArgType::PositionalList(_) | ArgType::Command | ArgType::Remainder => {
+            Cow::Owned(vals.iter().fold(String::new(), |mut acc, elem| {
+                acc.push_str(elem.as_ref());
+                acc.push(' ');
+                acc
+            }))
+        }
+        _ => Cow::Borrowed(vals.first().map_or("", |v| v.as_ref())),
+    }
+}
+
+/// The name of a program, from the path it was invoked by
+fn program_name(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_string_lossy();
+    
+    match name.strip_suffix(".exe") {
+        Some(stem) if !stem.is_empty() => Some(stem.into()),
+        _ => Some(name.into_owned()),
+    }
+}
+
+/// A command to run, from the program and arguments it was given as
+fn to_command(argv: Vec<String>) -> Option<Command> {
+    let (program, args) = argv.split_first()?;
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    
+    Some(cmd)
+}
+
+/// Parses the key-value pairs of a `Dict`, or those of its default when
+/// it wasn't given, into a map or, keeping their order, a `Vec`
+fn parse_dict<K, V, S, C>(pairs: Option<&[(S, S)]>, default: Option<&String>) -> Option<C>
+    where K: FromStr,
+          V: FromStr,
+          S: AsRef<str>,
+          C: FromIterator<(K, V)> {
+    let parse = |k: &str, v: &str| k.parse().ok().and_then(|k| v.parse().ok().map(|v| (k, v)));
+    
+    match pairs {
+        Some(pairs) => pairs.iter().map(|(k, v)| parse(k.as_ref(), v.as_ref())).collect(),
+        None => default.and_then(|d| {
+            d.split_whitespace()
+                .map(|p| p.split_once(':').and_then(|(k, v)| parse(k, v)))
+                .collect()
+        }),
+    }
+}
+
+/// Gathers key-value pairs into a map from each key to all of its values,
+/// splitting the values at commas. Returns `None` if a pair is missing
+/// or anything fails to parse.
+fn multimap<K, V, S, I>(pairs: I) -> Option<HashMap<K, Vec<V>>>
+    where K: FromStr + Hash + Eq,
+          V: FromStr,
+          S: AsRef<str>,
+          I: Iterator<Item = Option<(S, S)>> {
+    let mut map: HashMap<K, Vec<V>> = HashMap::new();
+    
+    for pair in pairs {
+        let (k, v) = pair?;
+        let vals = map.entry(k.as_ref().parse().ok()?).or_default();
+        for v in v.as_ref().split(',').filter(|v| !v.is_empty()) {
+            vals.push(v.parse().ok()?);
+        }
+    }
+    
+    Some(map)
+}
+
+/// Parses the key-value pairs of a `Dict` into a multimap, or those of
+/// its default when it wasn't given
+fn parse_multimap<K, V, S>(pairs: Option<&[(S, S)]>, default: Option<&String>) -> Option<HashMap<K, Vec<V>>>
+    where K: FromStr + Hash + Eq,
+          V: FromStr,
+          S: AsRef<str> {
+    match pairs {
+        Some(pairs) => multimap(pairs.iter().map(|(k, v)| Some((k, v)))),
+        None => default.and_then(|d| multimap(d.split_whitespace().map(|p| p.split_once(':')))),
+    }
+}
+
+/// A value of a `Dict` argument whose keys have been split at dots into
+/// paths, as returned by `get_nested`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DictNode {
+    /// The value given for a full path
+    Leaf(String),
+    /// The values under a path prefix, by the next part of the path
+    Branch(BTreeMap<String, DictNode>),
+}
+
+impl DictNode {
+    /// Follows a dotted `path` down from this node
+    pub fn get(&self, path: &str) -> Option<&DictNode> {
+        path.split('.').try_fold(self, |node, key| match *node {
+            DictNode::Branch(ref map) => map.get(key),
+            DictNode::Leaf(_) => None,
+        })
+    }
+    
+    /// The value of a leaf, or `None` for a branch
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            DictNode::Leaf(ref s) => Some(s),
+            DictNode::Branch(_) => None,
+        }
+    }
+}
+
+/// Builds the tree `get_nested` returns out of key-value pairs, or the
+/// pairs in the default when none were given
+fn nest<S: AsRef<str>>(pairs: Option<&[(S, S)]>, default: Option<&String>) -> Option<DictNode> {
+    let split: Vec<(&str, &str)> = match pairs {
+        Some(pairs) => pairs.iter().map(|(k, v)| (k.as_ref(), v.as_ref())).collect(),
+        None => default?.split_whitespace()
+            .map(|p| p.split_once(':'))
+            .collect::<Option<_>>()?,
+    };
+    
+    let mut root = DictNode::Branch(BTreeMap::new());
+    
+    for (key, val) in split {
+        if key.split('.').any(str::is_empty) {
+            return None;
+        }
+        
+        let mut node = &mut root;
+        for part in key.split('.') {
+            if let DictNode::Leaf(_) = *node {
+                *node = DictNode::Branch(BTreeMap::new());
+            }
+            
+            node = match *node {
+                DictNode::Branch(ref mut map) => map.entry(part.into())
+                    .or_insert_with(|| DictNode::Branch(BTreeMap::new())),
+                DictNode::Leaf(_) => return None,
+            };
+        }
+        
+        *node = DictNode::Leaf(val.into());
+    }
+    
+    Some(root)
+}
+
+/// Parses every value of a `List` with `parse`, or the whitespace-separated
+/// parts of its default when it wasn't given
+fn parse_list<T, S, F>(vals: Option<&[S]>, default: Option<&String>, parse: F) -> Option<Vec<T>>
+    where S: AsRef<str>,
+          F: Fn(&str) -> Option<T> {
+    match vals {
+        Some(vals) => vals.iter().map(|v| parse(v.as_ref())).collect(),
+        None => default.and_then(|d| d.split_whitespace().map(&parse).collect()),
+    }
 }
 
 #[derive(Debug, Clone)]
 /// This type represents the state and methods for parsing arguments.
-/// A new parser must be created for every set of arguments you want to parse.
+/// Parsing does not change the parser, so one parser can parse as many
+/// sets of arguments as you want. It is `Send + Sync`, and can be shared
+/// between threads through a `SharedParser`.
 pub struct ArgParser {
-    arguments: HashMap<String, Arg>,
+    defs: Arc<Definitions>,
     name: String,
-    done: bool,
+    color: ColorChoice,
+    messages: Messages,
+    pager: bool,
+    max_width: Option<usize>,
+    title: Option<String>,
+    description: Option<String>,
+    examples: Vec<(String, String)>,
+    help_column: Option<usize>,
+    options: ParseOptions,
+    groups: Vec<Group>,
+    rewrites: Vec<Rewrite>,
+    finishers: Vec<Finish>,
+}
+
+/// Arguments of which at least `min` must be given
+#[derive(Debug, Clone)]
+struct Group {
+    min: usize,
+    names: Vec<String>,
 }
 
 /// Simple type alias to reduce typing. The return type of
 /// `ArgParser::parse`.
-pub type ParseResult = Result<ArgParseResults, String>;
+pub type ParseResult = Result<ArgParseResults, ParseError>;
+
+/// The error produced when a set of arguments fails to parse. Besides
+/// the message, it remembers the command line it came from and which
+/// argument was at fault, so that it can be rendered in the style of
+/// a compiler diagnostic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    msg: String,
+    argv: Vec<String>,
+    pos: Option<usize>,
+    color: ColorChoice,
+    label: String,
+}
+
+impl ParseError {
+    fn new(msg: String, argv: &[String], pos: Option<usize>, parser: &ArgParser) -> ParseError {
+        ParseError {
+            msg,
+            argv: argv.to_vec(),
+            pos,
+            color: parser.color,
+            label: parser.messages.error.to_string(),
+        }
+    }
+
+    /// The plain error message, without any rendering
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
+
+    /// The index into the parsed arguments of the argument at fault,
+    /// if the error can be blamed on a single one
+    pub fn position(&self) -> Option<usize> {
+        self.pos
+    }
+
+    /// Renders the error along with the command line that produced it,
+    /// pointing at the offending argument. If no single argument is at
+    /// fault, the pointer is placed at the end of the line. Color is
+    /// applied according to the parser's `ColorChoice`, as for help.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, ColorChoice};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.color(ColorChoice::Never);
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of user", ArgType::Option);
+    ///
+    /// let test_1 = "./runner --name".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// let err = parser.parse(test_1.iter()).unwrap_err();
+    /// assert!(err.render().ends_with("./runner --name\n           ^^^^^^\n"));
+    /// ```
+    pub fn render(&self) -> String {
+        let paint = self.color.enabled(Stream::Stderr);
+        let mut line = String::new();
+        let mut marker = String::new();
+
+        for (i, arg) in self.argv.iter().enumerate() {
+            let shown = if arg.is_empty() || arg.contains(char::is_whitespace) {
+                format!("'{}'", arg)
+            } else {
+                arg.clone()
+            };
+            let width = display_width(&shown);
+
+            if i > 0 {
+                line.push(' ');
+                marker.push(' ');
+            }
+
+            if self.pos == Some(i) {
+                line.push_str(&color::paint(&shown, color::RED, paint));
+                marker.extend(::std::iter::repeat_n('^', width));
+                break;
+            }
+
+            line.push_str(&shown);
+            marker.extend(::std::iter::repeat_n(' ', width));
+        }
+
+        if self.pos.is_none_or(|p| p >= self.argv.len()) {
+            marker.push_str(" ^");
+        }
+
+        let marker = marker.trim_end();
+        let style = format!("{}{}", color::BOLD, color::RED);
+
+        format!("{}: {}\n  {}\n  {}\n",
+            color::paint(&self.label, &style, paint),
+            color::paint(&self.msg, color::BOLD, paint),
+            line,
+            color::paint(marker, &style, paint))
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl Error for ParseError {}
+
+/// A mistake in the definition of a parser, found by `ArgParser::validate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// Two arguments share a short flag, so only the last one added
+    /// can be given by it
+    DuplicateShort {
+        /// The short flag
+        flag: char,
+        /// The arguments sharing it, in the order they were added
+        names: (String, String),
+    },
+    /// Two `Positional` arguments have the same index
+    DuplicatePosition {
+        /// The index
+        index: u8,
+        /// The arguments sharing it, in the order they were added
+        names: (String, String),
+    },
+    /// No `Positional` argument has this index, though some have a
+    /// higher one, which can then never be given
+    PositionalGap {
+        /// The missing index
+        index: u8,
+    },
+    /// A required argument has a default, so it can't be missing
+    RequiredWithDefault {
+        /// The argument
+        name: String,
+    },
+    /// A group refers to an argument that has since been removed
+    UnknownInGroup {
+        /// The removed argument
+        name: String,
+    },
+    /// A group requires more of its members than it has
+    UnsatisfiableGroup {
+        /// How many are required
+        min: usize,
+        /// The members
+        names: Vec<String>,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::DuplicateShort { flag, names: (ref a, ref b) } =>
+                write!(f, "`{}` and `{}` both use the short flag `-{}`", a, b, flag),
+            ConfigError::DuplicatePosition { index, names: (ref a, ref b) } =>
+                write!(f, "`{}` and `{}` are both positional argument {}", a, b, index),
+            ConfigError::PositionalGap { index } =>
+                write!(f, "there is no positional argument {}, though there are later ones", index),
+            ConfigError::RequiredWithDefault { ref name } =>
+                write!(f, "`{}` is required but has a default", name),
+            ConfigError::UnknownInGroup { ref name } =>
+                write!(f, "a group refers to `{}`, which doesn't exist", name),
+            ConfigError::UnsatisfiableGroup { min, ref names } =>
+                write!(f, "a group requires {} of {}, which is more than it has", min, listed(names)),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Something worth telling the user about arguments that still parsed,
+/// see `ArgParseResults::warnings`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// An argument marked with `ArgParser::deprecated` was given
+    Deprecated {
+        /// The argument
+        name: String,
+        /// What to do instead
+        note: String,
+    },
+    /// An argument taking values was given again, replacing the
+    /// values given before
+    Overridden {
+        /// The argument
+        name: String,
+        /// Where it was given again
+        position: usize,
+    },
+    /// An argument was given more often than `max_occurrences` allows
+    TooMany {
+        /// The argument
+        name: String,
+        /// How often it may be given
+        max: u16,
+        /// Where it was given once too often
+        position: usize,
+    },
+    /// A flag no argument has was skipped over
+    UnknownFlag {
+        /// The flag, with its dashes
+        flag: String,
+        /// Where it was given
+        position: usize,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Warning::Deprecated { ref name, ref note } => write!(f, "`{}` is deprecated: {}", name, note),
+            Warning::Overridden { ref name, .. } =>
+                write!(f, "`{}` was given more than once, so only the last value is used", name),
+            Warning::TooMany { ref name, max, .. } =>
+                write!(f, "`{}` was given more than {} times", name, max),
+            Warning::UnknownFlag { ref flag, .. } => write!(f, "`{}` is not a known flag, so it was ignored", flag),
+        }
+    }
+}
+
+/// Something found while reading a command line, see
+/// `ArgParser::parse_iter`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseEvent<'a> {
+    /// A flag of an argument, with the values it took. A `Command` or
+    /// `Remainder` argument is matched by the first value it takes too.
+    ArgMatched {
+        /// The name of the argument
+        name: &'a str,
+        /// Its values, attached with `=` or following its flag
+        values: Vec<&'a str>,
+        /// The index of the flag, or first value, in the arguments
+        position: usize,
+    },
+    /// A value that no flag took
+    PositionalFound {
+        /// The value
+        value: &'a str,
+        /// Its index in the arguments
+        position: usize,
+    },
+    /// A flag no argument has, as it was given
+    Unknown {
+        /// The flag, with its dashes
+        flag: String,
+        /// Its index in the arguments
+        position: usize,
+    },
+    /// Why reading could not go on. It is the last event.
+    Error(ParseError),
+}
+
+/// Why `try_get` couldn't read an argument
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GetError {
+    /// There is no argument by that name
+    NoSuchArg(String),
+    /// The argument wasn't given, and has no default
+    Missing(String),
+    /// The argument was declared with `value_type` as a different type
+    WrongType {
+        /// The argument
+        name: String,
+        /// The type it was declared as
+        declared: &'static str,
+        /// The type it was read as
+        requested: &'static str,
+    },
+    /// The value couldn't be parsed as the type asked for
+    Invalid {
+        /// The argument
+        name: String,
+        /// Its value
+        value: String,
+        /// The type it was read as
+        requested: &'static str,
+    },
+}
+
+impl fmt::Display for GetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GetError::NoSuchArg(ref name) => write!(f, "there is no argument `{}`", name),
+            GetError::Missing(ref name) => write!(f, "`{}` was not given and has no default", name),
+            GetError::WrongType { ref name, declared, requested } =>
+                write!(f, "`{}` holds a `{}`, but was read as a `{}`", name, declared, requested),
+            GetError::Invalid { ref name, ref value, requested } =>
+                write!(f, "the value `{}` of `{}` is not a valid `{}`", value, name, requested),
+        }
+    }
+}
+
+impl Error for GetError {}
+
+/// Reads the value of an argument as a `T`, checking `T` against the
+/// type the argument was declared as
+fn read<T: FromStr + 'static>(def: &Arg, value: Option<Cow<str>>) -> Result<T, GetError> {
+    check_tag::<T>(def)?;
+    
+    let value = value.ok_or_else(|| GetError::Missing(def.name.clone()))?;
+    value.parse().map_err(|_| GetError::Invalid {
+        name: def.name.clone(),
+        value: value.into_owned(),
+        requested: any::type_name::<T>(),
+    })
+}
+
+/// Whether an argument may be read as a `T`, given the type it was declared as
+fn check_tag<T: 'static>(def: &Arg) -> Result<(), GetError> {
+    match def.tag {
+        Some(tag) if tag.id != TypeId::of::<T>() => Err(GetError::WrongType {
+            name: def.name.clone(),
+            declared: tag.name,
+            requested: any::type_name::<T>(),
+        }),
+        _ => Ok(()),
+    }
+}
 
 impl ArgParser {
     /// Constructs a new `ArgParser`, given the name of the program
     /// that you want to be printed in help messages
     pub fn new(name: String) -> ArgParser {
         let mut me = ArgParser {
-            arguments: HashMap::new(),
-            name: name,
-            done: false,
+            defs: Arc::new(Definitions::default()),
+            name,
+            color: ColorChoice::Auto,
+            messages: Messages::default(),
+            pager: false,
+            max_width: None,
+            title: None,
+            description: None,
+            examples: Vec::new(),
+            help_column: None,
+            options: ParseOptions::default(),
+            groups: Vec::new(),
+            rewrites: Vec::new(),
+            finishers: Vec::new(),
         };
 
+        let help = me.messages.help.clone();
         me.add_opt("help", Some("false"), 'h', false, 
-            "Show this help message", ArgType::Flag);
+            &help, ArgType::Flag);
         
         me
     }
     
-    /// Add another option to parse.
+    /// Constructs a new `ArgParser` named after the file the program
+    /// was invoked as, the last part of the first command line
+    /// argument, so that renamed or symlinked binaries show the name
+    /// they were run by. An `.exe` extension is left out.
+    /// # Example
+    /// ```
+    /// use argparse::ArgParser;
+    ///
+    /// let parser = ArgParser::from_argv0();
+    /// assert!(!parser.help_text().is_empty());
+    /// ```
+    pub fn from_argv0() -> ArgParser {
+        let argv0 = env::args_os().next().map(PathBuf::from);
+        let exe = || env::current_exe().ok();
+        
+        ArgParser::new(argv0.or_else(exe).as_deref()
+            .and_then(program_name)
+            .unwrap_or_else(|| "program".into()))
+    }
+    
+    /// Add another option to parse. A `flag` of `'\0'` gives it no
+    /// short form, so it is only given by its long one.
     /// # Example
     /// ```
     /// // add an option that is a `Flag`, with no default value, with
@@ -103,563 +1093,7200 @@ impl ArgParser {
     /// let mut parser = ArgParser::new("runner".into());
     /// parser.add_opt("verbose", Some("false"), 'v', false,
     ///     "Whether to produce verbose output", ArgType::Flag);
+    /// parser.add_opt("quiet", Some("false"), '\0', false,
+    ///     "Whether to print nothing", ArgType::Flag);
+    ///
+    /// assert!(parser.help_text().contains("--quiet "));
+    /// assert_eq!(parser.parse_line("-q").unwrap().warnings().len(), 1);
     /// ```
     pub fn add_opt(&mut self, name: &str, 
         default: Option<&str>, flag: char, required: bool, 
         help: &str, type_: ArgType) {
         
         let o = Arg {
-            val: default.map(|x| x.into()), 
-            count: 0, 
-            required: required,
-            flag: flag,
+            name: name.into(),
+            default: stored_default(&type_, default), 
+            required,
+            flag,
             help: help.into(),
-            type_: type_,
+            long_help: None,
+            order: None,
+            visible: None,
+            tag: None,
+            deprecated: None,
+            exclusive: false,
+            terminates: false,
+            overrides: Vec::new(),
+            max_occurrences: None,
+            choices: Vec::new(),
+            ignore_case: false,
+            terminator: None,
+            list_values: ListValues::Greedy,
+            negative: false,
+            joiner: None,
+            type_,
+            validator: None,
+            duplicates: DuplicateKeys::default(),
+            paths: PathOptions::default(),
+            #[cfg(feature = "glob")]
+            globs: false,
         };
         
-        self.arguments.insert(name.into(), o);
+        Arc::make_mut(&mut self.defs).insert(o);
     }
     
-    /// Remove an option from parsing consideration.
+    /// The definition of an argument, for changing its settings
+    fn arg_mut(&mut self, name: &str) -> Result<&mut Arg, &'static str> {
+        let defs = Arc::make_mut(&mut self.defs);
+        
+        match defs.names.get(name) {
+            Some(&idx) => Ok(&mut defs.args[idx]),
+            None => Err("No such Option"),
+        }
+    }
+    
+    /// Set extended help for an argument, shown after its one-line help
+    /// in `long_help_text` but not in the normal help.
     /// # Example
     /// ```
-    /// // add an option that is a `Flag`, with no default value, with
-    /// // a long form of `--verbose`, short form of `v`, that is not
-    /// // required to be passed, and has a default value of `false`
-    ///
     /// use argparse::{ArgParser, ArgType};
     ///
-    /// let mut parser = ArgParser::new("runner".into());
-    /// parser.add_opt("verbose", Some("false"), 'v', false,
-    ///     "Whether to produce verbose output", ArgType::Flag);
-    /// assert!(parser.remove_opt("verbose").is_ok())
+    /// let mut parser = ArgParser::new("server".into());
+    /// parser.add_opt("listen", Some("127.0.0.1:8080"), 'l', false,
+    ///     "Address to listen on", ArgType::Option);
+    /// parser.long_help("listen", "Use 0.0.0.0 to accept connections from other machines").unwrap();
+    ///
+    /// assert!(!parser.help_text().contains("other machines"));
+    /// assert!(parser.long_help_text().contains("other machines"));
     /// ```
-    pub fn remove_opt(&mut self, name: &str) -> Result<(), &'static str> {
-        
-        self.arguments.remove(name).map(|_| ()).ok_or("No such Option")
+    pub fn long_help(&mut self, name: &str, text: &str) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.long_help = Some(text.into()))
     }
     
-    /// Parse a set of arguments, given the previous configuration
+    /// Set where an argument is shown in help, so the most important
+    /// ones can come first whatever order they were added in. Arguments
+    /// with an order are shown first, lowest first, then the others;
+    /// ties keep the order the arguments were added in.
     /// # Example
     /// ```
-    /// // add an option that is a `Flag`, with no default value, with
-    /// // a long form of `--verbose`, short form of `v`, that is not
-    /// // required to be passed, and has a default value of `false`
-    ///
     /// use argparse::{ArgParser, ArgType};
     ///
-    /// let mut parser = ArgParser::new("runner".into());
+    /// let mut parser = ArgParser::new("server".into());
     /// parser.add_opt("verbose", Some("false"), 'v', false,
     ///     "Whether to produce verbose output", ArgType::Flag);
+    /// parser.add_opt("listen", Some("127.0.0.1:8080"), 'l', false,
+    ///     "Address to listen on", ArgType::Option);
+    /// parser.display_order("listen", 0).unwrap();
     ///
-    /// // Normally you'd get this from std::env::args().iter()
-    /// let test_1 = "./runner --verbose".split_whitespace()
-    ///     .map(|s| s.into())
-    ///     .collect::<Vec<String>>();
-    /// 
-    /// if let Ok(p_res) = parser.parse(test_1.iter()) {
-    ///     // do stuff here
-    /// }
+    /// let help = parser.help_text();
+    /// assert!(help.find("--listen (-l)") < help.find("--help (-h)"));
     /// ```
-    pub fn parse<'a, I: Iterator<Item = &'a String>> (&self, args: I) -> ParseResult {
-        use std::collections::hash_map::Entry;
-        
-        if self.arguments.len() == 0 || self.done {
-            return Err("No arguments given to parse".into());
-        }
-        
-        let argvec: Vec<String> = separate_flags(args.map(|s| s.clone()).collect());
-        
-        let mut taken_up = Vec::new();
-        let mut new_args = self.arguments.clone();
-        
-        for (argname, my_arg) in self.arguments.iter() {
-            for (flag, rest) in argvec.slide().filter(|&(f, _)| {f == &format!("-{}", my_arg.flag) || f == &format!("--{}", argname)}) {
-
-                if let Entry::Occupied(mut e) = new_args.entry(argname.clone()) {
-                    let arg = e.get_mut();
-                    arg.count = arg.count + 1;
-                    taken_up.push(flag);
-                    
-                    match arg.type_ {
-                        ArgType::Flag => { arg.val = Some("true".into()); }
-                        ArgType::Option => {
-                            let err = format!("This option `{}` requires a value you have not provided", argname);
-                            
-                            if let Some(rest) = rest {
-                                if is_flag(&rest[0]) || is_long_flag(&rest[0]) {
-                                    return Err(err);
-                                }
-                                
-                                arg.val = Some(rest[0].clone());
-                                taken_up.push(&rest[0]);
-                            } else {
-                                return Err(err);
-                            }
-                        }
-                        ArgType::List | ArgType::Dict => {
-                            if let Some(rest) = rest {
-                                arg.val = Some(rest.iter()
-                                    .take_while(|x| !(is_flag(x) || is_long_flag(x)))
-                                    .fold(String::new(), |mut acc, elem| {
-                                        acc.push_str(elem);
-                                        acc.push(' ');
-                                        acc
-                                    }));
-                                    
-                                taken_up.extend(rest.iter().take_while(|x| !(is_flag(x) || is_long_flag(x))));
-                            } else {
-                                let err = format!("This option `{}` requires a value you have not provided", argname);
-                                return Err(err);
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
-        
-        for (_, ref mut v) in new_args.iter_mut().filter(|&(_, ref vv)| vv.val.is_none() && vv.type_.is_positional()) {
-            
-            if let Some((_, x)) = argvec.iter().skip(1)
-                .filter(|e| !taken_up.contains(e))
-                .enumerate()
-                .find(|&(i, _)| {
-                    if let ArgType::Positional(idx) = v.type_ {
-                        idx as usize == i
-                    } else {
-                        false
-                    }
-                }) {
-                
-                    v.val = Some(x.clone());
-            }
-        }
-
-        if !new_args.iter().all(|(_, v)| !v.required | v.val.is_some()) {
-            return Err("Not all required arguments are found".into());
-        }
-        
-        let res = ArgParseResults::new(self.name.clone(), new_args);
-        res.p_args();
-        
-        Ok(res)
+    pub fn display_order(&mut self, name: &str, order: u32) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.order = Some(order))
     }
-
-    /// Prints the help message, which is constructed based on the options
-    /// used
+    
+    /// Only show an argument in help while `f` returns true, e.g. to
+    /// hide experimental options unless an environment variable is set,
+    /// or options that only work on some platforms. It is still parsed
+    /// either way.
     /// # Example
     /// ```
+    /// use std::env;
     /// use argparse::{ArgParser, ArgType};
     ///
+    /// let mut parser = ArgParser::new("server".into());
+    /// parser.add_opt("experimental-io", Some("false"), 'x', false,
+    ///     "Use the new IO engine", ArgType::Flag);
+    /// parser.visible_if("experimental-io", || env::var_os("SERVER_EXPERIMENTS").is_some()).unwrap();
+    /// parser.add_opt("service", Some("false"), 's', false,
+    ///     "Run as a Windows service", ArgType::Flag);
+    /// parser.visible_if("service", || cfg!(windows)).unwrap();
+    ///
+    /// assert!(!parser.help_text().contains("--experimental-io"));
+    ///
+    /// let test_1 = vec!["./server".to_string(), "-x".into()];
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// assert_eq!(p_res.get("experimental-io"), Some(true));
+    /// ```
+    pub fn visible_if<F>(&mut self, name: &str, f: F) -> Result<(), &'static str>
+        where F: Fn() -> bool + Send + Sync + 'static {
+        
+        self.arg_mut(name).map(|arg| arg.visible = Some(Condition(Arc::new(f))))
+    }
+    
+    /// The arguments shown in help, in the order they are shown in
+    fn displayed(&self) -> Vec<&Arg> {
+        let mut args: Vec<&Arg> = self.defs.args.iter()
+            .filter(|a| a.visible.as_ref().is_none_or(|Condition(f)| f()))
+            .collect();
+        args.sort_by_key(|a| a.order.unwrap_or(u32::MAX));
+        args
+    }
+    
+    /// Declare the type an argument's values are read as. `get`,
+    /// `get_list` and `try_get` then refuse to read them as any other
+    /// type, catching mistakes such as reading a `Flag` as a number.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, GetError};
+    ///
     /// let mut parser = ArgParser::new("runner".into());
     /// parser.add_opt("verbose", Some("false"), 'v', false,
     ///     "Whether to produce verbose output", ArgType::Flag);
+    /// parser.value_type::<bool>("verbose").unwrap();
     ///
-    /// // Normally you'd get this from std::env::args().iter()
-    /// let test_1 = "./runner --help".split_whitespace()
+    /// let test_1 = vec!["./runner".to_string(), "-v".into()];
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    ///
+    /// assert_eq!(p_res.get::<bool>("verbose"), Some(true));
+    /// assert_eq!(p_res.get::<u32>("verbose"), None);
+    /// assert_eq!(p_res.try_get::<u32>("verbose"), Err(GetError::WrongType {
+    ///     name: "verbose".into(),
+    ///     declared: "bool",
+    ///     requested: "u32",
+    /// }));
+    /// ```
+    pub fn value_type<T: 'static>(&mut self, name: &str) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.tag = Some(TypeTag::of::<T>()))
+    }
+    
+    /// Mark an argument as deprecated. It still works, but giving it adds
+    /// a warning with `note`, which should say what to use instead, to
+    /// the results.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, Warning};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("out", None, 'o', false,
+    ///     "Where to write", ArgType::Option);
+    /// parser.deprecated("out", "use --output instead").unwrap();
+    ///
+    /// let test_1 = "./runner --out a.txt".split_whitespace()
     ///     .map(|s| s.into())
     ///     .collect::<Vec<String>>();
-    /// 
-    /// if let Ok(p_res) = parser.parse(test_1.iter()) {
-    ///     if let Some(true) = p_res.get("help") {
-    ///         parser.help();
-    ///     }
-    /// }
+    ///
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// assert_eq!(p_res.get::<String>("out"), Some("a.txt".into()));
+    /// assert_eq!(p_res.warnings()[0].to_string(), "`out` is deprecated: use --output instead");
     /// ```
-    pub fn help(&self) {
-        print!("Usage:\t./{} ", self.name);
-        
-        for (argname, info) in self.arguments.iter() {
-            print!("[--{} {}] ", argname, ops(info, argname));
-        }
-        println!("");
-        
-        print!("Options:\n\n");
-        for (argname, info) in self.arguments.iter() {            
-            print!("--{} (-{})\t", argname, info.flag);
-            print!("Required: {}\t", info.required);
-            print!("Type: {}\n", info.type_);
-            print!("\t");
-            
-            let mut i = 0;
-            for c in info.help.chars() {
-                print!("{}", c);
-                
-                if i > 60 && c.is_whitespace() {
-                    print!("\n\t\t");
-                    i = 0;
-                }
-                
-                i = i + 1;
-            }
-            
-            println!("\n");
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-/// This type represents the result ofparsing arguments.
-pub struct ArgParseResults {
-    arguments: HashMap<String, Arg>,
-    name: String,
-}
-
-impl ArgParseResults {
-
-    fn new(name: String, args: HashMap<String, Arg>) -> ArgParseResults {
-        ArgParseResults { name: name, arguments: args }
+    pub fn deprecated(&mut self, name: &str, note: &str) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.deprecated = Some(note.into()))
     }
-
-    #[inline]
-    #[cfg(debug_assertions)]
-    fn p_args(&self) {
-        for (k, v) in self.arguments.iter() {
-            println!("{}:{:?}", k, v.val);
-        }
+    
+    /// Mark an argument as exclusive: when given, it must be the only
+    /// argument on the command line, like `--init` or `--help`. Parsing
+    /// fails when anything else is given with it, and otherwise skips
+    /// the checks for required arguments and groups.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("store".into());
+    /// parser.add_opt("init", Some("false"), 'i', false,
+    ///     "Create an empty store", ArgType::Flag);
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of the entry", ArgType::Option);
+    /// parser.exclusive("init").unwrap();
+    ///
+    /// assert_eq!(parser.parse_line("--init").unwrap().get("init"), Some(true));
+    /// assert!(parser.parse_line("--init -n x").is_err());
+    /// ```
+    pub fn exclusive(&mut self, name: &str) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.exclusive = true)
     }
     
-    #[inline]
-    #[cfg(not(debug_assertions))]
-    fn p_args(&self) {}
+    /// Mark an argument, such as `--version` or `--print-config`, as
+    /// ending the parse: once it has been read, with its value if it
+    /// takes one, nothing after it is, and no checks for required
+    /// arguments are made. `terminated_by` on the results then names
+    /// it.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("version", Some("false"), 'V', false,
+    ///     "Print the version and exit", ArgType::Flag);
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of user", ArgType::Option);
+    /// parser.terminates("version").unwrap();
+    ///
+    /// let p_res = parser.parse_line("--version --name").unwrap();
+    /// assert_eq!(p_res.terminated_by(), Some("version"));
+    /// assert_eq!(p_res.get::<String>("name"), None);
+    /// ```
+    pub fn terminates(&mut self, name: &str) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.terminates = true)
+    }
     
-    /// Extracts the argument, as long is the value type implements
-    /// `FromStr`
+    /// Declare that two arguments override each other, like `--quiet`
+    /// and `--verbose`: whichever is given last wins, and the other is
+    /// read as if it hadn't been given at all, falling back to its
+    /// default.
     /// # Example
     /// ```
     /// use argparse::{ArgParser, ArgType};
     ///
     /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("quiet", Some("false"), 'q', false,
+    ///     "Print nothing", ArgType::Flag);
     /// parser.add_opt("verbose", Some("false"), 'v', false,
-    ///     "Whether to produce verbose output", ArgType::Flag);
+    ///     "Print everything", ArgType::Flag);
+    /// parser.overrides_with("quiet", "verbose").unwrap();
     ///
-    /// // Normally you'd get this from std::env::args().iter()
-    /// let test_1 = "./runner -v".split_whitespace()
-    ///     .map(|s| s.into())
-    ///     .collect::<Vec<String>>();
-    /// 
-    /// if let Ok(p_res) = parser.parse(test_1.iter()) {
-    ///     if let Some(true) = p_res.get::<bool>("verbose") {
-    ///         // be verbose
-    ///     }
-    /// }
+    /// let p_res = parser.parse_line("-q -v").unwrap();
+    /// assert_eq!(p_res.get("quiet"), Some(false));
+    /// assert_eq!(p_res.get("verbose"), Some(true));
     /// ```
-    pub fn get<T: FromStr>(&self, name: &str) -> Option<T> {
-        if let Some(ref arg) = self.arguments.get(name.into()) {
-            arg.val.as_ref().and_then(|x| x.parse().ok())
-        } else {
-            None
-        }
+    pub fn overrides_with(&mut self, name: &str, other: &str) -> Result<(), &'static str> {
+        self.arg_mut(other)?;
+        self.arg_mut(name)?.overrides.push(other.into());
+        self.arg_mut(other).map(|arg| arg.overrides.push(name.into()))
     }
     
-    /// Extracts the argument, using the `ArgGetter<T>` that you provided
+    /// Limit how many times an argument may be given, so that e.g. a
+    /// second `--output` is caught instead of replacing the first.
+    /// `policy` says whether giving it more often fails the parse or
+    /// only adds a warning.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, TooMany};
     ///
-    /// # Note
-    /// See documentation for the trait [`ArgGetter`](./trait.ArgGetter.html) for more information
-    /// 
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("output", None, 'o', false,
+    ///     "Where to write", ArgType::Option);
+    /// parser.max_occurrences("output", 1, TooMany::Error).unwrap();
+    ///
+    /// assert!(parser.parse_line("-o a.txt").is_ok());
+    ///
+    /// let err = parser.parse_line("-o a.txt -o b.txt").unwrap_err();
+    /// assert_eq!(err.message(), "The option `output` can be given at most 1 times");
+    /// assert_eq!(err.position(), Some(3));
+    /// ```
+    pub fn max_occurrences(&mut self, name: &str, max: u16, policy: TooMany) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.max_occurrences = Some((max, policy)))
+    }
+    
+    /// Restrict the values of an argument to the given choices. Any
+    /// other value fails to parse, with a message listing them.
     /// # Example
     /// ```
     /// use argparse::{ArgParser, ArgType};
     ///
     /// let mut parser = ArgParser::new("runner".into());
-    /// parser.add_opt("verbose", Some("false"), 'v', false,
-    ///     "Whether to produce verbose output", ArgType::Flag);
+    /// parser.add_opt("format", Some("json"), 'f', false,
+    ///     "Output format", ArgType::Option);
+    /// parser.choices("format", &["json", "yaml"]).unwrap();
     ///
-    /// // Normally you'd get this from std::env::args().iter()
-    /// let test_1 = "./runner -v".split_whitespace()
+    /// assert!(parser.parse_line("-f yaml").is_ok());
+    /// assert!(parser.parse_line("-f xml").is_err());
+    /// ```
+    pub fn choices(&mut self, name: &str, choices: &[&str]) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.choices = choices.iter().map(|&c| c.into()).collect())
+    }
+    
+    /// Let the values of an argument with `choices` match them in any
+    /// case. The value is stored in the case of the choice it matched,
+    /// so `--format JSON` reads as `json`.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("format", Some("json"), 'f', false,
+    ///     "Output format", ArgType::Option);
+    /// parser.choices("format", &["json", "yaml"]).unwrap();
+    /// parser.ignore_case("format").unwrap();
+    ///
+    /// let p_res = parser.parse_line("-f JSON").unwrap();
+    /// assert_eq!(p_res.get::<String>("format"), Some("json".into()));
+    /// ```
+    pub fn ignore_case(&mut self, name: &str) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.ignore_case = true)
+    }
+    
+    /// Set a token, such as `;`, that ends the values of a `List` or
+    /// `Dict` argument, like `find -exec` does. The terminator itself
+    /// is not a value. Without one, values only end at the next flag,
+    /// so a list can't be followed by positional arguments.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("frequencies", None, 'f', false,
+    ///     "User's favorite frequencies", ArgType::List);
+    /// parser.add_opt("file", None, 'F', false,
+    ///     "File to write", ArgType::Positional(0));
+    /// parser.value_terminator("frequencies", ";").unwrap();
+    ///
+    /// let p_res = parser.parse_line("-f 1 2 3 ';' out.txt").unwrap();
+    /// assert_eq!(p_res.get_list("frequencies"), Some(vec![1, 2, 3]));
+    /// assert_eq!(p_res.get::<String>("file"), Some("out.txt".into()));
+    /// ```
+    pub fn value_terminator(&mut self, name: &str, terminator: &str) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.terminator = Some(terminator.into()))
+    }
+    
+    /// Set how many of the values following a `List` or `Dict` argument
+    /// it takes each time it is given. By default it takes all of them.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, ListValues};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("files", None, 'f', false,
+    ///     "Files to read", ArgType::List);
+    /// parser.add_opt("out", None, 'o', false,
+    ///     "File to write", ArgType::Positional(0));
+    /// parser.list_values("files", ListValues::AtMost(1)).unwrap();
+    ///
+    /// let p_res = parser.parse_line("-f a -f b out.txt").unwrap();
+    /// assert_eq!(p_res.get_list::<String>("files"), Some(vec!["a".into(), "b".into()]));
+    /// assert_eq!(p_res.get::<String>("out"), Some("out.txt".into()));
+    /// ```
+    pub fn list_values(&mut self, name: &str, policy: ListValues) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.list_values = policy)
+    }
+    
+    /// Let an argument take values that look like negative numbers when
+    /// parsing with `NegativeNumbers::PerArg`, under which they are
+    /// otherwise flags.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, NegativeNumbers, ParseOptions};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("offset", Some("0"), 'o', false,
+    ///     "Where to start", ArgType::Option);
+    /// parser.add_opt("lines", Some("10"), '1', false,
+    ///     "How many lines", ArgType::Option);
+    /// parser.negative_numbers("offset").unwrap();
+    /// parser.options(ParseOptions { negative_numbers: NegativeNumbers::PerArg, ..ParseOptions::default() });
+    ///
+    /// let p_res = parser.parse_line("--offset -5 -1 3").unwrap();
+    /// assert_eq!(p_res.get("offset"), Some(-5));
+    /// assert_eq!(p_res.get("lines"), Some(3));
+    /// ```
+    pub fn negative_numbers(&mut self, name: &str) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.negative = true)
+    }
+    
+    /// Make an `Option` given several times join its values with
+    /// `joiner`, in the order given, instead of keeping the last one.
+    /// Each value is still checked on its own.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("search".into());
+    /// parser.add_opt("query", None, 'q', false,
+    ///     "What to search for", ArgType::Option);
+    /// parser.concatenate("query", " AND ").unwrap();
+    ///
+    /// let p_res = parser.parse_line("-q 'size > 3' -q 'color = red'").unwrap();
+    /// assert_eq!(p_res.get::<String>("query"), Some("size > 3 AND color = red".into()));
+    /// ```
+    pub fn concatenate(&mut self, name: &str, joiner: &str) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.joiner = Some(joiner.into()))
+    }
+    
+    /// Sets a check that every value given for an argument must pass
+    /// while parsing. A value it rejects fails the parse with an error
+    /// pointing at the value, and the reason it returns. Defaults are
+    /// not checked.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, socket_addr_validator};
+    ///
+    /// let mut parser = ArgParser::new("server".into());
+    /// parser.add_opt("listen", Some("127.0.0.1:8080"), 'l', false,
+    ///     "Address to listen on", ArgType::Option);
+    /// parser.validator("listen", socket_addr_validator).unwrap();
+    ///
+    /// let test_1 = "./server --listen 0.0.0.0:99999".split_whitespace()
     ///     .map(|s| s.into())
     ///     .collect::<Vec<String>>();
-    /// 
-    /// let dumb_closure = |_: &str| { Some(true) };
-    /// 
-    /// if let Ok(p_res) = parser.parse(test_1.iter()) {
-    ///     if let Some(true) = p_res.get_with::<bool, _>("verbose", dumb_closure) {
-    ///         // be verbose
-    ///     }
+    ///
+    /// let err = parser.parse(test_1.iter()).unwrap_err();
+    /// assert!(err.message().ends_with("port out of range"));
+    /// assert_eq!(err.position(), Some(2));
+    /// ```
+    pub fn validator<F>(&mut self, name: &str, f: F) -> Result<(), &'static str>
+        where F: Fn(&str) -> Result<(), String> + Send + Sync + 'static {
+        
+        self.arg_mut(name).map(|arg| arg.validator = Some(Validator(Arc::new(f))))
+    }
+    
+    /// Set what happens when a `Dict` argument is given the same key
+    /// twice. Every occurrence of the argument adds its pairs to the
+    /// same map; by default, a repeated key keeps the last value given.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, DuplicateKeys};
+    ///
+    /// let mut parser = ArgParser::new("cc".into());
+    /// parser.add_opt("define", None, 'D', false,
+    ///     "Macros to define", ArgType::Dict);
+    /// parser.duplicate_keys("define", DuplicateKeys::Error).unwrap();
+    ///
+    /// let test_1 = "./cc -D DEBUG:1 -D LEVEL:2".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// assert_eq!(p_res.pairs("define").map(|p| p.len()), Some(2));
+    ///
+    /// let test_2 = "./cc -D DEBUG:1 -D DEBUG:0".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// assert_eq!(parser.parse(test_2.iter()).unwrap_err().position(), Some(4));
+    /// ```
+    pub fn duplicate_keys(&mut self, name: &str, policy: DuplicateKeys) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.duplicates = policy)
+    }
+    
+    /// Set how the value of a `Path` argument is normalized while
+    /// parsing. A path that can't be normalized, such as one that
+    /// doesn't exist when canonicalizing, fails the parse. A default
+    /// is normalized when the argument isn't given.
+    /// # Example
+    /// ```
+    /// use std::env;
+    /// use std::path::PathBuf;
+    /// use argparse::{ArgParser, ArgType, PathOptions};
+    ///
+    /// let mut parser = ArgParser::new("concat".into());
+    /// parser.add_opt("output", Some("out.txt"), 'o', false,
+    ///     "File to write", ArgType::Path);
+    /// parser.path_options("output", PathOptions { absolute: true, ..PathOptions::default() }).unwrap();
+    ///
+    /// let test_1 = "./concat -o ./all.txt".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// assert_eq!(p_res.get("output"), Some(env::current_dir().unwrap().join("all.txt")));
+    /// ```
+    pub fn path_options(&mut self, name: &str, options: PathOptions) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.paths = options)
+    }
+    
+    /// Expands glob patterns, such as `*.csv`, given as values of a
+    /// `List` argument into the paths they match, sorted. A pattern
+    /// matching nothing is kept as it is. Unix shells expand patterns
+    /// before the program sees them, but Windows' `cmd` does not, so
+    /// this makes `--inputs *.csv` behave the same everywhere. The
+    /// paths can be read with `get_list::<PathBuf>`. A match that isn't
+    /// valid UTF-8 fails to parse rather than being changed. Requires
+    /// the `glob` feature.
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "glob")]
+    /// # fn main() {
+    /// use std::fs;
+    /// use std::path::PathBuf;
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let dir = std::env::temp_dir().join(format!("concat-{}", std::process::id()));
+    /// fs::create_dir_all(&dir).unwrap();
+    /// for name in &["a.csv", "b.csv", "c.txt"] {
+    ///     fs::write(dir.join(name), "").unwrap();
     /// }
+    ///
+    /// let mut parser = ArgParser::new("concat".into());
+    /// parser.add_opt("inputs", None, 'i', true,
+    ///     "Files to read", ArgType::List);
+    /// parser.expand_globs("inputs").unwrap();
+    ///
+    /// let pattern = dir.join("*.csv").to_string_lossy().into_owned();
+    /// let p_res = parser.parse(["./concat".to_string(), "--inputs".into(), pattern].iter()).unwrap();
+    /// assert_eq!(p_res.get_list::<PathBuf>("inputs"),
+    ///     Some(vec![dir.join("a.csv"), dir.join("b.csv")]));
+    /// # fs::remove_dir_all(&dir).unwrap();
+    /// # }
+    /// # #[cfg(not(feature = "glob"))]
+    /// # fn main() {}
     /// ```
-    pub fn get_with<T, P>(&self, name: &str, parser: P) -> Option<T>
-    where P: ArgGetter<T> {
-        if let Some(ref arg) = self.arguments.get(name.into()) {
-            arg.val.as_ref().and_then(|x| parser.get_arg(&x))
-        } else {
-            None
+    #[cfg(feature = "glob")]
+    pub fn expand_globs(&mut self, name: &str) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| arg.globs = true)
+    }
+    
+    /// Require at least `min` of the named arguments to be given, as
+    /// in "at least two of `--x`, `--y` and `--z`". Defaults don't
+    /// count. Parsing fails with a message saying which of them were
+    /// given otherwise. A parser can have any number of groups.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("plot".into());
+    /// parser.add_opt("x", None, 'x', false, "X coordinate", ArgType::Option);
+    /// parser.add_opt("y", None, 'y', false, "Y coordinate", ArgType::Option);
+    /// parser.add_opt("z", Some("0"), 'z', false, "Z coordinate", ArgType::Option);
+    /// parser.require_at_least(2, &["x", "y", "z"]).unwrap();
+    ///
+    /// assert!(parser.parse_line("-x 1 -z 2").is_ok());
+    ///
+    /// let err = parser.parse_line("-x 1").unwrap_err();
+    /// assert_eq!(err.message(), "At least 2 of `x`, `y`, `z` must be given, but got 1 (`x`)");
+    /// ```
+    pub fn require_at_least(&mut self, min: usize, names: &[&str]) -> Result<(), &'static str> {
+        if names.iter().any(|name| !self.defs.names.contains_key(*name)) {
+            return Err("No such Option");
         }
+        
+        self.groups.push(Group { min, names: names.iter().map(|&name| name.into()).collect() });
+        Ok(())
     }
-}
-
-/// Represents something capable of turning a `&str` in the value
-/// type of your choice. Implement this to use with `ArgParseResults::get_with`
-///
-/// # Note
-/// An implementation is provided for all closures of type `F: FnOnce(&str) -> Option<T>`
-pub trait ArgGetter<T> {
-    /// This is the key function that converts from a string 
-    /// to the required value tpe
-    fn get_arg(self, s: &str) -> Option<T>;
-}
-
-impl<T, F: FnOnce(&str) -> Option<T>> ArgGetter<T> for F {
-    fn get_arg(self, s: &str) -> Option<T> {
-        self(s)
+    
+    /// Set whether help and error output is colorized. The default,
+    /// `ColorChoice::Auto`, only uses color when writing to a terminal
+    /// and the `NO_COLOR` environment variable is not set.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ColorChoice};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.color(ColorChoice::Never);
+    /// ```
+    pub fn color(&mut self, choice: ColorChoice) {
+        self.color = choice;
     }
-}
-
-/// Function that parses `List` arguments into `Vec`s.
-/// Provided for user convenience and use as an implementor of
-/// [`ArgGetter`](./trait.ArgGetter.html).
-pub fn vec_parser<T: FromStr>(s: &str) -> Option<Vec<T>> {
-    s.split_whitespace()
-        .map(|x| x.parse())
-        .enumerate()
-        .fold(None, |acc, (idx, elem)| {
-            if let Ok(x) = elem {
-                if idx == 0 {
-                    return Some(vec![x]);
-                } else {
-                    return acc.map(|mut v| {
-                        v.push(x);
-                        v
-                    });
+    
+    /// Replace the catalog of user-facing strings used in help and error
+    /// output, e.g. with a translation for the user's locale. The
+    /// description of the built-in `--help` flag is updated to match,
+    /// unless it has been replaced in the meantime.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, Messages};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.messages(Messages {
+    ///     usage: "Uso:".into(),
+    ///     options: "Opciones:".into(),
+    ///     ..Messages::default()
+    /// });
+    /// ```
+    pub fn messages(&mut self, catalog: Messages) {
+        let defs = Arc::make_mut(&mut self.defs);
+        
+        if let Some(&idx) = defs.names.get("help") {
+            if defs.args[idx].help == self.messages.help {
+                defs.args[idx].help = catalog.help.to_string();
+            }
+        }
+        
+        self.messages = catalog;
+    }
+    
+    /// Set whether `help` pipes its output through a pager when it is
+    /// too long to fit on the terminal. The pager is taken from the
+    /// `PAGER` environment variable, falling back to `less`, and is only
+    /// used when standard output is a terminal. With the `terminal_size`
+    /// feature the terminal is asked how many lines it has; otherwise
+    /// that is read from the `LINES` environment variable, which most
+    /// shells don't export, so 24 lines are assumed. Off by default.
+    /// # Example
+    /// ```
+    /// use argparse::ArgParser;
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.pager(true);
+    /// ```
+    pub fn pager(&mut self, enabled: bool) {
+        self.pager = enabled;
+    }
+    
+    /// Set a human-readable title, such as `"Image Resizer 2.1"`, shown
+    /// at the top of help. The name given to `new` stays the one used
+    /// in the usage line.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ColorChoice};
+    ///
+    /// let mut parser = ArgParser::new("resize".into());
+    /// parser.color(ColorChoice::Never);
+    /// parser.title("Image Resizer 2.1");
+    /// parser.description("Scales images to fit within the given bounds");
+    ///
+    /// let help = parser.help_text();
+    /// assert!(help.starts_with("Image Resizer 2.1\nScales images"));
+    /// assert!(help.contains("./resize "));
+    /// ```
+    pub fn title(&mut self, title: &str) {
+        self.title = Some(title.into());
+    }
+    
+    /// Set a description of the program, shown at the top of help
+    /// below the title. It is wrapped like the help of arguments.
+    pub fn description(&mut self, text: &str) {
+        self.description = Some(text.into());
+    }
+    
+    /// Add an example invocation, with what it does, to the examples
+    /// section at the end of help. Examples are shown in the order they
+    /// were added.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, ColorChoice};
+    ///
+    /// let mut parser = ArgParser::new("mytool".into());
+    /// parser.color(ColorChoice::Never);
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of user", ArgType::Option);
+    /// parser.example("mytool -n Johnny -f 1 2 3", "parse Johnny's frequencies");
+    ///
+    /// assert!(parser.help_text().contains("mytool -n Johnny -f 1 2 3\n    parse Johnny's frequencies"));
+    /// ```
+    pub fn example(&mut self, command: &str, description: &str) {
+        self.examples.push((command.into(), description.into()));
+    }
+    
+    /// Set the maximum width help output is wrapped to. Help is otherwise
+    /// wrapped to the width given by the `COLUMNS` environment variable,
+    /// or 80 columns if that is unset; an explicit maximum only ever
+    /// narrows the output when `COLUMNS` is smaller.
+    /// # Example
+    /// ```
+    /// use argparse::ArgParser;
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.max_width(72);
+    /// ```
+    pub fn max_width(&mut self, width: usize) {
+        self.max_width = Some(width);
+    }
+    
+    /// Set the column at which descriptions start in the options
+    /// section of help. By default it is just past the longest flags,
+    /// up to 32 columns in; flags too long to fit before it are put on
+    /// a line of their own.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, ColorChoice};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.color(ColorChoice::Never);
+    /// parser.add_opt("verbose", Some("false"), 'v', false,
+    ///     "Whether to produce verbose output", ArgType::Flag);
+    /// parser.help_column(24);
+    ///
+    /// assert!(parser.help_text().contains("\n  --verbose (-v)        Whether to produce verbose output\n"));
+    /// ```
+    pub fn help_column(&mut self, column: usize) {
+        self.help_column = Some(column);
+    }
+    
+    /// Set the options `parse`, `parse_line`, `parse_lines` and
+    /// `parse_borrowed` read arguments with. `parse_with` still uses
+    /// the options it is given.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ParseOptions};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.options(ParseOptions { strict: true, ..ParseOptions::default() });
+    ///
+    /// assert!(parser.parse_line("--colour").is_err());
+    /// ```
+    pub fn options(&mut self, opts: ParseOptions) {
+        self.options = opts;
+    }
+    
+    /// Add middleware that rewrites the arguments before they are read,
+    /// e.g. to supply an option from a file when it isn't given. It runs
+    /// in `parse`, `parse_with`, `parse_line` and `parse_lines`, on the
+    /// whole command line including the program name, and errors point
+    /// into the rewritten arguments. Middleware runs in the order it was
+    /// added. `parse_borrowed` and `parse_iter` can't rewrite what they
+    /// borrow, so they refuse to read arguments for a parser with any
+    /// middleware.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("deploy".into());
+    /// parser.add_opt("profile", None, 'p', true,
+    ///     "Profile to deploy with", ArgType::Option);
+    /// parser.before_parse(|args| {
+    ///     if !args.iter().any(|a| a == "-p" || a.starts_with("--profile")) {
+    ///         args.extend(vec!["--profile".to_string(), "locked".into()]);
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(parser.parse_line("").unwrap().get::<String>("profile"), Some("locked".into()));
+    /// assert_eq!(parser.parse_line("-p dev").unwrap().get::<String>("profile"), Some("dev".into()));
+    /// ```
+    pub fn before_parse<F>(&mut self, f: F)
+        where F: Fn(&mut Vec<String>) + Send + Sync + 'static {
+        
+        self.rewrites.push(Rewrite(Arc::new(f)));
+    }
+    
+    /// Add middleware that runs on the results once the arguments have
+    /// parsed, to check them further or add to them with
+    /// `ArgParseResults::set`. An error fails the parse with it as the
+    /// message. It runs wherever `before_parse` middleware does, in the
+    /// order it was added, and `parse_borrowed` and `parse_iter` refuse
+    /// parsers with it too.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("copy".into());
+    /// parser.add_opt("from", None, 'f', true, "Source", ArgType::Option);
+    /// parser.add_opt("to", None, 't', false, "Destination", ArgType::Option);
+    /// parser.after_parse(|p_res| {
+    ///     if p_res.get::<String>("to").is_none() {
+    ///         let from: String = p_res.get("from").unwrap_or_default();
+    ///         p_res.set("to", &[&format!("{}.bak", from)]).unwrap();
+    ///     }
+    ///     match p_res.get::<String>("from") == p_res.get("to") {
+    ///         true => Err("Can't copy a file onto itself".into()),
+    ///         false => Ok(()),
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(parser.parse_line("-f a").unwrap().get::<String>("to"), Some("a.bak".into()));
+    /// assert_eq!(parser.parse_line("-f a -t a").unwrap_err().message(), "Can't copy a file onto itself");
+    /// ```
+    pub fn after_parse<F>(&mut self, f: F)
+        where F: Fn(&mut ArgParseResults) -> Result<(), String> + Send + Sync + 'static {
+        
+        self.finishers.push(Finish(Arc::new(f)));
+    }
+    
+    /// Copy the settings chosen by `what` from `parent`, so that a
+    /// subcommand's parser looks and behaves like the parser of the
+    /// program it belongs to. The settings are copied as they are at
+    /// the time of the call, replacing the parser's own. Settings are
+    /// therefore resolved in this order, the first that applies winning:
+    ///
+    ///  1. setters called on this parser after `inherit`
+    ///  2. the parent's settings, if `what` includes them
+    ///  3. setters called on this parser before `inherit`
+    ///  4. the defaults
+    ///
+    /// Arguments, names, titles, descriptions and examples always
+    /// belong to the parser itself.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ColorChoice, Inherit, Messages, ParseOptions};
+    ///
+    /// let mut app = ArgParser::new("git".into());
+    /// app.color(ColorChoice::Never);
+    /// app.options(ParseOptions { strict: true, ..ParseOptions::default() });
+    /// app.messages(Messages { usage: "Uso:".into(), ..Messages::default() });
+    ///
+    /// let mut commit = ArgParser::new("git commit".into());
+    /// commit.inherit(&app, Inherit { messages: false, ..Inherit::default() });
+    ///
+    /// assert!(commit.parse_line("--colour").is_err());
+    /// assert!(commit.help_text().starts_with("Usage:"));
+    /// ```
+    pub fn inherit(&mut self, parent: &ArgParser, what: Inherit) {
+        if what.color {
+            self.color = parent.color;
+        }
+        if what.messages {
+            self.messages(parent.messages.clone());
+        }
+        if what.pager {
+            self.pager = parent.pager;
+        }
+        if what.layout {
+            self.max_width = parent.max_width;
+            self.help_column = parent.help_column;
+        }
+        if what.options {
+            self.options = parent.options;
+        }
+    }
+    
+    /// The name of the program, as shown in help
+    pub(crate) fn program(&self) -> &str {
+        &self.name
+    }
+    
+    /// The catalog of user-facing strings in use
+    pub(crate) fn catalog(&self) -> &Messages {
+        &self.messages
+    }
+    
+    /// The width help output is wrapped to, see `max_width`
+    fn help_width(&self) -> usize {
+        let columns = env::var("COLUMNS").ok().and_then(|c| c.parse().ok());
+        
+        match (columns, self.max_width) {
+            (Some(c), Some(m)) => ::std::cmp::min(c, m),
+            (Some(w), None) | (None, Some(w)) => w,
+            (None, None) => 80,
+        }
+    }
+    
+    /// Remove an option from parsing consideration.
+    /// # Example
+    /// ```
+    /// // add an option that is a `Flag`, with no default value, with
+    /// // a long form of `--verbose`, short form of `v`, that is not
+    /// // required to be passed, and has a default value of `false`
+    ///
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("verbose", Some("false"), 'v', false,
+    ///     "Whether to produce verbose output", ArgType::Flag);
+    /// assert!(parser.remove_opt("verbose").is_ok())
+    /// ```
+    pub fn remove_opt(&mut self, name: &str) -> Result<(), &'static str> {
+        
+        Arc::make_mut(&mut self.defs).remove(name)
+            .map(|_| ())
+            .ok_or("No such Option")
+    }
+    
+    /// Redefine an option that was already added, with the arguments of
+    /// `add_opt`. It keeps its place among the others, but every other
+    /// setting, such as its `choices`, starts over; use `modify_opt` to
+    /// change only some.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("jobs", Some("1"), 'j', false,
+    ///     "Jobs to run at once", ArgType::Option);
+    /// parser.replace_opt("jobs", None, 'J', true,
+    ///     "Jobs to run at once", ArgType::Option).unwrap();
+    ///
+    /// assert!(parser.help_text().contains("--jobs (-J)"));
+    /// assert!(parser.replace_opt("threads", None, 't', false,
+    ///     "Threads", ArgType::Option).is_err());
+    /// ```
+    pub fn replace_opt(&mut self, name: &str, 
+        default: Option<&str>, flag: char, required: bool, 
+        help: &str, type_: ArgType) -> Result<(), &'static str> {
+        
+        self.arg_mut(name)?;
+        self.add_opt(name, default, flag, required, help, type_);
+        Ok(())
+    }
+    
+    /// Change some settings of an option that was already added, e.g.
+    /// the default after loading a configuration or plugin, keeping the
+    /// rest.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("jobs", Some("1"), 'j', false,
+    ///     "Jobs to run at once", ArgType::Option);
+    /// parser.choices("jobs", &["1", "2", "4"]).unwrap();
+    ///
+    /// parser.modify_opt("jobs", |arg| {
+    ///     arg.default(Some("4")).help("Jobs to run at once, 4 by default");
+    /// }).unwrap();
+    ///
+    /// let args = vec!["./runner".to_string()];
+    /// assert_eq!(parser.parse(args.iter()).unwrap().get("jobs"), Some(4));
+    /// assert!(parser.help_text().contains("4 by default"));
+    /// ```
+    pub fn modify_opt<F: FnOnce(&mut ArgMut)>(&mut self, name: &str, f: F) -> Result<(), &'static str> {
+        self.arg_mut(name).map(|arg| f(&mut ArgMut { arg }))
+    }
+    
+    /// Checks the definitions of the arguments for mistakes that would
+    /// otherwise only show up when the program is run, such as two
+    /// arguments sharing a short flag. Call it from a test.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, ConfigError};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of user", ArgType::Option);
+    /// assert_eq!(parser.validate(), Ok(()));
+    ///
+    /// parser.add_opt("height", None, 'h', false,
+    ///     "Height of user", ArgType::Option);
+    /// assert_eq!(parser.validate(), Err(vec![ConfigError::DuplicateShort {
+    ///     flag: 'h',
+    ///     names: ("help".into(), "height".into()),
+    /// }]));
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let args = &self.defs.args;
+        let mut errors = Vec::new();
+        
+        for (i, a) in args.iter().enumerate() {
+            for b in args[i + 1..].iter() {
+                let names = || (a.name.clone(), b.name.clone());
+                
+                if short(a).is_some() && a.flag == b.flag {
+                    errors.push(ConfigError::DuplicateShort { flag: a.flag, names: names() });
+                }
+                
+                if let (ArgType::Positional(x), ArgType::Positional(y)) = (&a.type_, &b.type_) {
+                    if x == y {
+                        errors.push(ConfigError::DuplicatePosition { index: *x, names: names() });
+                    }
                 }
-            } else {
-                return None;
             }
+            
+            if a.required && a.default.is_some() {
+                errors.push(ConfigError::RequiredWithDefault { name: a.name.clone() });
+            }
+        }
+        
+        let indices: Vec<u8> = args.iter()
+            .filter_map(|a| match a.type_ {
+                ArgType::Positional(idx) => Some(idx),
+                _ => None,
+            })
+            .collect();
+        
+        if let Some(&last) = indices.iter().max() {
+            for index in (0..last).filter(|i| !indices.contains(i)) {
+                errors.push(ConfigError::PositionalGap { index });
+            }
+        }
+        
+        for group in self.groups.iter() {
+            for name in group.names.iter().filter(|&name| !self.defs.names.contains_key(name)) {
+                errors.push(ConfigError::UnknownInGroup { name: name.clone() });
+            }
+            
+            if group.min > group.names.len() {
+                errors.push(ConfigError::UnsatisfiableGroup { min: group.min, names: group.names.clone() });
+            }
+        }
+        
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+    
+    /// Whether the argument that would take the `n`th positional
+    /// argument, in the given segment, takes negative numbers
+    fn takes_number(&self, n: usize, segment: u8) -> bool {
+        let claimed = self.defs.args.iter().any(|def| def.type_ == ArgType::Positional(n as u8));
+        
+        self.defs.args.iter().any(|def| def.negative && match def.type_ {
+            ArgType::Positional(idx) => idx as usize == n,
+            ArgType::PositionalList(group) => group == segment && !claimed,
+            ArgType::Command | ArgType::Remainder => !claimed,
+            _ => false,
         })
-}
-
-/// Function that parses `Dict` arguments into `HashMap`s.
-/// Provided for user convenience and use as an implementor of
-/// [`ArgGetter`](./trait.ArgGetter.html).
-/// # Panics
-/// Panics if improper or no separator is found (expects `key:value key2:value2...`)
-pub fn hashmap_parser<K, V>(s: &str) -> Option<HashMap<K,V>> 
-    where K: FromStr + Hash + Eq,
-          V: FromStr {
-    s.split_whitespace()
-        .map(|x| {
-            let colpos = x.find(':')
-                .expect("No separator found in dict map argument");
-            let (k, v) = x.split_at(colpos);
-            let v = &v[1..];
-            (k, v)
-        })
-        .map(|(k, v)| {
-            k.parse().ok().and_then(|k2|
-                v.parse().ok().map(|v2| (k2, v2)))
-        })
-        .enumerate()
-        .fold(None, |acc, (idx, elem)| {
-            if let Some((k, v)) = elem {
-                if idx == 0 {
-                    let mut h = HashMap::new();
-                    h.insert(k,v);
-                    return Some(h);
-                } else {
-                    return acc.map(|mut h| {
-                        h.insert(k, v);
-                        h
-                    });
+    }
+    
+    /// Finds the index of the argument a flag refers to, written just as
+    /// it was defined. When two arguments share a short flag, the one
+    /// added last wins.
+    fn lookup(&self, flag: Flag, kebab_case: KebabCase) -> Option<usize> {
+        match flag {
+            Flag::Short(c) => self.defs.shorts.get(&c).cloned(),
+            Flag::Long(name) => {
+                let dashed = || match kebab_case {
+                    KebabCase::Off => None,
+                    KebabCase::On | KebabCase::Both => self.defs.kebab.get(name).cloned(),
+                };
+                
+                self.defs.names.get(name).cloned()
+                    .filter(|_| kebab_case != KebabCase::On || !name.contains('_'))
+                    .or_else(dashed)
+            }
+        }
+    }
+    
+    /// Every long flag the arguments answer to, with their index. These
+    /// are all built when arguments are added, so that looking flags up
+    /// doesn't format any.
+    fn long_flags(&self, kebab_case: KebabCase) -> impl Iterator<Item = (&str, usize)> {
+        // In kebab case only, a name with underscores isn't a flag as is
+        let plain = self.defs.names.iter()
+            .filter(move |&(name, _)| kebab_case != KebabCase::On || !name.contains('_'));
+        let dashed = self.defs.kebab.iter()
+            .filter(move |_| kebab_case != KebabCase::Off);
+        
+        plain.chain(dashed).map(|(f, &idx)| (f.as_str(), idx))
+    }
+    
+    /// Looks up a flag as `lookup` does, then as the options allow.
+    /// When several arguments match, they are the error, in the order
+    /// they were added.
+    fn lookup_with(&self, flag: Flag, opts: &ParseOptions) -> Result<Option<usize>, Vec<usize>> {
+        if let Some(idx) = self.lookup(flag, opts.kebab_case) {
+            return Ok(Some(idx));
+        }
+        
+        let unique = |mut hits: Vec<usize>| {
+            hits.sort_unstable();
+            hits.dedup();
+            if hits.len() > 1 { Err(hits) } else { Ok(hits.pop()) }
+        };
+        
+        match flag {
+            Flag::Short(_) if opts.case_sensitive => Ok(None),
+            Flag::Short(c) => unique(self.defs.shorts.iter()
+                .filter(|&(s, _)| s.to_lowercase().eq(c.to_lowercase()))
+                .map(|(_, &idx)| idx)
+                .collect()),
+            Flag::Long(_) if opts.case_sensitive && !opts.abbreviations => Ok(None),
+            Flag::Long(given) => {
+                let given = fold(given, opts.case_sensitive);
+                let exact = unique(self.long_flags(opts.kebab_case)
+                    .filter(|&(f, _)| fold(f, opts.case_sensitive) == given)
+                    .map(|(_, idx)| idx)
+                    .collect())?;
+                
+                match exact {
+                    Some(idx) => Ok(Some(idx)),
+                    None => unique(self.long_flags(opts.kebab_case)
+                        .filter(|&(f, _)| opts.abbreviations && fold(f, opts.case_sensitive).starts_with(&*given))
+                        .map(|(_, idx)| idx)
+                        .collect()),
                 }
-            } else {
-                return None;
             }
-        })
-}
-
-fn ops(a: &Arg, name: &str) -> String {
-    if a.type_ == ArgType::Option {
-        name.chars().map(|c| c.to_uppercase().next().unwrap_or(c)).collect::<String>()
-    } else if a.type_ == ArgType::List {
-        name.chars().map(|c| c.to_uppercase().next().unwrap_or(c)).chain("...".chars()).collect::<String>()
-    } else if a.type_ == ArgType::Dict {
-        "k:v k2:v2...".into()
-    } else {
-        String::new()
+        }
     }
-}
-
-fn is_flag(s: &str) -> bool {
-    if s.len() < 2 {
-        return false;
+    
+    /// The error for a flag, `shown` as given, that `lookup_with` found
+    /// several arguments for
+    fn ambiguous(&self, shown: &str, flag: Flag, candidates: &[usize], opts: &ParseOptions) -> String {
+        let flags: Vec<String> = candidates.iter()
+            .map(|&idx| &self.defs.args[idx])
+            .map(|def| match flag {
+                Flag::Short(_) => format!("-{}", def.flag),
+                Flag::Long(_) => format!("--{}", opts.kebab_case.flag(&def.name)),
+            })
+            .collect();
+        
+        Messages::fill_value(&self.messages.ambiguous_flag, shown, &listed(&flags))
+    }
+    
+    /// Parse a set of arguments, given the previous configuration.
+    /// The parser is left untouched, so it can be called again with
+    /// other arguments.
+    /// # Example
+    /// ```
+    /// // add an option that is a `Flag`, with no default value, with
+    /// // a long form of `--verbose`, short form of `v`, that is not
+    /// // required to be passed, and has a default value of `false`
+    ///
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("verbose", Some("false"), 'v', false,
+    ///     "Whether to produce verbose output", ArgType::Flag);
+    ///
+    /// // Normally you'd get this from std::env::args().iter()
+    /// let test_1 = "./runner --verbose".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    /// 
+    /// if let Ok(p_res) = parser.parse(test_1.iter()) {
+    ///     // do stuff here
+    /// }
+    /// ```
+    pub fn parse<'a, I: Iterator<Item = &'a String>> (&self, args: I) -> ParseResult {
+        let original = self.copy_args(args, &self.options)?;
+        
+        self.parse_options(&original, &self.options).and_then(|res| self.finish(res, &original, &self.options))
+    }
+    
+    /// Parse a set of arguments like `parse` does, taking them as
+    /// `env::args_os` gives them, which unlike `env::args` doesn't panic
+    /// on arguments that aren't valid UTF-8. Those are read with their
+    /// invalid bytes replaced by `U+FFFD`, but the bytes of each are
+    /// kept for `ArgParseResults::get_raw_bytes` to return.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', false,
+    ///     "Name of user", ArgType::Option);
+    ///
+    /// let p_res = parser.parse_os(&["./runner", "--name", "Johnny"]).unwrap();
+    /// assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
+    /// assert_eq!(p_res.get_raw_bytes("name"), Some(b"Johnny".to_vec()));
+    /// ```
+    pub fn parse_os<I, S>(&self, args: I) -> ParseResult
+        where I: IntoIterator<Item = S>,
+              S: AsRef<OsStr> {
+        
+        let mut raw = Vec::new();
+        let mut lossy = false;
+        let args: Vec<String> = args.into_iter().map(|arg| {
+            let bytes = arg.as_ref().as_encoded_bytes();
+            let text = arg.as_ref().to_string_lossy().into_owned();
+            lossy |= text.as_bytes() != bytes;
+            raw.push(bytes.to_vec());
+            text
+        }).collect();
+        
+        let mut res = self.parse(args.iter())?;
+        if lossy {
+            res.raw = raw;
+        }
+        Ok(res)
+    }
+    
+    /// Parse a set of arguments like `parse` does, reading them as
+    /// `opts` says rather than as the parser's `options` do.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, ParseOptions};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', false,
+    ///     "Name of user", ArgType::Option);
+    ///
+    /// let test_1 = "./runner --name Johnny --colour".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// assert!(parser.parse(test_1.iter()).is_ok());
+    ///
+    /// let strict = ParseOptions { strict: true, ..ParseOptions::default() };
+    /// let err = parser.parse_with(test_1.iter(), strict).unwrap_err();
+    /// assert_eq!(err.message(), "Unknown flag `--colour`");
+    /// ```
+    pub fn parse_with<'a, I: Iterator<Item = &'a String>> (&self, args: I, opts: ParseOptions) -> ParseResult {
+        let original = self.copy_args(args, &opts)?;
+        
+        self.parse_options(&original, &opts).and_then(|res| self.finish(res, &original, &opts))
+    }
+    
+    /// Parse the arguments in a single line, split as a shell would
+    /// split them (see `lexer::split_line`). Unlike `parse`, the line
+    /// doesn't start with the program name. This makes testing a
+    /// parser's definitions easy; see also `assert_parse_ok!`,
+    /// `assert_parse_err!` and the `testing` module.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of user", ArgType::Option);
+    /// parser.add_opt("frequencies", None, 'f', false,
+    ///     "User's favorite frequencies", ArgType::List);
+    ///
+    /// let p_res = parser.parse_line("--name 'Johnny Smith' -f 1 2 3").unwrap();
+    /// assert_eq!(p_res.get::<String>("name"), Some("Johnny Smith".into()));
+    /// assert_eq!(p_res.get_list("frequencies"), Some(vec![1, 2, 3]));
+    /// ```
+    pub fn parse_line(&self, line: &str) -> ParseResult {
+        let mut args = vec![self.name.clone()];
+        args.extend(lexer::split_line(line));
+        self.rewrite(&mut args);
+        
+        self.parse_options(&args, &self.options).and_then(|res| self.finish(res, &args, &self.options))
+    }
+    
+    /// Parses each line read from `input` on its own with `parse_line`,
+    /// as in an interactive shell, skipping blank lines. Every line
+    /// gets results of its own: nothing given on one line carries over
+    /// to the next.
+    /// # Example
+    /// ```
+    /// use std::io::Cursor;
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("shell".into());
+    /// parser.add_opt("name", Some("nobody"), 'n', false,
+    ///     "Name of user", ArgType::Option);
+    ///
+    /// let input = Cursor::new("--name Johnny\n\n--name\n--help\n");
+    /// let results: Vec<_> = parser.parse_lines(input).map(|r| r.unwrap()).collect();
+    ///
+    /// assert_eq!(results.len(), 3);
+    /// assert_eq!(results[0].as_ref().unwrap().get::<String>("name"), Some("Johnny".into()));
+    /// assert!(results[1].is_err());
+    /// assert_eq!(results[2].as_ref().unwrap().get::<String>("name"), Some("nobody".into()));
+    /// ```
+    pub fn parse_lines<R: BufRead>(&self, input: R) -> ParseLines<'_, R> {
+        ParseLines { parser: self, lines: input.lines() }
+    }
+    
+    /// Reads the arguments one flag or value at a time, the first being
+    /// the program name as with `parse`, as an iterator of what it finds.
+    /// Nothing is read before it is asked for, and nothing is kept, so
+    /// this suits very long command lines or handling arguments as they
+    /// come. It is a lower level than `parse`, though it follows the same
+    /// rules as the parser's `options` say: flags are looked up, values
+    /// checked against `choices` and validators, negative numbers read
+    /// as values or flags, and a `Command` or `Remainder` argument takes
+    /// the rest of the arguments. But positionals aren't assigned to
+    /// arguments, and nothing that needs the whole command line is
+    /// checked or done, such as required arguments, repeated `Dict` keys
+    /// or leaving the last values of a `List` to positionals after it.
+    /// Middleware can't run on borrowed arguments, so for a parser with
+    /// any the only event is an error.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, ParseEvent};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of user", ArgType::Option);
+    /// parser.add_opt("frequencies", None, 'f', false,
+    ///     "User's favorite frequencies", ArgType::List);
+    ///
+    /// let test_1 = "./runner -n Johnny in.txt -f 1 2 --colour".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// let events: Vec<_> = parser.parse_iter(&test_1).collect();
+    /// assert_eq!(events, vec![
+    ///     ParseEvent::ArgMatched { name: "name", values: vec!["Johnny"], position: 1 },
+    ///     ParseEvent::PositionalFound { value: "in.txt", position: 3 },
+    ///     ParseEvent::ArgMatched { name: "frequencies", values: vec!["1", "2"], position: 4 },
+    ///     ParseEvent::Unknown { flag: "--colour".into(), position: 7 },
+    /// ]);
+    /// ```
+    pub fn parse_iter<'a>(&'a self, args: &'a [String]) -> ParseEvents<'a> {
+        let rest = args.get(1..).unwrap_or(&[]);
+        let tokens = Lexer::new(rest)
+            .numbers_as_flags(self.options.negative_numbers == NegativeNumbers::Flags)
+            .peekable();
+        
+        let mut events = ParseEvents {
+            parser: self,
+            args,
+            tokens,
+            split: VecDeque::new(),
+            positionals: 0,
+            last_positional: None,
+            segment: 0,
+            terminated: false,
+            flags_ended: false,
+            done: false,
+            refused: None,
+        };
+        events.refused = self.refuse_middleware(args).err();
+        events
+    }
+    
+    /// Parse a set of arguments like `parse` does, but without copying
+    /// them: the results borrow their values from `args`. Middleware
+    /// can't run on borrowed arguments, so this is an error for a parser
+    /// with any.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of user", ArgType::Option);
+    ///
+    /// let test_1 = "./runner --name Johnny".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    /// 
+    /// let p_res = parser.parse_borrowed(&test_1).unwrap();
+    /// assert_eq!(p_res.get_str("name"), Some("Johnny"));
+    /// ```
+    pub fn parse_borrowed<'a>(&self, args: &'a [String]) -> Result<BorrowedResults<'a>, ParseError> {
+        self.refuse_middleware(args)?;
+        self.parse_options(args, &self.options)
+    }
+    
+    /// The error for borrowed parsing of a parser with middleware
+    fn refuse_middleware(&self, args: &[String]) -> Result<(), ParseError> {
+        if self.rewrites.is_empty() && self.finishers.is_empty() {
+            return Ok(());
+        }
+        
+        let mut err = ParseError::new(self.messages.middleware.to_string(), args, None, self);
+        err.color = self.options.color.unwrap_or(err.color);
+        Err(err)
+    }
+    
+    /// Copies the arguments to parse, unquoting and interpolating them
+    /// if the options say to
+    fn copy_args<'a, I: Iterator<Item = &'a String>>(&self, args: I, opts: &ParseOptions) -> Result<Vec<String>, ParseError> {
+        let mut args: Vec<String> = args.cloned().collect();
+        self.rewrite(&mut args);
+        
+        for pos in 1..args.len() {
+            if opts.unquote {
+                args[pos] = lexer::unquote(&args[pos]).into_owned();
+            }
+            
+            if opts.interpolate == Interpolate::All {
+                match interpolate(&args[pos]) {
+                    Ok(Cow::Borrowed(_)) => {}
+                    Ok(Cow::Owned(value)) => args[pos] = value,
+                    Err(reason) => {
+                        let err = self.messages.invalid_argument.replace("{value}", &args[pos]).replace("{reason}", &reason);
+                        let mut err = ParseError::new(err, &args, Some(pos), self);
+                        err.color = opts.color.unwrap_or(err.color);
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        
+        Ok(args)
+    }
+    
+    /// Runs the `before_parse` middleware
+    fn rewrite(&self, args: &mut Vec<String>) {
+        for Rewrite(f) in self.rewrites.iter() {
+            f(args);
+        }
+    }
+    
+    /// Copies the results out of the arguments, and runs the
+    /// `after_parse` middleware on them
+    fn finish(&self, res: BorrowedResults, args: &[String], opts: &ParseOptions) -> ParseResult {
+        let mut res = res.into_owned();
+        
+        for Finish(f) in self.finishers.iter() {
+            if let Err(msg) = f(&mut res) {
+                let mut err = ParseError::new(msg, args, None, self);
+                err.color = opts.color.unwrap_or(err.color);
+                return Err(err);
+            }
+        }
+        
+        Ok(res)
+    }
+    
+    fn parse_options<'a>(&self, args: &'a [String], opts: &ParseOptions) -> Result<BorrowedResults<'a>, ParseError> {
+        self.parse_inner(args, opts).map_err(|mut err| {
+            err.color = opts.color.unwrap_or(err.color);
+            err
+        })
+    }
+    
+    fn parse_inner<'a>(&self, args: &'a [String], opts: &ParseOptions) -> Result<BorrowedResults<'a>, ParseError> {
+        let error = |msg: String, pos: Option<usize>| {
+            Err(ParseError::new(msg, args, pos, self))
+        };
+        
+        if self.defs.args.is_empty() {
+            return error(self.messages.no_arguments.to_string(), None);
+        }
+        
+        // The first element is the program name
+        let rest = args.get(1..).unwrap_or(&[]);
+        let mut tokens: Vec<(usize, Token<'a>)> = Lexer::new(rest)
+            .numbers_as_flags(opts.negative_numbers == NegativeNumbers::Flags)
+            .map(|(pos, token)| (pos + 1, token))
+            .collect();
+        
+        // Under `NegativeNumbers::PerArg` negative numbers are lexed as
+        // values, and split into flags where the argument taking them
+        // doesn't take numbers
+        let terminator = tokens.iter()
+            .find(|&&(_, token)| token == Token::Terminator)
+            .map_or(args.len(), |&(pos, _)| pos);
+        let splittable = |(pos, token): (usize, Token)| match token {
+            Token::Value(v) => opts.negative_numbers == NegativeNumbers::PerArg
+                && pos < terminator && lexer::is_negative_number(v),
+            _ => false,
+        };
+        let as_flags = |pos: usize| Lexer::new(&args[pos..=pos])
+            .numbers_as_flags(true)
+            .map(move |(_, token)| (pos, token))
+            .collect::<Vec<_>>();
+        
+        let check = |def: &Arg, val: &str, pos: usize| {
+            let invalid = |reason: String| {
+                let err = Messages::fill_value(&self.messages.invalid_value, &def.name, val)
+                    .replace("{reason}", &reason);
+                ParseError::new(err, args, Some(pos), self)
+            };
+            
+            if !def.choices.is_empty() && choice(def, val).is_none() {
+                return Err(invalid(format!("expected one of {}", listed(&def.choices))));
+            }
+            
+            match def.validator {
+                Some(Validator(ref f)) => f(val).map_err(invalid),
+                None => Ok(()),
+            }
+        };
+        
+        // The arguments from `start` on, as given, for a `Command`
+        let trailing = |def: &Arg, start: usize| {
+            let mut vals = Vec::new();
+            
+            for (pos, val) in args.iter().enumerate().skip(start) {
+                check(def, val, pos)?;
+                vals.push(Cow::Borrowed(val.as_str()));
+            }
+            
+            Ok(vals)
+        };
+        
+        // Positionals taken by index are left out of the groups
+        let claimed: Vec<usize> = self.defs.args.iter()
+            .filter_map(|def| match def.type_ {
+                ArgType::Positional(idx) => Some(idx as usize),
+                _ => None,
+            })
+            .collect();
+        let command = self.defs.args.iter().position(|def| def.type_ == ArgType::Command);
+        let remainder = self.defs.args.iter().position(|def| def.type_ == ArgType::Remainder);
+        
+        let mut found = vec![Found::default(); self.defs.args.len()];
+        let mut warnings = Vec::new();
+        let mut positionals = Vec::new();
+        let mut segment = 0;
+        // Without permuting, flags end at the first positional
+        let mut flags_ended = false;
+        let mut terminated = None;
+        
+        let mut i = 0;
+        while i < tokens.len() && terminated.is_none() {
+            let (origin, token) = tokens[i];
+            i += 1;
+            
+            let token = match token {
+                // Combined short flags are one positional
+                _ if flags_ended && positionals.last().is_some_and(|&(pos, _, _)| pos == origin) => continue,
+                _ if flags_ended => Token::Value(args[origin].as_str()),
+                token => token,
+            };
+            
+            let (flag, attached) = match token {
+                Token::Value(_) if !flags_ended && splittable((origin, token)) && !self.takes_number(positionals.len(), segment) => {
+                    i -= 1;
+                    tokens.splice(i..=i, as_flags(origin));
+                    continue;
+                }
+                Token::Value(v) => {
+                    if let Some(idx) = command.or(remainder).filter(|_| !claimed.contains(&positionals.len())) {
+                        found[idx].vals = Some(trailing(&self.defs.args[idx], origin)?);
+                        found[idx].origin = Some(origin);
+                        break;
+                    }
+                    
+                    positionals.push((origin, v, segment));
+                    flags_ended = !opts.permute;
+                    continue;
+                }
+                Token::Terminator => {
+                    if let Some(idx) = command {
+                        let vals = trailing(&self.defs.args[idx], origin + 1)?;
+                        found[idx].vals = Some(vals).filter(|v| !v.is_empty());
+                        found[idx].origin = Some(origin + 1);
+                        break;
+                    }
+                    
+                    segment += 1;
+                    continue;
+                }
+                Token::ShortFlag(c) => (Flag::Short(c), None),
+                Token::LongFlag(name) => (Flag::Long(name), None),
+                Token::Attached { flag, value } => (flag, Some(value)),
+            };
+            
+            // Unknown flags start the remainder, or are skipped over, and
+            // flags that could be several arguments fail
+            let shown = || match flag {
+                Flag::Short(c) => format!("-{}", c),
+                Flag::Long(name) => format!("--{}", name),
+            };
+            let idx = match (self.lookup_with(flag, opts), remainder) {
+                (Ok(Some(idx)), _) => idx,
+                (Err(candidates), _) => return error(self.ambiguous(&shown(), flag, &candidates, opts), Some(origin)),
+                (Ok(None), Some(idx)) => {
+                    found[idx].vals = Some(trailing(&self.defs.args[idx], origin)?);
+                    found[idx].origin = Some(origin);
+                    break;
+                }
+                (Ok(None), None) => {
+                    let flag = shown();
+                    if opts.strict {
+                        return error(Messages::fill(&self.messages.unknown_flag, &flag), Some(origin));
+                    }
+                    
+                    warnings.push(Warning::UnknownFlag { flag, position: origin });
+                    continue;
+                }
+            };
+            
+            let def = &self.defs.args[idx];
+            for other in def.overrides.iter().filter_map(|name| self.defs.names.get(name)) {
+                found[*other] = Found::default();
+            }
+            
+            let arg = &mut found[idx];
+            arg.count = arg.count.saturating_add(1);
+            
+            match def.deprecated {
+                Some(ref note) if arg.count == 1 => {
+                    warnings.push(Warning::Deprecated { name: def.name.clone(), note: note.clone() });
+                }
+                _ => {}
+            }
+            match def.max_occurrences {
+                Some((max, TooMany::Error)) if arg.count > max => {
+                    let err = Messages::fill_value(&self.messages.too_many, &def.name, &max.to_string());
+                    return error(err, Some(origin));
+                }
+                Some((max, TooMany::Warn)) if arg.count > max => {
+                    warnings.push(Warning::TooMany { name: def.name.clone(), max, position: origin });
+                }
+                _ if arg.vals.is_some() && replaces_values(def) => {
+                    warnings.push(Warning::Overridden { name: def.name.clone(), position: origin });
+                }
+                _ => {}
+            }
+            if def.terminates {
+                terminated = Some(idx);
+            }
+            
+            // Only lists take more than the next value
+            let takes = match def.type_ {
+                ArgType::List | ArgType::Dict => tokens.len(),
+                _ => i + 1,
+            };
+            let number = tokens[i..takes.min(tokens.len())].iter()
+                .take_while(|&&(_, token)| matches!(token, Token::Value(_)))
+                .position(|&t| splittable(t));
+            if let (Some(at), None, false) = (number, attached, def.negative) {
+                let pos = tokens[i + at].0;
+                tokens.splice(i + at..=i + at, as_flags(pos));
+            }
+            
+            let missing = || {
+                let err = Messages::fill(&self.messages.missing_value, &def.name);
+                error(err, Some(origin))
+            };
+            
+            let mut single = || match (attached, tokens.get(i)) {
+                (Some(val), _) => Some((origin, val)),
+                (None, Some(&(pos, Token::Value(val)))) => {
+                    i += 1;
+                    Some((pos, val))
+                }
+                _ => None,
+            };
+            
+            match def.type_ {
+                ArgType::Flag => {
+                    // `--flag=no` is read leniently, and stored as `get::<bool>` expects
+                    let val = match attached.map(bool_parser) {
+                        None | Some(Some(true)) => "true",
+                        Some(Some(false)) => "false",
+                        Some(None) => {
+                            let val = attached.unwrap_or_default();
+                            let err = Messages::fill_value(&self.messages.invalid_value, &def.name, val)
+                                .replace("{reason}", BOOL_EXPECTED);
+                            return error(err, Some(origin));
+                        }
+                    };
+                    
+                    check(def, val, origin)?;
+                    arg.vals = Some(vec![val.into()]);
+                }
+                ArgType::Counter => {
+                    if let Some(val) = attached {
+                        let err = Messages::fill_value(&self.messages.invalid_value, &def.name, val)
+                            .replace("{reason}", NO_VALUE);
+                        return error(err, Some(origin));
+                    }
+                    
+                    arg.vals = Some(vec![arg.count.to_string().into()]);
+                }
+                ArgType::Option => {
+                    let (pos, val) = match single() {
+                        Some(found) => found,
+                        None => return missing(),
+                    };
+                    
+                    check(def, val, pos)?;
+                    arg.vals = match (arg.vals.take(), &def.joiner) {
+                        (Some(before), Some(joiner)) => Some(vec![format!("{}{}{}", before[0], joiner, val).into()]),
+                        _ => Some(vec![val.into()]),
+                    };
+                    arg.origin = Some(pos);
+                }
+                ArgType::Path => {
+                    let (pos, val) = match single() {
+                        Some(found) => found,
+                        None => return missing(),
+                    };
+                    
+                    check(def, val, pos)?;
+                    arg.origin = Some(pos);
+                    match normalize_path(def.paths, val) {
+                        Ok(path) => arg.vals = Some(vec![path]),
+                        Err(reason) => {
+                            let err = Messages::fill_value(&self.messages.invalid_value, &def.name, val)
+                                .replace("{reason}", &reason);
+                            return error(err, Some(pos));
+                        }
+                    }
+                }
+                ArgType::Json => {
+                    let (pos, val) = match single() {
+                        Some(found) => found,
+                        None => return missing(),
+                    };
+                    
+                    if let Err(reason) = check_json(val) {
+                        let err = Messages::fill_value(&self.messages.invalid_value, &def.name, val)
+                            .replace("{reason}", &reason);
+                        return error(err, Some(pos));
+                    }
+                    
+                    check(def, val, pos)?;
+                    arg.vals = Some(vec![val.into()]);
+                    arg.origin = Some(pos);
+                }
+                ArgType::List | ArgType::Dict => {
+                    let mut given: Vec<(usize, &str)> = match attached {
+                        Some(val) => vec![(origin, val)],
+                        None if i == tokens.len() => return missing(),
+                        None => tokens[i..].iter()
+                            .map_while(|&(pos, token)| match token {
+                                Token::Value(val) if def.terminator.as_deref() != Some(val) => Some((pos, val)),
+                                _ => None,
+                            })
+                            .take(match def.list_values {
+                                ListValues::Greedy => usize::MAX,
+                                ListValues::AtMost(n) => n,
+                            })
+                            .collect(),
+                    };
+                    
+                    // Values at the end are reserved for positionals not given yet
+                    if attached.is_none() && def.list_values == ListValues::Greedy && i + given.len() == tokens.len() {
+                        let open = self.defs.args.iter()
+                            .filter(|p| match p.type_ {
+                                ArgType::Positional(idx) => idx as usize >= positionals.len() && p.default.is_none(),
+                                _ => false,
+                            })
+                            .count();
+                        let keep = given.len().saturating_sub(open).max(1);
+                        given.truncate(keep);
+                    }
+                    
+                    i += if attached.is_some() { 0 } else { given.len() };
+                    
+                    if let (None, Some(&(_, Token::Value(val)))) = (attached, tokens.get(i)) {
+                        if def.terminator.as_deref() == Some(val) {
+                            i += 1;
+                        }
+                    }
+                    
+                    for &(pos, val) in given.iter() {
+                        check(def, val, pos)?;
+                    }
+                    
+                    // Greedy lists start over, other occurrences add to the values
+                    let first = given.first().map(|&(pos, _)| pos);
+                    if (def.type_ == ArgType::List && def.list_values == ListValues::Greedy) || arg.origin.is_none() {
+                        arg.origin = first;
+                    }
+                    
+                    if def.type_ == ArgType::Dict {
+                        // Occurrences add to the same map, with one value per pair
+                        let mut pairs = arg.pairs.take().unwrap_or_default();
+                        let mut vals = arg.vals.take().unwrap_or_default();
+                        
+                        for (pos, val) in given {
+                            let pair = match val.split_once(':') {
+                                Some(pair) => pair,
+                                None => {
+                                    let err = Messages::fill_value(&self.messages.malformed_pair, &def.name, val);
+                                    return error(err, Some(pos));
+                                }
+                            };
+                            
+                            match (pairs.iter().position(|&(k, _)| k == pair.0), def.duplicates) {
+                                (None, _) | (Some(_), DuplicateKeys::Keep) => {
+                                    pairs.push(pair);
+                                    vals.push(val.into());
+                                }
+                                (Some(at), DuplicateKeys::LastWins) => {
+                                    pairs[at] = pair;
+                                    vals[at] = val.into();
+                                }
+                                (Some(_), DuplicateKeys::FirstWins) => {}
+                                (Some(_), DuplicateKeys::Error) => {
+                                    let err = Messages::fill_value(&self.messages.duplicate_key, &def.name, pair.0);
+                                    return error(err, Some(pos));
+                                }
+                            }
+                        }
+                        
+                        arg.pairs = Some(pairs);
+                        arg.vals = Some(vals);
+                        continue;
+                    }
+                    
+                    let mut vals = match def.list_values {
+                        ListValues::Greedy => Vec::with_capacity(given.len()),
+                        ListValues::AtMost(_) => arg.vals.take().unwrap_or_default(),
+                    };
+                    for (pos, val) in given {
+                        match expand_glob(def, val) {
+                            Ok(expanded) => vals.extend(expanded),
+                            Err(reason) => {
+                                let err = Messages::fill_value(&self.messages.invalid_value, &def.name, val)
+                                    .replace("{reason}", &reason);
+                                return error(err, Some(pos));
+                            }
+                        }
+                    }
+                    
+                    arg.vals = Some(vals);
+                }
+                ArgType::Command | ArgType::Remainder => {
+                    let mut vals = match attached {
+                        Some(val) => {
+                            check(def, val, origin)?;
+                            vec![val.into()]
+                        }
+                        None => Vec::new(),
+                    };
+                    vals.extend(trailing(def, origin + 1)?);
+                    arg.origin = Some(if attached.is_some() { origin } else { origin + 1 });
+                    
+                    if vals.is_empty() {
+                        return missing();
+                    }
+                    
+                    arg.vals = Some(vals);
+                    break;
+                }
+                ArgType::Positional(_) | ArgType::PositionalList(_) => {}
+            }
+        }
+        
+        for (def, v) in self.defs.args.iter().zip(found.iter_mut()) {
+            match def.type_ {
+                ArgType::Positional(idx) => {
+                    if let Some(&(pos, x, _)) = positionals.get(idx as usize) {
+                        check(def, x, pos)?;
+                        v.vals = Some(vec![x.into()]);
+                        v.origin = Some(pos);
+                    }
+                }
+                ArgType::PositionalList(group) => {
+                    let mut vals = Vec::new();
+                    
+                    for (n, &(pos, x, seg)) in positionals.iter().enumerate() {
+                        if seg == group && !claimed.contains(&n) {
+                            check(def, x, pos)?;
+                            vals.push(x.into());
+                            v.origin = v.origin.or(Some(pos));
+                        }
+                    }
+                    
+                    if !vals.is_empty() {
+                        v.vals = Some(vals);
+                    }
+                }
+                ArgType::Counter if v.vals.is_none() && def.default.is_none() => {
+                    v.vals = Some(vec!["0".into()]);
+                }
+                _ => {}
+            }
+        }
+        
+        // Choices matched in another case are stored in their own
+        for (def, v) in self.defs.args.iter().zip(found.iter_mut()).filter(|&(def, _)| def.ignore_case) {
+            for val in v.vals.iter_mut().flatten() {
+                match choice(def, val) {
+                    Some(canonical) if canonical != val => *val = Cow::Owned(canonical.clone()),
+                    _ => {}
+                }
+            }
+        }
+        
+        // Interpolated defaults go in a copy of the definitions
+        let mut defs = self.defs.clone();
+        if opts.interpolate != Interpolate::Never {
+            for (idx, def) in self.defs.args.iter().enumerate() {
+                let default = match def.default {
+                    Some(ref default) => default,
+                    None => continue,
+                };
+                
+                match interpolate(default) {
+                    Ok(Cow::Borrowed(_)) => {}
+                    Ok(Cow::Owned(value)) => {
+                        Arc::make_mut(&mut defs).args[idx].default = stored_default(&def.type_, Some(&value));
+                    }
+                    Err(reason) => {
+                        let err = Messages::fill_value(&self.messages.invalid_value, &def.name, default)
+                            .replace("{reason}", &reason);
+                        return error(err, None);
+                    }
+                }
+            }
+        }
+        
+        if terminated.is_some() {
+            return Ok(BorrowedResults { defs, found, warnings, terminated });
+        }
+
+        // Defaults of paths are normalized like given values
+        for (def, v) in defs.args.iter().zip(found.iter_mut()) {
+            if def.paths == PathOptions::default() {
+                continue;
+            }
+            
+            if let (&ArgType::Path, None, Some(default)) = (&def.type_, &v.vals, &def.default) {
+                match normalize_path(def.paths, default) {
+                    Ok(path) => v.vals = Some(vec![path.into_owned().into()]),
+                    Err(reason) => {
+                        let err = Messages::fill_value(&self.messages.invalid_value, &def.name, default)
+                            .replace("{reason}", &reason);
+                        return error(err, None);
+                    }
+                }
+            }
+        }
+        
+        let exclusive = self.defs.args.iter().zip(found.iter())
+            .position(|(def, v)| def.exclusive && given(def, v));
+        if let Some(idx) = exclusive {
+            let others = self.defs.args.iter().zip(found.iter()).enumerate()
+                .any(|(i, (def, v))| i != idx && given(def, v));
+            let unknown = warnings.iter().any(|w| matches!(*w, Warning::UnknownFlag { .. }));
+            
+            if others || unknown {
+                return error(Messages::fill(&self.messages.exclusive, &self.defs.args[idx].name), None);
+            }
+            
+            return Ok(BorrowedResults { defs, found, warnings, terminated });
+        }
+        
+        let present = |(def, v): (&Arg, &Found)| !def.required | v.vals.is_some() | def.default.is_some();
+        if !self.defs.args.iter().zip(found.iter()).all(present) {
+            return error(self.messages.missing_required.to_string(), None);
+        }
+        
+        for group in self.groups.iter() {
+            let given: Vec<&String> = group.names.iter()
+                .filter(|&name| self.defs.names.get(name).is_some_and(|&idx| given(&self.defs.args[idx], &found[idx])))
+                .collect();
+            
+            if given.len() < group.min {
+                let list = if given.is_empty() { String::new() } else { format!(" ({})", listed(&given)) };
+                let err = self.messages.too_few_in_group
+                    .replace("{min}", &group.min.to_string())
+                    .replace("{names}", &listed(&group.names))
+                    .replace("{count}", &given.len().to_string())
+                    .replace("{given}", &list);
+                return error(err, None);
+            }
+        }
+        
+        Ok(BorrowedResults { defs, found, warnings, terminated })
+    }
+
+    /// Prints the help message, which is constructed based on the options
+    /// used. This is the only method of the crate that writes anywhere;
+    /// use `help_text` to get the message as a string instead.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("verbose", Some("false"), 'v', false,
+    ///     "Whether to produce verbose output", ArgType::Flag);
+    ///
+    /// // Normally you'd get this from std::env::args().iter()
+    /// let test_1 = "./runner --help".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    /// 
+    /// if let Ok(p_res) = parser.parse(test_1.iter()) {
+    ///     if let Some(true) = p_res.get("help") {
+    ///         parser.help();
+    ///     }
+    /// }
+    /// ```
+    pub fn help(&self) {
+        let text = self.render_help(self.color.enabled(Stream::Stdout), false);
+        
+        if self.pager && io::stdout().is_terminal()
+            && text.lines().count() >= terminal_height() && page(&text) {
+            return;
+        }
+        
+        print!("{}", text);
+    }
+    
+    /// Returns the help message `help` would print, without writing it
+    /// anywhere. It is colored only with `ColorChoice::Always`, since
+    /// there is no terminal to detect.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("verbose", Some("false"), 'v', false,
+    ///     "Whether to produce verbose output", ArgType::Flag);
+    ///
+    /// let text = parser.help_text();
+    /// assert!(text.contains("--verbose (-v)"));
+    /// ```
+    pub fn help_text(&self) -> String {
+        self.render_help(self.color == ColorChoice::Always, false)
+    }
+    
+    /// Writes the help message `help_text` returns to `out`, such as a
+    /// locked stdout or a log. Help is only put together when it is
+    /// asked for, by this or the other help methods: adding arguments
+    /// and parsing never format any of it.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("verbose", Some("false"), 'v', false,
+    ///     "Whether to produce verbose output", ArgType::Flag);
+    ///
+    /// let mut out = Vec::new();
+    /// parser.write_help(&mut out).unwrap();
+    /// assert_eq!(out, parser.help_text().into_bytes());
+    /// ```
+    pub fn write_help<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(self.help_text().as_bytes())
+    }
+    
+    /// Like `help_text`, but with the extended help of arguments set
+    /// with `long_help`, as for a `--help` that is more detailed than `-h`
+    pub fn long_help_text(&self) -> String {
+        self.render_help(self.color == ColorChoice::Always, true)
+    }
+    
+    /// Returns just the usage line of help, without a final newline,
+    /// e.g. to show with errors or in a help layout of your own. Like
+    /// help, it is wrapped to fit, and only colored with
+    /// `ColorChoice::Always`.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of user", ArgType::Option);
+    ///
+    /// assert_eq!(parser.render_usage(), "Usage:\t./runner [--help] --name NAME");
+    /// ```
+    pub fn render_usage(&self) -> String {
+        self.usage_text(self.color == ColorChoice::Always)
+    }
+    
+    /// Returns a Markdown snippet of the usage line, in a fenced code
+    /// block, followed by a table of the options shown in help, to be
+    /// included in a README so that it always matches the parser. It is
+    /// never colored, and the headings come from the `Messages` catalog.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of user", ArgType::Option);
+    ///
+    /// let snippet = parser.markdown_usage();
+    /// let lines: Vec<&str> = snippet.lines().collect();
+    ///
+    /// assert_eq!(lines[1], "Usage:\t./runner [--help] --name NAME");
+    /// assert_eq!(lines[4], "| Options | | Required | Type |");
+    /// assert_eq!(lines[7], "| `--name (-n)` | Name of user | true | Option |");
+    /// ```
+    pub fn markdown_usage(&self) -> String {
+        use std::fmt::Write;
+        
+        let label = |s: &str| s.trim_end_matches(':').trim().to_string();
+        let cell = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ").replace('|', "\\|");
+        
+        let mut out = format!("```text\n{}\n```\n\n", self.usage_text(false));
+        let _ = writeln!(out, "| {} | | {} | {} |", label(&self.messages.options),
+            label(&self.messages.required), label(&self.messages.type_));
+        out.push_str("|---|---|---|---|\n");
+        
+        for info in self.displayed() {
+            let _ = writeln!(out, "| `{}` | {} | {} | {} |", flag_names(info, &self.options.kebab_case.flag(&info.name)),
+                cell(&info.help), info.required, info.type_);
+        }
+        
+        out
+    }
+    
+    /// Returns a bash completion script for the program, to be sourced
+    /// or installed into `bash_completion.d`. It completes the flags of
+    /// the arguments shown in help, the declared `choices` of an option
+    /// after its flag and of positionals, and file names for `Path`s.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("format", Some("json"), 'f', false,
+    ///     "Output format", ArgType::Option);
+    /// parser.choices("format", &["json", "yaml", "toml"]).unwrap();
+    ///
+    /// let script = parser.bash_completion();
+    /// assert!(script.contains("--format|-f) COMPREPLY=($(compgen -W 'json yaml toml' -- \"$cur\")); return ;;"));
+    /// assert!(script.ends_with("complete -o default -F _runner runner\n"));
+    /// ```
+    pub fn bash_completion(&self) -> String {
+        use std::fmt::Write;
+        
+        let function: String = self.name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        // `compgen -W` splits and unquotes the words again, so anything
+        // special is escaped for that before quoting the whole list
+        let words = |list: &[String]| {
+            let escaped: Vec<String> = list.iter()
+                .map(|w| w.chars().flat_map(|c| {
+                    let plain = c.is_alphanumeric() || "-_.,/:=@+%".contains(c);
+                    if plain { None } else { Some('\\') }.into_iter().chain(Some(c))
+                }).collect())
+                .collect();
+            format!("'{}'", escaped.join(" ").replace('\'', "'\\''"))
+        };
+        
+        let mut flags = Vec::new();
+        let mut positional = Vec::new();
+        let mut out = format!("_{}() {{\n", function);
+        out.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+        out.push_str("    case \"$prev\" in\n");
+        
+        for info in self.displayed() {
+            let long = self.options.kebab_case.flag(&info.name);
+            match info.type_ {
+                ArgType::Positional(_) | ArgType::PositionalList(_) | ArgType::Command | ArgType::Remainder => {
+                    positional.extend(info.choices.iter().cloned());
+                    continue;
+                }
+                _ => {
+                    flags.push(format!("--{}", long));
+                    flags.extend(short(info).map(|c| format!("-{}", c)));
+                }
+            }
+        
+            let values = match info.type_ {
+                ArgType::Flag | ArgType::Counter => continue,
+                _ if !info.choices.is_empty() => format!("COMPREPLY=($(compgen -W {} -- \"$cur\"))", words(&info.choices)),
+                ArgType::Path => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+                _ => "COMPREPLY=()".to_string(),
+            };
+            let pattern = match short(info) {
+                Some(c) => format!("--{}|-{}", long, c),
+                None => format!("--{}", long),
+            };
+            let _ = writeln!(out, "        {}) {}; return ;;", pattern, values);
+        }
+        
+        out.push_str("    esac\n");
+        flags.extend(positional);
+        let _ = writeln!(out, "    COMPREPLY=($(compgen -W {} -- \"$cur\"))\n}}", words(&flags));
+        let _ = writeln!(out, "complete -o default -F _{} {}", function, self.name);
+        
+        out
+    }
+    
+    /// Returns a JSON Schema for a config file mirroring the arguments,
+    /// an object with a key for each argument by name, so that editors
+    /// can check such files. Values are typed from the kind of argument
+    /// and its `value_type`, and carry its help, default and `choices`.
+    /// There is no config file support in the parser itself. Requires
+    /// the `json` feature.
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "json")]
+    /// # fn main() {
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("jobs", Some("4"), 'j', false,
+    ///     "How many jobs to run at once", ArgType::Option);
+    /// parser.value_type::<u32>("jobs").unwrap();
+    ///
+    /// let schema = parser.json_schema();
+    /// assert_eq!(schema["properties"]["jobs"]["type"], "integer");
+    /// assert_eq!(schema["properties"]["jobs"]["default"], 4);
+    /// # }
+    /// # #[cfg(not(feature = "json"))]
+    /// # fn main() {}
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn json_schema(&self) -> serde_json::Value {
+        use serde_json::{Map, Value};
+        
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        
+        for info in self.defs.args.iter() {
+            // The built-in help flag is no setting
+            if info.name == "help" && info.type_ == ArgType::Flag {
+                continue;
+            }
+            
+            let kind = match info.type_ {
+                ArgType::Flag => "boolean",
+                ArgType::Counter => "integer",
+                ArgType::Json => "",
+                _ => match info.tag.map(|t| t.name) {
+                    Some("bool") => "boolean",
+                    Some("f32") | Some("f64") => "number",
+                    Some("i8") | Some("i16") | Some("i32") | Some("i64") | Some("i128") | Some("isize")
+                    | Some("u8") | Some("u16") | Some("u32") | Some("u64") | Some("u128") | Some("usize") => "integer",
+                    _ => "string",
+                },
+            };
+            
+            let mut value = Map::new();
+            if !kind.is_empty() {
+                value.insert("type".into(), kind.into());
+            }
+            if !info.choices.is_empty() {
+                value.insert("enum".into(), info.choices.clone().into());
+            }
+            
+            let mut schema = match info.type_ {
+                ArgType::List | ArgType::PositionalList(_) | ArgType::Remainder => {
+                    let mut array = Map::new();
+                    array.insert("type".into(), "array".into());
+                    array.insert("items".into(), Value::Object(value));
+                    array
+                }
+                ArgType::Dict => {
+                    let mut object = Map::new();
+                    object.insert("type".into(), "object".into());
+                    object.insert("additionalProperties".into(), Value::Object(value));
+                    object
+                }
+                _ => {
+                    if let Some(ref default) = info.default {
+                        let typed = match kind {
+                            "string" => None,
+                            _ => serde_json::from_str(default).ok(),
+                        };
+                        value.insert("default".into(), typed.unwrap_or_else(|| default.clone().into()));
+                    }
+                    value
+                }
+            };
+            
+            schema.insert("description".into(), info.help.clone().into());
+            if info.deprecated.is_some() {
+                schema.insert("deprecated".into(), true.into());
+            }
+            if info.required {
+                required.push(Value::from(info.name.clone()));
+            }
+            properties.insert(info.name.clone(), Value::Object(schema));
+        }
+        
+        let mut root = Map::new();
+        root.insert("$schema".into(), "https://json-schema.org/draft/2020-12/schema".into());
+        root.insert("title".into(), self.name.clone().into());
+        root.insert("type".into(), "object".into());
+        root.insert("properties".into(), Value::Object(properties));
+        root.insert("required".into(), Value::Array(required));
+        root.insert("additionalProperties".into(), false.into());
+        Value::Object(root)
+    }
+    
+    /// Builds a `clap::Command` equivalent to the parser, to migrate to
+    /// `clap` an argument at a time or to share the definition with
+    /// tools built on it. Flags, values, defaults, help, `choices`,
+    /// `ignore_case`, `exclusive`, `overrides_with`, `value_terminator`,
+    /// `visible_if` and the handling of negative numbers carry over. `clap`'s own help
+    /// flag is replaced by this parser's `help` argument. Groups from
+    /// `require_at_least` only require one of their arguments, as in
+    /// `clap`, and `deprecated` and `max_occurrences` are dropped.
+    /// Requires the `clap` feature.
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "clap")]
+    /// # fn main() {
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', true,
+    ///     "Name of user", ArgType::Option);
+    ///
+    /// let matches = parser.to_clap().get_matches_from(vec!["runner", "-n", "Johnny"]);
+    /// assert_eq!(matches.get_one::<String>("name").map(|s| s.as_str()), Some("Johnny"));
+    /// # }
+    /// # #[cfg(not(feature = "clap"))]
+    /// # fn main() {}
+    /// ```
+    #[cfg(feature = "clap")]
+    pub fn to_clap(&self) -> clap::Command {
+        use clap::{Arg, ArgAction, ArgGroup};
+        use clap::builder::PossibleValuesParser;
+        
+        let mut command = clap::Command::new(self.name.clone()).disable_help_flag(true);
+        if let Some(ref description) = self.description {
+            command = command.about(description.clone());
+        }
+        
+        for (idx, info) in self.defs.args.iter().enumerate() {
+            let mut arg = Arg::new(info.name.clone())
+                .help(info.help.clone())
+                .required(info.required);
+            
+            // A short flag reused by a later argument only reaches that one
+            let long = self.options.kebab_case.flag(&info.name).into_owned();
+            arg = match info.type_ {
+                ArgType::Positional(idx) => arg.index(idx as usize + 1),
+                ArgType::PositionalList(seg) => arg.num_args(1..).action(ArgAction::Append).last(seg > 0),
+                ArgType::Command | ArgType::Remainder => arg.num_args(1..).trailing_var_arg(true)
+                    .allow_hyphen_values(true),
+                _ if self.defs.shorts.get(&info.flag) == Some(&idx) => arg.long(long.clone()).short(info.flag),
+                _ => arg.long(long.clone()),
+            };
+            if self.options.kebab_case == KebabCase::Both && long != info.name && arg.get_long().is_some() {
+                arg = arg.alias(info.name.clone());
+            }
+            
+            arg = match (&info.type_, info.list_values) {
+                (&ArgType::Flag, _) if info.name == "help" => arg.action(ArgAction::Help),
+                (&ArgType::Flag, _) => arg.action(ArgAction::SetTrue),
+                (&ArgType::Counter, _) => arg.action(ArgAction::Count),
+                (&ArgType::List, ListValues::AtMost(n)) => arg.num_args(1..=n).action(ArgAction::Append),
+                (&ArgType::List, _) | (&ArgType::Dict, _) => arg.num_args(1..).action(ArgAction::Append),
+                _ => arg,
+            };
+            
+            if let Some(ref default) = info.default {
+                arg = match info.type_ {
+                    ArgType::Flag | ArgType::Counter => arg,
+                    ArgType::List | ArgType::Dict | ArgType::PositionalList(_) =>
+                        arg.default_values(default.split_whitespace().map(String::from)),
+                    _ => arg.default_value(default.clone()),
+                };
+            }
+            if !info.choices.is_empty() {
+                arg = arg.value_parser(PossibleValuesParser::new(info.choices.clone()));
+            }
+            if let Some(ref text) = info.long_help {
+                arg = arg.long_help(text.clone());
+            }
+            if let Some(Condition(ref shown)) = info.visible {
+                arg = arg.hide(!shown());
+            }
+            if let Some(ref terminator) = info.terminator {
+                arg = arg.value_terminator(terminator.clone());
+            }
+            for other in info.overrides.iter() {
+                arg = arg.overrides_with(other.clone());
+            }
+            
+            let negative = match self.options.negative_numbers {
+                _ if info.type_ == ArgType::Flag || info.type_ == ArgType::Counter => false,
+                NegativeNumbers::Values => true,
+                NegativeNumbers::Flags => false,
+                NegativeNumbers::PerArg => info.negative,
+            };
+            
+            command = command.arg(arg.ignore_case(info.ignore_case)
+                .exclusive(info.exclusive)
+                .allow_negative_numbers(negative));
+        }
+        
+        for (n, group) in self.groups.iter().enumerate() {
+            command = command.group(ArgGroup::new(format!("group{}", n))
+                .args(group.names.iter().cloned())
+                .multiple(true)
+                .required(group.min > 0));
+        }
+        
+        command
+    }
+    
+    fn usage_text(&self, paint: bool) -> String {
+        let heading = format!("{}{}", color::BOLD, color::UNDERLINE);
+        let program = format!("./{} ", self.name);
+        
+        // Options come first, then positionals in the order they are read
+        let mut in_usage = self.displayed();
+        in_usage.sort_by_key(|a| usage_rank(a));
+        let items: Vec<String> = in_usage.into_iter()
+            .map(|a| usage(a, &self.options.kebab_case.flag(&a.name)))
+            .collect();
+        
+        // Continuation lines line up with the first argument
+        let tab_stop = (display_width(&self.messages.usage) / TAB_WIDTH + 1) * TAB_WIDTH;
+        let indent = tab_stop + display_width(&program);
+        let width = self.help_width().saturating_sub(indent);
+        let lines = wrap_words(items.iter().map(|s| s.as_str()), width, width);
+        
+        format!("{}\t{}{}", color::paint(&self.messages.usage, &heading, paint), program,
+            lines.join(&format!("\n{:1$}", "", indent)))
+    }
+    
+    fn render_help(&self, paint: bool, long: bool) -> String {
+        use std::fmt::Write;
+        
+        let heading = format!("{}{}", color::BOLD, color::UNDERLINE);
+        let mut out = String::new();
+        
+        if let Some(ref title) = self.title {
+            let _ = writeln!(out, "{}", color::paint(title, color::BOLD, paint));
+        }
+        if let Some(ref description) = self.description {
+            let width = self.help_width();
+            let _ = writeln!(out, "{}", wrap(description, width, width).join("\n"));
+        }
+        if self.title.is_some() || self.description.is_some() {
+            out.push('\n');
+        }
+        
+        let _ = writeln!(out, "{}", self.usage_text(paint));
+        let _ = write!(out, "{}\n\n", color::paint(&self.messages.options, &heading, paint));
+        
+        // Flags go in the left column, and everything else in the right
+        let args = self.displayed();
+        let flags: Vec<String> = args.iter()
+            .map(|a| flag_names(a, &self.options.kebab_case.flag(&a.name)))
+            .collect();
+        let column = self.help_column.unwrap_or_else(|| {
+            let longest = flags.iter().map(|f| display_width(f)).max().unwrap_or(0);
+            ::std::cmp::min(HELP_INDENT + longest + COLUMN_GAP, MAX_COLUMN)
+        });
+        let width = self.help_width().saturating_sub(column);
+        
+        for (info, flags) in args.into_iter().zip(flags) {
+            let mut lines = wrap(&info.help, width, width);
+            lines.push(format!("{} {}  {} {}", self.messages.required, info.required,
+                self.messages.type_, info.type_));
+            
+            if let Some(text) = info.long_help.as_ref().filter(|_| long) {
+                lines.push(String::new());
+                lines.extend(wrap(text, width, width));
+            }
+            
+            let used = HELP_INDENT + display_width(&flags);
+            let _ = write!(out, "{:2$}{}", "", color::paint(&flags, color::GREEN, paint), HELP_INDENT);
+            
+            if used + COLUMN_GAP > column {
+                let _ = write!(out, "\n{:1$}", "", column);
+            } else {
+                let _ = write!(out, "{:1$}", "", column - used);
+            }
+            
+            for (n, line) in lines.iter().enumerate() {
+                match (n, line.is_empty()) {
+                    (0, _) | (_, true) => out.push_str(line),
+                    _ => { let _ = write!(out, "{:2$}{}", "", line, column); }
+                }
+                out.push('\n');
+            }
+            out.push('\n');
+        }
+        
+        if !self.examples.is_empty() {
+            let _ = write!(out, "{}\n\n", color::paint(&self.messages.examples, &heading, paint));
+        }
+        for (command, description) in self.examples.iter() {
+            let _ = writeln!(out, "{:2$}{}", "", color::paint(command, color::GREEN, paint), HELP_INDENT);
+            
+            let indent = 2 * HELP_INDENT;
+            let width = self.help_width().saturating_sub(indent);
+            let lines = wrap(description, width, width);
+            let _ = write!(out, "{:2$}{}\n\n", "", lines.join(&format!("\n{:1$}", "", indent)), indent);
+        }
+        
+        out
+    }
+}
+
+/// An option being changed by `ArgParser::modify_opt`. Its setters
+/// can be chained.
+#[derive(Debug)]
+pub struct ArgMut<'a> {
+    arg: &'a mut Arg,
+}
+
+impl<'a> ArgMut<'a> {
+    /// The name of the option
+    pub fn name(&self) -> &str {
+        &self.arg.name
+    }
+    
+    /// Set the default value, or remove it with `None`
+    pub fn default(&mut self, default: Option<&str>) -> &mut ArgMut<'a> {
+        self.arg.default = stored_default(&self.arg.type_, default);
+        self
+    }
+    
+    /// Set whether the option must be given
+    pub fn required(&mut self, required: bool) -> &mut ArgMut<'a> {
+        self.arg.required = required;
+        self
+    }
+    
+    /// Set the one-line help
+    pub fn help(&mut self, help: &str) -> &mut ArgMut<'a> {
+        self.arg.help = help.into();
+        self
+    }
+}
+
+/// Iterator over the results of parsing each line of some input, see
+/// `ArgParser::parse_lines`. Reading the input can fail, so each item
+/// is an `io::Result`.
+#[derive(Debug)]
+pub struct ParseLines<'p, R> {
+    parser: &'p ArgParser,
+    lines: io::Lines<R>,
+}
+
+impl<'p, R: BufRead> Iterator for ParseLines<'p, R> {
+    type Item = io::Result<ParseResult>;
+    
+    fn next(&mut self) -> Option<io::Result<ParseResult>> {
+        loop {
+            match self.lines.next()? {
+                Ok(ref line) if line.trim().is_empty() => continue,
+                Ok(line) => return Some(Ok(self.parser.parse_line(&line))),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Iterator over what reading a command line finds, see
+/// `ArgParser::parse_iter`
+#[derive(Debug)]
+pub struct ParseEvents<'a> {
+    parser: &'a ArgParser,
+    args: &'a [String],
+    tokens: Peekable<Lexer<'a, String>>,
+    /// Negative numbers split into flags, read before the rest
+    split: VecDeque<(usize, Token<'a>)>,
+    positionals: usize,
+    last_positional: Option<usize>,
+    segment: u8,
+    terminated: bool,
+    flags_ended: bool,
+    done: bool,
+    refused: Option<ParseError>,
+}
+
+impl<'a> ParseEvents<'a> {
+    /// The next token, at its index in the arguments
+    fn pull(&mut self) -> Option<(usize, Token<'a>)> {
+        self.split.pop_front().or_else(|| self.tokens.next().map(|(pos, token)| (pos + 1, token)))
+    }
+    
+    fn peek(&mut self) -> Option<(usize, Token<'a>)> {
+        match self.split.front() {
+            Some(&next) => Some(next),
+            None => self.tokens.peek().map(|&(pos, token)| (pos + 1, token)),
+        }
+    }
+    
+    /// Whether a token is a negative number to read as flags where it
+    /// isn't taken as a number, as with `parse`
+    fn splittable(&self, token: Token<'a>) -> bool {
+        match token {
+            Token::Value(v) => self.parser.options.negative_numbers == NegativeNumbers::PerArg
+                && !self.terminated && lexer::is_negative_number(v),
+            _ => false,
+        }
+    }
+    
+    /// Reads the negative number at `position` as flags instead
+    fn split_at(&mut self, position: usize) {
+        let flags = Lexer::new(&self.args[position..=position])
+            .numbers_as_flags(true)
+            .map(|(_, token)| (position, token))
+            .collect::<Vec<_>>();
+        
+        for flag in flags.into_iter().rev() {
+            self.split.push_front(flag);
+        }
+    }
+    
+    fn error(&self, msg: String, pos: usize) -> ParseEvent<'a> {
+        let mut err = ParseError::new(msg, self.args, Some(pos), self.parser);
+        err.color = self.parser.options.color.unwrap_or(err.color);
+        ParseEvent::Error(err)
+    }
+    
+    /// The event for the values an argument took, checked against its
+    /// choices and validator
+    fn checked(&self, def: &'a Arg, values: Vec<(usize, &'a str)>, position: usize) -> ParseEvent<'a> {
+        for &(pos, val) in values.iter() {
+            let reason = if !def.choices.is_empty() && choice(def, val).is_none() {
+                Some(format!("expected one of {}", listed(&def.choices)))
+            } else {
+                def.validator.as_ref().and_then(|Validator(f)| f(val).err())
+            };
+            
+            if let Some(reason) = reason {
+                let err = Messages::fill_value(&self.parser.messages.invalid_value, &def.name, val)
+                    .replace("{reason}", &reason);
+                return self.error(err, pos);
+            }
+        }
+        
+        ParseEvent::ArgMatched {
+            name: &def.name,
+            values: values.into_iter().map(|(_, val)| val).collect(),
+            position,
+        }
+    }
+    
+    /// The `Command` or `Remainder` argument that takes a value found
+    /// where no positional argument is
+    fn trailing(&self) -> Option<usize> {
+        let args = &self.parser.defs.args;
+        let claimed = args.iter().any(|def| def.type_ == ArgType::Positional(self.positionals as u8));
+        let command = args.iter().position(|def| def.type_ == ArgType::Command);
+        
+        command.or_else(|| args.iter().position(|def| def.type_ == ArgType::Remainder))
+            .filter(|_| !claimed)
+    }
+    
+    /// The event for the argument at `idx` taking every argument from
+    /// `start` on, which ends the events
+    fn rest(&mut self, idx: usize, start: usize) -> ParseEvent<'a> {
+        self.done = true;
+        
+        let values = (start..self.args.len()).map(|pos| (pos, self.args[pos].as_str())).collect();
+        self.checked(&self.parser.defs.args[idx], values, start)
+    }
+    
+    /// The event for a flag found at `position`
+    fn matched(&mut self, flag: Flag<'a>, attached: Option<&'a str>, position: usize) -> ParseEvent<'a> {
+        let parser = self.parser;
+        let opts = &parser.options;
+        let shown = || match flag {
+            Flag::Short(c) => format!("-{}", c),
+            Flag::Long(name) => format!("--{}", name),
+        };
+        
+        let remainder = parser.defs.args.iter().position(|def| def.type_ == ArgType::Remainder);
+        let def = match (parser.lookup_with(flag, opts), remainder) {
+            (Ok(Some(idx)), _) => &parser.defs.args[idx],
+            (Err(candidates), _) => return self.error(parser.ambiguous(&shown(), flag, &candidates, opts), position),
+            (Ok(None), Some(idx)) => return self.rest(idx, position),
+            (Ok(None), None) => return ParseEvent::Unknown { flag: shown(), position },
+        };
+        
+        let mut values: Vec<(usize, &'a str)> = attached.map(|v| (position, v)).into_iter().collect();
+        if let ArgType::Command | ArgType::Remainder = def.type_ {
+            self.done = true;
+            values.extend((position + 1..self.args.len()).map(|pos| (pos, self.args[pos].as_str())));
+        }
+        
+        // A value attached with `=` is the only one taken
+        let most = match (&def.type_, def.list_values) {
+            _ if attached.is_some() => 0,
+            (&ArgType::List, ListValues::AtMost(n)) => n,
+            (&ArgType::List, _) | (&ArgType::Dict, _) => usize::MAX,
+            (&ArgType::Option, _) | (&ArgType::Path, _) | (&ArgType::Json, _) => 1,
+            _ => 0,
+        };
+        let at_end = self.peek().is_none();
+        while values.len() < most {
+            match self.peek() {
+                Some((pos, token)) if !def.negative && self.splittable(token) => {
+                    self.pull();
+                    self.split_at(pos);
+                    break;
+                }
+                Some((pos, Token::Value(val))) => {
+                    self.pull();
+                    if def.terminator.as_deref() == Some(val) {
+                        break;
+                    }
+                    values.push((pos, val));
+                }
+                _ => break,
+            }
+        }
+        
+        // Lists go without values unless there are no more arguments
+        let missing = values.is_empty() && match def.type_ {
+            ArgType::Flag | ArgType::Counter | ArgType::Positional(_) | ArgType::PositionalList(_) => false,
+            ArgType::List | ArgType::Dict => at_end,
+            _ => true,
+        };
+        if missing {
+            return self.error(Messages::fill(&parser.messages.missing_value, &def.name), position);
+        }
+        if def.type_ == ArgType::Dict {
+            if let Some(&(pos, val)) = values.iter().find(|&&(_, val)| !val.contains(':')) {
+                return self.error(Messages::fill_value(&parser.messages.malformed_pair, &def.name, val), pos);
+            }
+        }
+        
+        self.checked(def, values, position)
+    }
+}
+
+impl<'a> Iterator for ParseEvents<'a> {
+    type Item = ParseEvent<'a>;
+    
+    fn next(&mut self) -> Option<ParseEvent<'a>> {
+        if self.done {
+            return None;
+        }
+        if let Some(err) = self.refused.take() {
+            self.done = true;
+            return Some(ParseEvent::Error(err));
+        }
+        
+        loop {
+            let (position, token) = self.pull()?;
+            
+            let token = match token {
+                // Combined short flags are one positional
+                _ if self.flags_ended && self.last_positional == Some(position) => continue,
+                _ if self.flags_ended => Token::Value(self.args[position].as_str()),
+                token => token,
+            };
+            
+            let event = match token {
+                Token::Value(_) if !self.flags_ended && self.splittable(token)
+                    && !self.parser.takes_number(self.positionals, self.segment) => {
+                    
+                    self.split_at(position);
+                    continue;
+                }
+                Token::Value(value) => match self.trailing() {
+                    Some(idx) => self.rest(idx, position),
+                    None => {
+                        self.positionals += 1;
+                        self.last_positional = Some(position);
+                        self.flags_ended = !self.parser.options.permute;
+                        ParseEvent::PositionalFound { value, position }
+                    }
+                },
+                Token::Terminator => {
+                    self.terminated = true;
+                    match self.parser.defs.args.iter().position(|def| def.type_ == ArgType::Command) {
+                        Some(_) if position + 1 == self.args.len() => {
+                            self.done = true;
+                            return None;
+                        }
+                        Some(idx) => self.rest(idx, position + 1),
+                        None => {
+                            self.segment += 1;
+                            continue;
+                        }
+                    }
+                }
+                Token::ShortFlag(c) => self.matched(Flag::Short(c), None, position),
+                Token::LongFlag(name) => self.matched(Flag::Long(name), None, position),
+                Token::Attached { flag, value } => self.matched(flag, Some(value), position),
+            };
+            
+            self.done |= matches!(event, ParseEvent::Error(_));
+            return Some(event);
+        }
+    }
+}
+
+/// A cheaply cloneable handle to a fully configured `ArgParser`, for
+/// parsing from several threads at once. Cloning it only bumps a
+/// reference count, and it derefs to the parser it wraps.
+/// # Example
+/// ```
+/// use std::thread;
+/// use argparse::{ArgParser, ArgType, SharedParser};
+///
+/// let mut parser = ArgParser::new("server".into());
+/// parser.add_opt("name", None, 'n', true,
+///     "Name of user", ArgType::Option);
+///
+/// let shared = SharedParser::new(parser);
+///
+/// let handles: Vec<_> = ["Johnny", "Tina"].iter().map(|name| {
+///     let parser = shared.clone();
+///     let args = vec!["./server".to_string(), "-n".into(), name.to_string()];
+///
+///     thread::spawn(move || {
+///         let p_res = parser.parse(args.iter()).unwrap();
+///         p_res.get::<String>("name").unwrap()
+///     })
+/// }).collect();
+///
+/// for (handle, name) in handles.into_iter().zip(&["Johnny", "Tina"]) {
+///     assert_eq!(handle.join().unwrap(), *name);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedParser(Arc<ArgParser>);
+
+impl SharedParser {
+    /// Wraps a configured parser so it can be shared
+    pub fn new(parser: ArgParser) -> SharedParser {
+        SharedParser(Arc::new(parser))
+    }
+}
+
+impl From<ArgParser> for SharedParser {
+    fn from(parser: ArgParser) -> SharedParser {
+        SharedParser::new(parser)
+    }
+}
+
+impl Deref for SharedParser {
+    type Target = ArgParser;
+    
+    fn deref(&self) -> &ArgParser {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+/// This type represents the result ofparsing arguments.
+pub struct ArgParseResults {
+    defs: Arc<Definitions>,
+    values: Vec<Value>,
+    warnings: Vec<Warning>,
+    terminated: Option<usize>,
+    /// The bytes `parse_os` was given for each argument, only kept when
+    /// any of them wasn't valid UTF-8
+    raw: Vec<Vec<u8>>,
+}
+
+impl ArgParseResults {
+    
+    /// The value given for an argument, falling back to its default
+    fn value(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.defs.names.get(name).and_then(|&idx| self.value_at(idx))
+    }
+    
+    /// The value of the argument at `idx` in the definitions
+    fn value_at(&self, idx: usize) -> Option<Cow<'_, str>> {
+        let def = &self.defs.args[idx];
+        
+        match self.values[idx].vals {
+            Some(ref vals) => Some(joined(vals, &def.type_)),
+            None => def.default.as_ref().map(|d| Cow::Borrowed(d.as_str())),
+        }
+    }
+    
+    /// Iterates over every argument, in the order they were added, with
+    /// the value `get` would parse, falling back to the default, and
+    /// the number of times it was given. This is the full effective
+    /// configuration, e.g. to log it or pass it on.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', false,
+    ///     "Name of user", ArgType::Option);
+    /// parser.add_opt("verbose", Some("false"), 'v', false,
+    ///     "Whether to produce verbose output", ArgType::Flag);
+    ///
+    /// let test_1 = "./runner -v -v".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// for (name, value, occurrences) in p_res.iter() {
+    ///     match name {
+    ///         "name" => assert_eq!((value, occurrences), (None, 0)),
+    ///         "verbose" => assert_eq!((value, occurrences), (Some("true".into()), 2)),
+    ///         _ => {}
+    ///     }
+    /// }
+    /// ```
+    pub fn iter(&self) -> ResultsIter<'_> {
+        ResultsIter { results: self, idx: 0 }
+    }
+    
+    /// Problems with the arguments that didn't stop them from parsing,
+    /// in the order they were found, for the application to show to
+    /// the user.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, Warning};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', false,
+    ///     "Name of user", ArgType::Option);
+    ///
+    /// let test_1 = "./runner -n Johnny --colour -n Tina".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// assert_eq!(p_res.warnings(), &[
+    ///     Warning::UnknownFlag { flag: "--colour".into(), position: 3 },
+    ///     Warning::Overridden { name: "name".into(), position: 4 },
+    /// ]);
+    /// ```
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+    
+    /// The argument marked with `ArgParser::terminates` that ended the
+    /// parse, if one did. Arguments after it weren't read, so the
+    /// results may lack required ones.
+    pub fn terminated_by(&self) -> Option<&str> {
+        self.terminated.map(|idx| self.defs.args[idx].name.as_str())
+    }
+    
+    /// Copies every argument's value, as `iter` gives it, into a map
+    /// from names to values, for code that only understands strings.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', false,
+    ///     "Name of user", ArgType::Option);
+    /// parser.add_opt("tags", None, 't', false,
+    ///     "Tags to apply", ArgType::List);
+    ///
+    /// let test_1 = "./runner -t a b".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// let map = parser.parse(test_1.iter()).unwrap().to_map();
+    /// assert_eq!(map["name"], None);
+    /// assert_eq!(map["tags"], Some("a b ".into()));
+    /// assert_eq!(map["help"], Some("false".into()));
+    /// ```
+    pub fn to_map(&self) -> HashMap<String, Option<String>> {
+        self.iter()
+            .map(|(name, value, _)| (name.into(), value.map(Cow::into_owned)))
+            .collect()
+    }
+    
+    /// The arguments whose values differ between these results and
+    /// `other`, comparing the values `iter` gives, so an argument given
+    /// its default value is the same as one left out. The results may
+    /// come from different parsers; an argument only one of them has
+    /// has no value in the other.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("name", None, 'n', false,
+    ///     "Name of user", ArgType::Option);
+    /// parser.add_opt("level", Some("1"), 'l', false,
+    ///     "How hard to try", ArgType::Option);
+    ///
+    /// let parse = |cmd: &str| {
+    ///     let args: Vec<String> = cmd.split_whitespace().map(|s| s.into()).collect();
+    ///     parser.parse(args.iter()).unwrap()
+    /// };
+    ///
+    /// let last_run = parse("./runner -n Johnny");
+    /// let this_run = parse("./runner -n Tina -l 1");
+    ///
+    /// let diff = last_run.diff(&this_run);
+    /// assert_eq!(diff.len(), 1);
+    /// assert_eq!(diff[0].name, "name");
+    /// assert_eq!(diff[0].before, Some("Johnny".into()));
+    /// assert_eq!(diff[0].after, Some("Tina".into()));
+    /// ```
+    pub fn diff(&self, other: &ArgParseResults) -> Vec<ArgDiff> {
+        let mut before = self.to_map();
+        let mut diff = Vec::new();
+        
+        for (name, after, _) in other.iter() {
+            let before = before.remove(name).unwrap_or(None);
+            
+            if before.as_deref() != after.as_deref() {
+                diff.push(ArgDiff { name: name.into(), before, after: after.map(Cow::into_owned) });
+            }
+        }
+        
+        // Arguments only these results have
+        for (name, _, _) in self.iter() {
+            if let Some(Some(before)) = before.remove(name) {
+                diff.push(ArgDiff { name: name.into(), before: Some(before), after: None });
+            }
+        }
+        
+        diff
+    }
+
+    /// Extracts the argument, as long is the value type implements
+    /// `FromStr`
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("verbose", Some("false"), 'v', false,
+    ///     "Whether to produce verbose output", ArgType::Flag);
+    ///
+    /// // Normally you'd get this from std::env::args().iter()
+    /// let test_1 = "./runner -v".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    /// 
+    /// if let Ok(p_res) = parser.parse(test_1.iter()) {
+    ///     if let Some(true) = p_res.get::<bool>("verbose") {
+    ///         // be verbose
+    ///     }
+    /// }
+    /// ```
+    pub fn get<T: FromStr + 'static>(&self, name: &str) -> Option<T> {
+        self.try_get(name).ok()
+    }
+    
+    /// Extracts the argument like `get`, but says why it couldn't: the
+    /// argument doesn't exist, has no value, was declared as another
+    /// type with `ArgParser::value_type`, or its value doesn't parse.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, GetError};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("level", Some("high"), 'l', false,
+    ///     "How hard to try", ArgType::Option);
+    ///
+    /// let test_1 = vec!["./runner".to_string()];
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    ///
+    /// assert_eq!(p_res.try_get::<String>("level"), Ok("high".into()));
+    /// assert_eq!(p_res.try_get::<u8>("level").unwrap_err().to_string(),
+    ///     "the value `high` of `level` is not a valid `u8`");
+    /// assert_eq!(p_res.try_get::<u8>("lvl"), Err(GetError::NoSuchArg("lvl".into())));
+    /// ```
+    pub fn try_get<T: FromStr + 'static>(&self, name: &str) -> Result<T, GetError> {
+        match self.defs.names.get(name) {
+            Some(&idx) => read(&self.defs.args[idx], self.value_at(idx)),
+            None => Err(GetError::NoSuchArg(name.into())),
+        }
+    }
+    
+    /// Extracts the argument, using the `ArgGetter<T>` that you provided
+    ///
+    /// # Note
+    /// See documentation for the trait [`ArgGetter`](./trait.ArgGetter.html) for more information
+    /// 
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("verbose", Some("false"), 'v', false,
+    ///     "Whether to produce verbose output", ArgType::Flag);
+    ///
+    /// // Normally you'd get this from std::env::args().iter()
+    /// let test_1 = "./runner -v".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    /// 
+    /// let dumb_closure = |_: &str| { Some(true) };
+    /// 
+    /// if let Ok(p_res) = parser.parse(test_1.iter()) {
+    ///     if let Some(true) = p_res.get_with::<bool, _>("verbose", dumb_closure) {
+    ///         // be verbose
+    ///     }
+    /// }
+    /// ```
+    pub fn get_with<T, P>(&self, name: &str, parser: P) -> Option<T>
+    where P: ArgGetter<T> {
+        self.value(name).and_then(|x| parser.get_arg(&x))
+    }
+    
+    /// Extracts every value of a `List` argument, each parsed on its own.
+    /// `get` and `get_with` see the values of `List` and `Dict` arguments
+    /// joined by spaces, so splitting them again loses values that
+    /// contain whitespace; this and the other structured getters keep
+    /// every argument exactly as it was given. Returns `None` if any
+    /// value fails to parse.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("frequencies", None, 'f', false,
+    ///     "User's favorite frequencies", ArgType::List);
+    ///
+    /// let test_1 = "./runner -f 1 2 3".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    /// 
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// assert_eq!(p_res.get_list("frequencies"), Some(vec![1, 2, 3]));
+    /// ```
+    pub fn get_list<T: FromStr + 'static>(&self, name: &str) -> Option<Vec<T>> {
+        let idx = *self.defs.names.get(name)?;
+        check_tag::<T>(&self.defs.args[idx]).ok()?;
+        
+        self.get_list_with(name, |s: &str| s.parse().ok())
+    }
+    
+    /// Extracts every value of a `List` argument, each converted on its
+    /// own by the `ArgGetter<T>` that you provided
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("cities", None, 'c', false,
+    ///     "Cities to visit", ArgType::List);
+    ///
+    /// let test_1 = vec!["./runner".to_string(), "-c".into(),
+    ///     "New York".into(), "Paris".into()];
+    /// 
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// let upper = |s: &str| Some(s.to_uppercase());
+    /// assert_eq!(p_res.get_list_with("cities", upper),
+    ///     Some(vec!["NEW YORK".to_string(), "PARIS".into()]));
+    /// ```
+    pub fn get_list_with<T, P>(&self, name: &str, parser: P) -> Option<Vec<T>>
+    where P: ArgGetter<T> + Clone {
+        self.defs.names.get(name).and_then(|&idx| {
+            parse_list(self.values[idx].vals.as_deref(), self.defs.args[idx].default.as_ref(),
+                |s| parser.clone().get_arg(s))
+        })
+    }
+    
+    /// Builds the `std::process::Command` given by a `Command` argument,
+    /// with its first value as the program and the rest as arguments.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("timeout".into());
+    /// parser.add_opt("duration", None, 'd', true,
+    ///     "How long to wait", ArgType::Positional(0));
+    /// parser.add_opt("command", None, 'c', true,
+    ///     "Command to run", ArgType::Command);
+    ///
+    /// let test_1 = "./timeout 5s sleep -n 10".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// let cmd = p_res.get_command("command").unwrap();
+    /// assert_eq!(cmd.get_program(), "sleep");
+    /// assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["-n", "10"]);
+    /// ```
+    pub fn get_command(&self, name: &str) -> Option<Command> {
+        self.get_list(name).and_then(to_command)
+    }
+    
+    /// Every value given for an argument, exactly as it was given
+    pub fn values(&self, name: &str) -> Option<&[String]> {
+        self.defs.names.get(name)
+            .and_then(|&idx| self.values[idx].vals.as_deref())
+    }
+    
+    /// The bytes of an argument's value as they were given to
+    /// `ArgParser::parse_os`, even when they aren't valid UTF-8 and the
+    /// other getters see `U+FFFD` in place of the invalid ones. For
+    /// `List` and `Dict` arguments this is the first value. The bytes
+    /// are those of the argument the value was read from, so values
+    /// that read the same once replaced keep their own. Returns `None`
+    /// for a value with replaced bytes that isn't as it was given, e.g.
+    /// one joined with an earlier occurrence.
+    /// # Example
+    /// ```
+    /// # #[cfg(unix)]
+    /// # fn main() {
+    /// use std::ffi::OsString;
+    /// use std::os::unix::ffi::OsStringExt;
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("file", None, 'f', false,
+    ///     "File to read", ArgType::Option);
+    ///
+    /// let args = vec![OsString::from("./runner"), OsString::from_vec(b"--file=caf\xe9.txt".to_vec())];
+    /// let p_res = parser.parse_os(&args).unwrap();
+    /// assert_eq!(p_res.get::<String>("file"), Some("caf\u{fffd}.txt".into()));
+    /// assert_eq!(p_res.get_raw_bytes("file"), Some(b"caf\xe9.txt".to_vec()));
+    /// # }
+    /// # #[cfg(not(unix))]
+    /// # fn main() {}
+    /// ```
+    pub fn get_raw_bytes(&self, name: &str) -> Option<Vec<u8>> {
+        let idx = *self.defs.names.get(name)?;
+        let value = &self.values[idx];
+        let text = match value.vals {
+            Some(ref vals) => vals.first()?.as_str(),
+            None => self.defs.args[idx].default.as_deref()?,
+        };
+        
+        let given = value.origin.and_then(|pos| self.raw.get(pos));
+        match given.and_then(|bytes| trailing_bytes(bytes, text)) {
+            Some(bytes) => Some(bytes.to_vec()),
+            None if !self.raw.is_empty() && text.contains(char::REPLACEMENT_CHARACTER) => None,
+            None => Some(text.as_bytes().to_vec()),
+        }
+    }
+    
+    /// Replace the values of an argument as if they had been given,
+    /// e.g. from `after_parse` middleware. It counts as given at least
+    /// once afterwards. The values of a `Dict` are split into
+    /// `key:value` pairs as when parsing, with repeated keys handled as
+    /// its `DuplicateKeys` says.
+    pub fn set(&mut self, name: &str, values: &[&str]) -> Result<(), &'static str> {
+        let idx = *self.defs.names.get(name).ok_or("No such Option")?;
+        let def = &self.defs.args[idx];
+        
+        if def.type_ != ArgType::Dict {
+            let value = &mut self.values[idx];
+            value.vals = Some(values.iter().map(|&v| v.into()).collect());
+            value.count = value.count.max(1);
+            value.origin = None;
+            return Ok(());
+        }
+        
+        let mut pairs: Vec<(String, String)> = Vec::new();
+        let mut vals: Vec<String> = Vec::new();
+        for &val in values {
+            let (k, v) = val.split_once(':').ok_or("Expected a key:value pair")?;
+            match (pairs.iter().position(|(key, _)| key == k), def.duplicates) {
+                (None, _) | (Some(_), DuplicateKeys::Keep) => {
+                    pairs.push((k.into(), v.into()));
+                    vals.push(val.into());
+                }
+                (Some(at), DuplicateKeys::LastWins) => {
+                    pairs[at] = (k.into(), v.into());
+                    vals[at] = val.into();
+                }
+                (Some(_), DuplicateKeys::FirstWins) => {}
+                (Some(_), DuplicateKeys::Error) => return Err("Duplicate key"),
+            }
+        }
+        
+        let value = &mut self.values[idx];
+        value.pairs = Some(pairs);
+        value.vals = Some(vals);
+        value.count = value.count.max(1);
+        value.origin = None;
+        Ok(())
+    }
+    
+    /// Extracts the key-value pairs of a `Dict` argument into a map.
+    /// The pairs were split apart at the first `:` while parsing, so
+    /// this never panics; it returns `None` if any key or value fails
+    /// to parse.
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("runner".into());
+    /// parser.add_opt("socks", None, 's', false,
+    ///     "If you wear socks that day", ArgType::Dict);
+    ///
+    /// let test_1 = "./runner -s Monday:true Friday:false".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    /// 
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// let socks: HashMap<String, bool> = p_res.get_dict("socks").unwrap();
+    /// assert_eq!(socks["Monday"], true);
+    /// ```
+    pub fn get_dict<K, V>(&self, name: &str) -> Option<HashMap<K, V>>
+        where K: FromStr + Hash + Eq,
+              V: FromStr {
+        self.defs.names.get(name).and_then(|&idx| {
+            parse_dict(self.values[idx].pairs.as_deref(), self.defs.args[idx].default.as_ref())
+        })
+    }
+    
+    /// The key-value pairs given for a `Dict` argument, in order
+    pub fn pairs(&self, name: &str) -> Option<&[(String, String)]> {
+        self.defs.names.get(name)
+            .and_then(|&idx| self.values[idx].pairs.as_deref())
+    }
+    
+    /// Extracts the key-value pairs of a `Dict` argument in the order
+    /// they were given, for options where order matters, such as
+    /// headers. A repeated key stays where it was first given, with the
+    /// value `DuplicateKeys` picks, unless the argument keeps every
+    /// pair. Returns `None` if any key or value fails to parse.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("curl".into());
+    /// parser.add_opt("header", None, 'H', false,
+    ///     "Headers to send", ArgType::Dict);
+    ///
+    /// let test_1 = "./curl -H b:2 -H a:1 c:3 b:4".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// let headers = p_res.get_ordered_dict::<String, u8>("header").unwrap();
+    /// assert_eq!(headers, vec![("b".into(), 4), ("a".into(), 1), ("c".into(), 3)]);
+    /// ```
+    pub fn get_ordered_dict<K: FromStr, V: FromStr>(&self, name: &str) -> Option<Vec<(K, V)>> {
+        self.defs.names.get(name).and_then(|&idx| {
+            parse_dict(self.values[idx].pairs.as_deref(), self.defs.args[idx].default.as_ref())
+        })
+    }
+    
+    /// Extracts the key-value pairs of a `Dict` argument into a map from
+    /// each key to all of its values, as for kubectl-style labels: the
+    /// values of a pair are split at commas, so `env:prod,staging` gives
+    /// `env` two values. A key given again only adds to its values if
+    /// the argument keeps every pair, see `DuplicateKeys::Keep`.
+    /// Returns `None` if any key or value fails to parse.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType, DuplicateKeys};
+    ///
+    /// let mut parser = ArgParser::new("kubectl".into());
+    /// parser.add_opt("label", None, 'l', false,
+    ///     "Labels to select by", ArgType::Dict);
+    /// parser.duplicate_keys("label", DuplicateKeys::Keep).unwrap();
+    ///
+    /// let test_1 = "./kubectl -l env:prod,staging tier:web -l env:dev".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// let labels = p_res.get_multimap::<String, String>("label").unwrap();
+    /// assert_eq!(labels["env"], ["prod", "staging", "dev"]);
+    /// assert_eq!(labels["tier"], ["web"]);
+    /// ```
+    pub fn get_multimap<K, V>(&self, name: &str) -> Option<HashMap<K, Vec<V>>>
+        where K: FromStr + Hash + Eq,
+              V: FromStr {
+        self.defs.names.get(name).and_then(|&idx| {
+            parse_multimap(self.values[idx].pairs.as_deref(), self.defs.args[idx].default.as_ref())
+        })
+    }
+    
+    /// Extracts the key-value pairs of a `Dict` argument into a tree,
+    /// splitting the keys at dots, so that `server.port:8080` ends up
+    /// under `server`. When a path is given twice, or as both a value
+    /// and a prefix of other paths, the later pair wins. Returns `None`
+    /// if a key has an empty part, as in `a..b`.
+    /// # Example
+    /// ```
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("server".into());
+    /// parser.add_opt("set", None, 's', false,
+    ///     "Overrides for the configuration", ArgType::Dict);
+    ///
+    /// let test_1 = "./server --set server.port:8080 server.tls:true log:debug".split_whitespace()
+    ///     .map(|s| s.into())
+    ///     .collect::<Vec<String>>();
+    ///
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// let set = p_res.get_nested("set").unwrap();
+    /// assert_eq!(set.get("server.port").and_then(|n| n.as_str()), Some("8080"));
+    /// assert_eq!(set.get("log").and_then(|n| n.as_str()), Some("debug"));
+    /// ```
+    pub fn get_nested(&self, name: &str) -> Option<DictNode> {
+        self.defs.names.get(name).and_then(|&idx| {
+            nest(self.values[idx].pairs.as_deref(), self.defs.args[idx].default.as_ref())
+        })
+    }
+    
+    /// Deserializes the value of an argument, typically a `Json` one,
+    /// into any type implementing `Deserialize`, including
+    /// `serde_json::Value`. Requires the `json` feature.
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "json")]
+    /// # fn main() {
+    /// use std::collections::HashMap;
+    /// use argparse::{ArgParser, ArgType};
+    ///
+    /// let mut parser = ArgParser::new("client".into());
+    /// parser.add_opt("payload", Some("{}"), 'p', false,
+    ///     "Body of the request", ArgType::Json);
+    ///
+    /// let test_1 = vec!["./client".to_string(), "-p".into(), r#"{"id": 1, "size": 20}"#.into()];
+    ///
+    /// let p_res = parser.parse(test_1.iter()).unwrap();
+    /// let payload: HashMap<String, u32> = p_res.get_json("payload").unwrap();
+    /// assert_eq!(payload["size"], 20);
+    /// # }
+    /// # #[cfg(not(feature = "json"))]
+    /// # fn main() {}
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn get_json<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        self.value(name).and_then(|x| serde_json::from_str(&x).ok())
+    }
+}
+
+/// An argument whose value differs between two results, see
+/// `ArgParseResults::diff`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArgDiff {
+    /// The name of the argument
+    pub name: String,
+    /// Its value in the results `diff` was called on
+    pub before: Option<String>,
+    /// Its value in the results passed to `diff`
+    pub after: Option<String>,
+}
+
+impl<'a> IntoIterator for &'a ArgParseResults {
+    type Item = (&'a str, Option<Cow<'a, str>>, u16);
+    type IntoIter = ResultsIter<'a>;
+    
+    fn into_iter(self) -> ResultsIter<'a> {
+        self.iter()
+    }
+}
+
+/// Iterator over the arguments of an `ArgParseResults`, with their
+/// values and how many times they were given. See `ArgParseResults::iter`.
+#[derive(Debug, Clone)]
+pub struct ResultsIter<'a> {
+    results: &'a ArgParseResults,
+    idx: usize,
+}
+
+impl<'a> Iterator for ResultsIter<'a> {
+    type Item = (&'a str, Option<Cow<'a, str>>, u16);
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        let results = self.results;
+        let def = results.defs.args.get(self.idx)?;
+        let item = (def.name.as_str(), results.value_at(self.idx), results.values[self.idx].count);
+        self.idx += 1;
+        
+        Some(item)
+    }
+    
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.results.defs.args.len() - self.idx;
+        (left, Some(left))
+    }
+}
+
+impl<'a> ExactSizeIterator for ResultsIter<'a> {}
+
+/// The result of parsing arguments with `ArgParser::parse_borrowed`.
+/// Rather than owning copies of the values found, it borrows them from
+/// the arguments that were parsed.
+#[derive(Debug, Clone)]
+pub struct BorrowedResults<'a> {
+    defs: Arc<Definitions>,
+    found: Vec<Found<'a>>,
+    warnings: Vec<Warning>,
+    terminated: Option<usize>,
+}
+
+impl<'a> BorrowedResults<'a> {
+    /// The value given for an argument, falling back to its default
+    fn value(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.defs.names.get(name).and_then(|&idx| {
+            let def = &self.defs.args[idx];
+            
+            match self.found[idx].vals {
+                Some(ref vals) => Some(joined(vals, &def.type_)),
+                None => def.default.as_ref().map(|d| Cow::Borrowed(d.as_str())),
+            }
+        })
+    }
+    
+    /// Extracts the argument, as long is the value type implements
+    /// `FromStr`. See `ArgParseResults::get`.
+    pub fn get<T: FromStr + 'static>(&self, name: &str) -> Option<T> {
+        self.try_get(name).ok()
+    }
+    
+    /// Extracts the argument like `get`, but says why it couldn't.
+    /// See `ArgParseResults::try_get`.
+    pub fn try_get<T: FromStr + 'static>(&self, name: &str) -> Result<T, GetError> {
+        match self.defs.names.get(name) {
+            Some(&idx) => read(&self.defs.args[idx], self.value(name)),
+            None => Err(GetError::NoSuchArg(name.into())),
+        }
+    }
+    
+    /// Extracts the argument, using the `ArgGetter<T>` that you provided.
+    /// See `ArgParseResults::get_with`.
+    pub fn get_with<T, P>(&self, name: &str, parser: P) -> Option<T>
+    where P: ArgGetter<T> {
+        self.value(name).and_then(|x| parser.get_arg(&x))
+    }
+    
+    /// Extracts every value of a `List` argument, each parsed on its own.
+    /// See `ArgParseResults::get_list`.
+    pub fn get_list<T: FromStr + 'static>(&self, name: &str) -> Option<Vec<T>> {
+        let idx = *self.defs.names.get(name)?;
+        check_tag::<T>(&self.defs.args[idx]).ok()?;
+        
+        self.get_list_with(name, |s: &str| s.parse().ok())
+    }
+    
+    /// Extracts every value of a `List` argument, each converted on its
+    /// own. See `ArgParseResults::get_list_with`.
+    pub fn get_list_with<T, P>(&self, name: &str, parser: P) -> Option<Vec<T>>
+    where P: ArgGetter<T> + Clone {
+        self.defs.names.get(name).and_then(|&idx| {
+            parse_list(self.found[idx].vals.as_deref(), self.defs.args[idx].default.as_ref(),
+                |s| parser.clone().get_arg(s))
+        })
+    }
+    
+    /// Builds the `std::process::Command` given by a `Command` argument.
+    /// See `ArgParseResults::get_command`.
+    pub fn get_command(&self, name: &str) -> Option<Command> {
+        self.get_list(name).and_then(to_command)
+    }
+    
+    /// The value of a single-valued argument, without copying it. For
+    /// `List` and `Dict` arguments this is the first value.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.defs.names.get(name).and_then(|&idx| {
+            match self.found[idx].vals {
+                Some(ref vals) => vals.first().map(|v| &**v),
+                None => self.defs.args[idx].default.as_deref(),
+            }
+        })
+    }
+    
+    /// Every value given for an argument, without copying them. Values
+    /// are only owned if parsing rewrote them, e.g. by expanding globs.
+    pub fn values(&self, name: &str) -> Option<&[Cow<'a, str>]> {
+        self.defs.names.get(name)
+            .and_then(|&idx| self.found[idx].vals.as_ref())
+            .map(|vals| &vals[..])
+    }
+    
+    /// Extracts the key-value pairs of a `Dict` argument into a map.
+    /// See `ArgParseResults::get_dict`.
+    pub fn get_dict<K, V>(&self, name: &str) -> Option<HashMap<K, V>>
+        where K: FromStr + Hash + Eq,
+              V: FromStr {
+        self.defs.names.get(name).and_then(|&idx| {
+            parse_dict(self.found[idx].pairs.as_deref(), self.defs.args[idx].default.as_ref())
+        })
+    }
+    
+    /// The key-value pairs given for a `Dict` argument, without copying them
+    pub fn pairs(&self, name: &str) -> Option<&[(&'a str, &'a str)]> {
+        self.defs.names.get(name)
+            .and_then(|&idx| self.found[idx].pairs.as_deref())
+    }
+    
+    /// Extracts the key-value pairs of a `Dict` argument in the order
+    /// they were given. See `ArgParseResults::get_ordered_dict`.
+    pub fn get_ordered_dict<K: FromStr, V: FromStr>(&self, name: &str) -> Option<Vec<(K, V)>> {
+        self.defs.names.get(name).and_then(|&idx| {
+            parse_dict(self.found[idx].pairs.as_deref(), self.defs.args[idx].default.as_ref())
+        })
+    }
+    
+    /// Extracts the key-value pairs of a `Dict` argument into a map from
+    /// each key to all of its values. See `ArgParseResults::get_multimap`.
+    pub fn get_multimap<K, V>(&self, name: &str) -> Option<HashMap<K, Vec<V>>>
+        where K: FromStr + Hash + Eq,
+              V: FromStr {
+        self.defs.names.get(name).and_then(|&idx| {
+            parse_multimap(self.found[idx].pairs.as_deref(), self.defs.args[idx].default.as_ref())
+        })
+    }
+    
+    /// Extracts the key-value pairs of a `Dict` argument into a tree.
+    /// See `ArgParseResults::get_nested`.
+    pub fn get_nested(&self, name: &str) -> Option<DictNode> {
+        self.defs.names.get(name).and_then(|&idx| {
+            nest(self.found[idx].pairs.as_deref(), self.defs.args[idx].default.as_ref())
+        })
+    }
+    
+    /// Deserializes the value of an argument, typically a `Json` one.
+    /// See `ArgParseResults::get_json`.
+    #[cfg(feature = "json")]
+    pub fn get_json<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        self.value(name).and_then(|x| serde_json::from_str(&x).ok())
+    }
+    
+    /// Problems with the arguments that didn't stop them from parsing.
+    /// See `ArgParseResults::warnings`.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+    
+    /// The argument that ended the parse, if one did. See
+    /// `ArgParseResults::terminated_by`.
+    pub fn terminated_by(&self) -> Option<&str> {
+        self.terminated.map(|idx| self.defs.args[idx].name.as_str())
+    }
+    
+    /// Copies the values into an `ArgParseResults`, ending the borrow
+    pub fn into_owned(self) -> ArgParseResults {
+        let values = self.found.iter()
+            .map(|f| Value {
+                vals: f.vals.as_ref().map(|v| v.iter().map(|s| s.to_string()).collect()),
+                pairs: f.pairs.as_ref().map(|p| {
+                    p.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+                }),
+                count: f.count,
+                origin: f.origin,
+            })
+            .collect();
+        
+        ArgParseResults { defs: self.defs, values, warnings: self.warnings, terminated: self.terminated, raw: Vec::new() }
+    }
+}
+
+/// Represents something capable of turning a `&str` in the value
+/// type of your choice. Implement this to use with `ArgParseResults::get_with`
+///
+/// # Note
+/// An implementation is provided for all closures of type `F: FnOnce(&str) -> Option<T>`
+pub trait ArgGetter<T> {
+    /// This is the key function that converts from a string 
+    /// to the required value tpe
+    fn get_arg(self, s: &str) -> Option<T>;
+}
+
+impl<T, F: FnOnce(&str) -> Option<T>> ArgGetter<T> for F {
+    fn get_arg(self, s: &str) -> Option<T> {
+        self(s)
+    }
+}
+
+/// Function that parses `List` arguments into `Vec`s.
+/// Provided for user convenience and use as an implementor of
+/// [`ArgGetter`](./trait.ArgGetter.html).
+/// Returns `None` if any element fails to parse; use `try_vec_parser`
+/// to find out which, or `lenient_vec_parser` to skip it.
+pub fn vec_parser<T: FromStr>(s: &str) -> Option<Vec<T>> {
+    s.split_whitespace()
+        .map(|x| x.parse())
+        .enumerate()
+        .fold(None, |acc, (idx, elem)| {
+            if let Ok(x) = elem {
+                if idx == 0 {
+                    Some(vec![x])
+                } else {
+                    acc.map(|mut v| {
+                        v.push(x);
+                        v
+                    })
+                }
+            } else {
+                None
+            }
+        })
+}
+
+/// Function that parses `List` arguments into `Vec`s like `vec_parser`,
+/// but on failure returns the index and text of the first element that
+/// fails to parse.
+/// # Example
+/// ```
+/// use argparse::try_vec_parser;
+///
+/// assert_eq!(try_vec_parser::<u8>("1 2 3"), Ok(vec![1, 2, 3]));
+/// assert_eq!(try_vec_parser::<u8>("1 two 3"), Err((1, "two".into())));
+/// ```
+pub fn try_vec_parser<T: FromStr>(s: &str) -> Result<Vec<T>, (usize, String)> {
+    s.split_whitespace()
+        .enumerate()
+        .map(|(idx, x)| x.parse().map_err(|_| (idx, x.to_string())))
+        .collect()
+}
+
+/// Function that parses `List` arguments into `Vec`s like `vec_parser`,
+/// but skips the elements that fail to parse instead of failing. It
+/// never returns `None`, though the `Vec` may be empty.
+/// # Example
+/// ```
+/// use argparse::{ArgParser, ArgType, lenient_vec_parser};
+///
+/// let mut parser = ArgParser::new("runner".into());
+/// parser.add_opt("ports", None, 'p', false,
+///     "Ports to listen on", ArgType::List);
+///
+/// let test_1 = "./runner -p 80 http 443".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// let ports: Vec<u16> = p_res.get_with("ports", lenient_vec_parser).unwrap();
+/// assert_eq!(ports, vec![80, 443]);
+/// ```
+pub fn lenient_vec_parser<T: FromStr>(s: &str) -> Option<Vec<T>> {
+    Some(s.split_whitespace().filter_map(|x| x.parse().ok()).collect())
+}
+
+/// Function that parses `List` arguments into `HashSet`s, dropping
+/// repeated values. Like `vec_parser`, it returns `None` if any value
+/// fails to parse.
+/// # Example
+/// ```
+/// use std::collections::HashSet;
+/// use argparse::{ArgParser, ArgType, hashset_parser};
+///
+/// let mut parser = ArgParser::new("runner".into());
+/// parser.add_opt("tags", None, 't', false,
+///     "Tags to apply", ArgType::List);
+///
+/// let test_1 = "./runner -t red blue red".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// let tags: HashSet<String> = p_res.get_with("tags", hashset_parser).unwrap();
+/// assert_eq!(tags.len(), 2);
+/// assert!(tags.contains("red"));
+/// ```
+pub fn hashset_parser<T>(s: &str) -> Option<HashSet<T>>
+    where T: FromStr + Hash + Eq {
+    s.split_whitespace()
+        .map(|x| x.parse().ok())
+        .collect::<Option<HashSet<T>>>()
+        .filter(|h| !h.is_empty())
+}
+
+/// Why a value given to a `Counter`, as in `-v=3`, is rejected
+const NO_VALUE: &str = "counters take no value";
+
+/// What `bool_parser` accepts, as the reason a value was rejected
+const BOOL_EXPECTED: &str = "expected yes/no, on/off, true/false or 1/0";
+
+/// The default of an argument as it is kept: a `Flag`'s is read with
+/// `bool_parser`, so that `Some("on")` gives `get::<bool>` `true`
+/// The bytes at the end of an argument given to `parse_os` that read as
+/// `text` once invalid UTF-8 in them is replaced, if there are such
+fn trailing_bytes<'b>(bytes: &'b [u8], text: &str) -> Option<&'b [u8]> {
+    let lossy = String::from_utf8_lossy(bytes);
+    let skip = lossy.len().checked_sub(text.len()).filter(|_| lossy.ends_with(text))?;
+    
+    // Each run of invalid bytes reads as one `U+FFFD`
+    let (mut read, mut taken) = (0, 0);
+    for chunk in bytes.utf8_chunks() {
+        if skip < read {
+            return None;
+        }
+        
+        let valid = chunk.valid().len();
+        if skip <= read + valid {
+            return Some(&bytes[taken + skip - read..]);
+        }
+        
+        read += valid;
+        taken += valid + chunk.invalid().len();
+        if !chunk.invalid().is_empty() {
+            read += char::REPLACEMENT_CHARACTER.len_utf8();
+        }
+    }
+    
+    Some(&bytes[taken..]).filter(|_| skip == read)
+}
+
+fn stored_default(type_: &ArgType, default: Option<&str>) -> Option<String> {
+    default.map(|d| match (type_, bool_parser(d)) {
+        (&ArgType::Flag, Some(b)) => b.to_string(),
+        _ => d.into(),
+    })
+}
+
+/// Function that parses booleans leniently: besides `true` and `false`
+/// it accepts `yes`/`no`, `y`/`n`, `on`/`off` and `1`/`0`, ignoring
+/// case. Values attached to `Flag`s, as in `--verbose=off`, and their
+/// defaults are read this way. Usable with `get_with`.
+/// # Example
+/// ```
+/// use argparse::{ArgParser, ArgType, bool_parser};
+///
+/// let mut parser = ArgParser::new("runner".into());
+/// parser.add_opt("cache", Some("on"), 'c', false,
+///     "Whether to cache results", ArgType::Option);
+/// parser.add_opt("verbose", Some("off"), 'v', false,
+///     "Whether to produce verbose output", ArgType::Flag);
+///
+/// assert_eq!(parser.parse_line("").unwrap().get("verbose"), Some(false));
+///
+/// let test_1 = "./runner --cache NO --verbose=yes".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// assert_eq!(p_res.get_with("cache", bool_parser), Some(false));
+/// assert_eq!(p_res.get("verbose"), Some(true));
+/// ```
+pub fn bool_parser(s: &str) -> Option<bool> {
+    match &*s.trim().to_lowercase() {
+        "true" | "yes" | "y" | "on" | "1" => Some(true),
+        "false" | "no" | "n" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Function that parses a proportion, given either as a percentage
+/// (`75%`) or as a fraction (`0.75`), into a fraction between 0 and 1.
+/// Usable with `get_with`.
+/// # Example
+/// ```
+/// use argparse::{ArgParser, ArgType, percentage_parser, percentage_validator};
+///
+/// let mut parser = ArgParser::new("sampler".into());
+/// parser.add_opt("rate", Some("100%"), 'r', false,
+///     "Share of requests to sample", ArgType::Option);
+/// parser.validator("rate", percentage_validator).unwrap();
+///
+/// let test_1 = "./sampler --rate 12.5%".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// assert_eq!(p_res.get_with("rate", percentage_parser), Some(0.125));
+/// ```
+pub fn percentage_parser(s: &str) -> Option<f64> {
+    check_percentage(s).ok()
+}
+
+/// Validator for proportions, as read by `percentage_parser`
+pub fn percentage_validator(s: &str) -> Result<(), String> {
+    check_percentage(s).map(|_| ())
+}
+
+fn check_percentage(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let (number, scale) = match s.strip_suffix('%') {
+        Some(number) => (number.trim_end(), 100.0),
+        None => (s, 1.0),
+    };
+    
+    let value = number.parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| String::from("expected a percentage like `75%` or a fraction like `0.75`"))?
+        / scale;
+    
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else if scale == 1.0 {
+        Err("must be a fraction between 0 and 1, or a percentage like `75%`".into())
+    } else {
+        Err("must be between 0% and 100%".into())
+    }
+}
+
+/// Integer types that can be parsed in a given base, for use with
+/// `int_parser`. It is implemented for all the primitive integers.
+pub trait FromRadix: Sized {
+    /// Parses `s`, an optional `-` followed by digits in base `radix`
+    fn from_radix(s: &str, radix: u32) -> Option<Self>;
+}
+
+macro_rules! impl_from_radix {
+    ($($t:ty)*) => {$(
+        impl FromRadix for $t {
+            fn from_radix(s: &str, radix: u32) -> Option<$t> {
+                <$t>::from_str_radix(s, radix).ok()
+            }
+        }
+    )*}
+}
+
+impl_from_radix!(i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize);
+
+/// Function that parses integers written the way Rust literals are:
+/// with a `0x`, `0o` or `0b` prefix for hexadecimal, octal or binary,
+/// and with `_` between digits, as in `0x1F`, `0o755` or `1_000_000`.
+/// A `-` may come before the prefix. Usable with `get_with`.
+/// # Example
+/// ```
+/// use argparse::{ArgParser, ArgType, int_parser};
+///
+/// let mut parser = ArgParser::new("chmod".into());
+/// parser.add_opt("mode", None, 'm', true,
+///     "Permissions to set", ArgType::Option);
+///
+/// let test_1 = "./chmod --mode 0o755".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// assert_eq!(p_res.get_with("mode", int_parser::<u32>), Some(493));
+/// ```
+pub fn int_parser<T: FromRadix>(s: &str) -> Option<T> {
+    let (sign, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", s.strip_prefix('+').unwrap_or(s)),
+    };
+    
+    let lower = unsigned.get(..2).map(|p| p.to_ascii_lowercase());
+    let (radix, digits) = match lower.as_deref() {
+        Some("0x") => (16, &unsigned[2..]),
+        Some("0o") => (8, &unsigned[2..]),
+        Some("0b") => (2, &unsigned[2..]),
+        _ => (10, unsigned),
+    };
+    
+    // Underscores may only separate digits, and a sign was taken already
+    if digits.starts_with(['_', '+', '-']) || digits.ends_with('_') {
+        return None;
+    }
+    
+    let digits: String = sign.chars().chain(digits.chars().filter(|&c| c != '_')).collect();
+    T::from_radix(&digits, radix)
+}
+
+/// Function that parses `Dict` arguments into `HashMap`s.
+/// Provided for user convenience and use as an implementor of
+/// [`ArgGetter`](./trait.ArgGetter.html).
+/// Returns `None` if any pair lacks the `:` separator (it expects
+/// `key:value key2:value2...`) or fails to parse; use
+/// `try_hashmap_parser` to find out which.
+pub fn hashmap_parser<K, V>(s: &str) -> Option<HashMap<K,V>> 
+    where K: FromStr + Hash + Eq,
+          V: FromStr {
+    try_hashmap_parser(s).ok().filter(|h| !h.is_empty())
+}
+
+/// Function that parses `Dict` arguments into `HashMap`s like
+/// `hashmap_parser`, but on failure returns the first pair that lacks
+/// the `:` separator or whose key or value fails to parse.
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use argparse::try_hashmap_parser;
+///
+/// let h: Result<HashMap<String, u8>, _> = try_hashmap_parser("a:1 b2 c:3");
+/// assert_eq!(h, Err("b2"));
+///
+/// let h: Result<HashMap<String, u8>, _> = try_hashmap_parser("a:1 b:300");
+/// assert_eq!(h, Err("b:300"));
+/// ```
+pub fn try_hashmap_parser<K, V>(s: &str) -> Result<HashMap<K, V>, &str>
+    where K: FromStr + Hash + Eq,
+          V: FromStr {
+    s.split_whitespace()
+        .map(|x| {
+            x.split_once(':')
+                .and_then(|(k, v)| k.parse().ok().and_then(|k2|
+                    v.parse().ok().map(|v2| (k2, v2))))
+                .ok_or(x)
+        })
+        .collect()
+}
+
+/// Function that parses `Dict` arguments into `BTreeMap`s, whose
+/// iteration order is sorted by key rather than random, e.g. for
+/// deterministic output. It follows the same rules as `hashmap_parser`.
+/// # Example
+/// ```
+/// use std::collections::BTreeMap;
+/// use argparse::{ArgParser, ArgType, btreemap_parser};
+///
+/// let mut parser = ArgParser::new("runner".into());
+/// parser.add_opt("socks", None, 's', false,
+///     "If you wear socks that day", ArgType::Dict);
+///
+/// let test_1 = "./runner -s Sunday:true Monday:false".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// let socks: BTreeMap<String, bool> = p_res.get_with("socks", btreemap_parser).unwrap();
+/// assert_eq!(socks.keys().collect::<Vec<_>>(), vec!["Monday", "Sunday"]);
+/// ```
+pub fn btreemap_parser<K, V>(s: &str) -> Option<BTreeMap<K, V>>
+    where K: FromStr + Ord,
+          V: FromStr {
+    s.split_whitespace()
+        .map(|x| {
+            x.split_once(':').and_then(|(k, v)| k.parse().ok().and_then(|k2|
+                v.parse().ok().map(|v2| (k2, v2))))
+        })
+        .collect::<Option<BTreeMap<K, V>>>()
+        .filter(|b| !b.is_empty())
+}
+
+/// Function that parses `Dict` arguments into a `Vec` of key-value
+/// pairs, keeping the order they were given in, which a `HashMap` loses.
+/// It follows the same rules as `hashmap_parser`.
+/// # Example
+/// ```
+/// use argparse::{ArgParser, ArgType, ordered_dict_parser};
+///
+/// let mut parser = ArgParser::new("curl".into());
+/// parser.add_opt("header", None, 'H', false,
+///     "Headers to send", ArgType::Dict);
+///
+/// let test_1 = "./curl -H Accept:json -H Auth:token".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// let headers: Vec<(String, String)> = p_res.get_with("header", ordered_dict_parser).unwrap();
+/// assert_eq!(headers[0], ("Accept".into(), "json".into()));
+/// ```
+pub fn ordered_dict_parser<K, V>(s: &str) -> Option<Vec<(K, V)>>
+    where K: FromStr,
+          V: FromStr {
+    s.split_whitespace()
+        .map(|x| {
+            x.split_once(':').and_then(|(k, v)| k.parse().ok().and_then(|k2|
+                v.parse().ok().map(|v2| (k2, v2))))
+        })
+        .collect::<Option<Vec<(K, V)>>>()
+        .filter(|v| !v.is_empty())
+}
+
+/// Function that parses `Dict` arguments into maps from each key to all
+/// of its values, splitting the values of a pair at commas and adding
+/// those of a repeated key to it, as in `env:prod,staging env:dev`.
+/// Values are seen joined by spaces, so prefer
+/// `ArgParseResults::get_multimap` when they may contain whitespace.
+/// Otherwise it follows the same rules as `hashmap_parser`.
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use argparse::{ArgParser, ArgType, DuplicateKeys, multimap_parser};
+///
+/// let mut parser = ArgParser::new("kubectl".into());
+/// parser.add_opt("selector", None, 's', false,
+///     "Labels to select by", ArgType::Dict);
+/// parser.duplicate_keys("selector", DuplicateKeys::Keep).unwrap();
+///
+/// let test_1 = "./kubectl -s env:prod,staging -s env:dev".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// let selector: HashMap<String, Vec<String>> = p_res.get_with("selector", multimap_parser).unwrap();
+/// assert_eq!(selector["env"], ["prod", "staging", "dev"]);
+/// ```
+pub fn multimap_parser<K, V>(s: &str) -> Option<HashMap<K, Vec<V>>>
+    where K: FromStr + Hash + Eq,
+          V: FromStr {
+    multimap(s.split_whitespace().map(|x| x.split_once(':')))
+        .filter(|m| !m.is_empty())
+}
+
+/// Makes a function that parses a value made of two parts joined by
+/// `sep`, like `1920x1080` or `key:value`, into a tuple. The value is
+/// split at the first `sep`. Usable with `get_with`.
+/// # Example
+/// ```
+/// use argparse::{ArgParser, ArgType, tuple_parser};
+///
+/// let mut parser = ArgParser::new("runner".into());
+/// parser.add_opt("size", Some("800x600"), 's', false,
+///     "Window size", ArgType::Option);
+///
+/// let test_1 = "./runner --size 1920x1080".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// assert_eq!(p_res.get_with("size", tuple_parser("x")), Some((1920u32, 1080u32)));
+/// ```
+pub fn tuple_parser<'s, T, U>(sep: &'s str) -> impl Fn(&str) -> Option<(T, U)> + Clone + 's
+    where T: FromStr,
+          U: FromStr {
+    move |s: &str| {
+        let (a, b) = s.split_once(sep)?;
+        
+        Some((a.parse().ok()?, b.parse().ok()?))
+    }
+}
+
+/// Makes a function that parses a value made of three parts joined by
+/// `sep`, like `1.2.3` or `255,128,0`, into a tuple. Usable with
+/// `get_with`.
+/// # Example
+/// ```
+/// use argparse::triple_parser;
+///
+/// let rgb = triple_parser::<u8, u8, u8>(",");
+/// assert_eq!(rgb("255,128,0"), Some((255, 128, 0)));
+/// assert_eq!(rgb("255,128"), None);
+/// ```
+pub fn triple_parser<'s, T, U, V>(sep: &'s str) -> impl Fn(&str) -> Option<(T, U, V)> + Clone + 's
+    where T: FromStr,
+          U: FromStr,
+          V: FromStr {
+    move |s: &str| {
+        let (a, rest) = s.split_once(sep)?;
+        let (b, c) = rest.split_once(sep)?;
+        
+        Some((a.parse().ok()?, b.parse().ok()?, c.parse().ok()?))
+    }
+}
+
+/// Function that parses human-readable durations such as `30s`, `5m`,
+/// `1h30m`, `250ms` or `1.5h`. Each number needs a unit, one of `ns`,
+/// `us` (or `µs`), `ms`, `s`, `m`, `h` and `d`, and the parts are added
+/// up. Usable with `get_with`.
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use argparse::{ArgParser, ArgType, duration_parser};
+///
+/// let mut parser = ArgParser::new("runner".into());
+/// parser.add_opt("timeout", Some("30s"), 't', false,
+///     "How long to wait", ArgType::Option);
+///
+/// let test_1 = "./runner --timeout 1h30m".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// assert_eq!(p_res.get_with("timeout", duration_parser), Some(Duration::from_secs(5400)));
+/// ```
+pub fn duration_parser(s: &str) -> Option<Duration> {
+    let is_number = |c: char| c.is_ascii_digit() || c == '.';
+    let mut rest = s.trim();
+    let mut nanos: u64 = 0;
+    
+    if rest.is_empty() {
+        return None;
+    }
+    
+    while !rest.is_empty() {
+        let (number, tail) = rest.split_at(rest.find(|c| !is_number(c)).unwrap_or(rest.len()));
+        let (unit, tail) = tail.split_at(tail.find(is_number).unwrap_or(tail.len()));
+        
+        let per_unit: u64 = match unit {
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "s" => 1_000_000_000,
+            "m" => 60 * 1_000_000_000,
+            "h" => 3_600 * 1_000_000_000,
+            "d" => 86_400 * 1_000_000_000,
+            _ => return None,
+        };
+        
+        let part = if number.contains('.') {
+            let part = number.parse::<f64>().ok()? * per_unit as f64;
+            
+            if !part.is_finite() || part >= u64::MAX as f64 {
+                return None;
+            }
+            part.round() as u64
+        } else {
+            number.parse::<u64>().ok()?.checked_mul(per_unit)?
+        };
+        
+        nanos = nanos.checked_add(part)?;
+        rest = tail;
+    }
+    
+    Some(Duration::from_nanos(nanos))
+}
+
+/// Function that parses sizes such as `10MB`, `4GiB`, `512k` or `1.5G`
+/// into a number of bytes. SI prefixes (`k`, `M`, `G`, `T`, `P`, `E`)
+/// are powers of 1000 and binary ones (`Ki`, `Mi`, ...) powers of 1024;
+/// case is ignored and a trailing `B` is optional. Usable with
+/// `get_with`.
+/// # Example
+/// ```
+/// use argparse::{ArgParser, ArgType, bytesize_parser};
+///
+/// let mut parser = ArgParser::new("runner".into());
+/// parser.add_opt("cache", Some("512k"), 'c', false,
+///     "Size of the cache", ArgType::Option);
+///
+/// let test_1 = "./runner --cache 4GiB".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// assert_eq!(p_res.get_with("cache", bytesize_parser), Some(4 * 1024 * 1024 * 1024));
+/// ```
+pub fn bytesize_parser(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let (number, unit) = s.split_at(s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len()));
+    
+    let unit = unit.trim_start().to_lowercase();
+    let unit = unit.strip_suffix('b').unwrap_or(&unit);
+    let (prefix, base) = match unit.strip_suffix('i') {
+        Some(prefix) if !prefix.is_empty() => (prefix, 1024u64),
+        _ => (unit, 1000u64),
+    };
+    
+    let power = match prefix {
+        "" => 0,
+        "k" => 1,
+        "m" => 2,
+        "g" => 3,
+        "t" => 4,
+        "p" => 5,
+        "e" => 6,
+        _ => return None,
+    };
+    let multiplier = base.checked_pow(power)?;
+    
+    if number.contains('.') {
+        let bytes = number.parse::<f64>().ok()? * multiplier as f64;
+        
+        if !bytes.is_finite() || bytes >= u64::MAX as f64 {
+            return None;
+        }
+        Some(bytes.round() as u64)
+    } else {
+        number.parse::<u64>().ok()?.checked_mul(multiplier)
+    }
+}
+
+/// Splits a `host:port` value, where an IPv6 host must be in brackets
+/// (`[::1]:80`), explaining what is wrong if it can't be done
+fn split_host_port(s: &str) -> Result<(&str, u16), String> {
+    let (host, port) = match s.strip_prefix('[') {
+        Some(rest) => match rest.split_once("]:") {
+            Some(parts) => parts,
+            None => return Err("missing `]:port` after the IPv6 address".into()),
+        },
+        None => match s.rsplit_once(':') {
+            Some((host, _)) if host.contains(':') =>
+                return Err("IPv6 addresses need brackets, as in `[::1]:80`".into()),
+            Some(parts) => parts,
+            None => return Err("missing `:port`".into()),
+        },
+    };
+    
+    if host.is_empty() {
+        return Err("missing host".into());
+    }
+    
+    if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) {
+        return port.parse()
+            .map(|port| (host, port))
+            .map_err(|_| "port out of range".into());
+    }
+    
+    Err(format!("port `{}` is not a number", port))
+}
+
+/// Whether `host` is an IP address or a valid host name
+fn valid_host(host: &str) -> bool {
+    host.parse::<IpAddr>().is_ok() || host.split('.').all(|label| {
+        !label.is_empty() && label.len() <= 63
+            && !label.starts_with('-') && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// Validator for IP addresses, v4 or v6. The values can then be read
+/// with `get::<IpAddr>`.
+pub fn ip_validator(s: &str) -> Result<(), String> {
+    s.parse::<IpAddr>()
+        .map(|_| ())
+        .map_err(|_| "not an IP address".into())
+}
+
+/// Validator for socket addresses, an IP address and port such as
+/// `127.0.0.1:80` or `[::1]:80`. The values can then be read with
+/// `get::<SocketAddr>`.
+pub fn socket_addr_validator(s: &str) -> Result<(), String> {
+    if s.parse::<SocketAddr>().is_ok() {
+        return Ok(());
+    }
+    
+    let (host, _) = split_host_port(s)?;
+    Err(format!("`{}` is not an IP address", host))
+}
+
+/// Function that parses `host:port` values, where the host is a name or
+/// an IP address (IPv6 in brackets), into a host and port. Usable with
+/// `get_with`.
+/// # Example
+/// ```
+/// use argparse::host_port_parser;
+///
+/// assert_eq!(host_port_parser("example.com:443"), Some(("example.com".into(), 443)));
+/// assert_eq!(host_port_parser("[::1]:80"), Some(("::1".into(), 80)));
+/// assert_eq!(host_port_parser("example.com"), None);
+/// ```
+pub fn host_port_parser(s: &str) -> Option<(String, u16)> {
+    match split_host_port(s) {
+        Ok((host, port)) if valid_host(host) => Some((host.into(), port)),
+        _ => None,
+    }
+}
+
+/// Validator for `host:port` values, as read by `host_port_parser`
+pub fn host_port_validator(s: &str) -> Result<(), String> {
+    let (host, _) = split_host_port(s)?;
+    
+    if valid_host(host) {
+        Ok(())
+    } else {
+        Err(format!("`{}` is not a valid host name", host))
+    }
+}
+
+/// Function that parses network ranges in CIDR notation, such as
+/// `10.0.0.0/8` or `fe80::/10`, into an address and prefix length.
+/// Usable with `get_with`.
+/// # Example
+/// ```
+/// use std::net::IpAddr;
+/// use argparse::cidr_parser;
+///
+/// assert_eq!(cidr_parser("10.0.0.0/8"), Some(("10.0.0.0".parse::<IpAddr>().unwrap(), 8)));
+/// assert_eq!(cidr_parser("10.0.0.0/33"), None);
+/// ```
+pub fn cidr_parser(s: &str) -> Option<(IpAddr, u8)> {
+    split_cidr(s).ok()
+}
+
+/// Validator for network ranges in CIDR notation, as read by
+/// `cidr_parser`
+pub fn cidr_validator(s: &str) -> Result<(), String> {
+    split_cidr(s).map(|_| ())
+}
+
+fn split_cidr(s: &str) -> Result<(IpAddr, u8), String> {
+    let (addr, len) = s.split_once('/').ok_or("missing `/prefix`")?;
+    let addr: IpAddr = addr.parse().map_err(|_| format!("`{}` is not an IP address", addr))?;
+    let max = if addr.is_ipv4() { 32 } else { 128 };
+    
+    match len.parse::<u8>() {
+        Ok(len) if len <= max => Ok((addr, len)),
+        _ => Err(format!("prefix length `{}` out of range (at most {})", len, max)),
+    }
+}
+
+/// Function that parses absolute URLs with a host, such as
+/// `https://example.com/api`, into `Url`s. Usable with `get_with`.
+/// Requires the `url` feature.
+/// # Example
+/// ```
+/// # #[cfg(feature = "url")]
+/// # fn main() {
+/// use argparse::{ArgParser, ArgType, url_parser, url_validator};
+///
+/// let mut parser = ArgParser::new("client".into());
+/// parser.add_opt("endpoint", Some("http://localhost:8080"), 'e', false,
+///     "Server to talk to", ArgType::Option);
+/// parser.validator("endpoint", url_validator).unwrap();
+///
+/// let test_1 = "./client --endpoint https://example.com/api".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// let url = p_res.get_with("endpoint", url_parser).unwrap();
+/// assert_eq!(url.host_str(), Some("example.com"));
+/// # }
+/// # #[cfg(not(feature = "url"))]
+/// # fn main() {}
+/// ```
+#[cfg(feature = "url")]
+pub fn url_parser(s: &str) -> Option<Url> {
+    check_url(s).ok()
+}
+
+/// Validator for URLs, as read by `url_parser`. Requires the `url`
+/// feature.
+#[cfg(feature = "url")]
+pub fn url_validator(s: &str) -> Result<(), String> {
+    check_url(s).map(|_| ())
+}
+
+/// Makes a validator for URLs, as read by `url_parser`, that also
+/// restricts the scheme to one of `schemes`. Requires the `url`
+/// feature.
+/// # Example
+/// ```
+/// # #[cfg(feature = "url")]
+/// # fn main() {
+/// use argparse::url_scheme_validator;
+///
+/// let web = url_scheme_validator(&["http", "https"]);
+/// assert!(web("https://example.com").is_ok());
+/// assert_eq!(web("ftp://example.com"),
+///     Err("scheme `ftp` is not one of `http`, `https`".into()));
+/// # }
+/// # #[cfg(not(feature = "url"))]
+/// # fn main() {}
+/// ```
+#[cfg(feature = "url")]
+pub fn url_scheme_validator<'s>(schemes: &'s [&'s str]) -> impl Fn(&str) -> Result<(), String> + Clone + 's {
+    move |s: &str| {
+        let url = check_url(s)?;
+        
+        if schemes.iter().any(|scheme| scheme.eq_ignore_ascii_case(url.scheme())) {
+            Ok(())
+        } else {
+            let allowed: Vec<String> = schemes.iter().map(|s| format!("`{}`", s)).collect();
+            Err(format!("scheme `{}` is not one of {}", url.scheme(), allowed.join(", ")))
+        }
+    }
+}
+
+#[cfg(feature = "url")]
+fn check_url(s: &str) -> Result<Url, String> {
+    let url = Url::parse(s).map_err(|e| e.to_string())?;
+    
+    match url.host_str() {
+        Some(host) if !host.is_empty() => Ok(url),
+        _ => Err("missing host".into()),
+    }
+}
+
+/// Function that parses dates, either in ISO 8601 form (`2024-01-31`)
+/// or relative to today: `today`, `yesterday`, `tomorrow`, or a number
+/// of days or weeks before or after it, as in `-2d` or `+1w`. Usable
+/// with `get_with`. Requires the `chrono` feature.
+/// # Example
+/// ```
+/// # #[cfg(feature = "chrono")]
+/// # fn main() {
+/// use argparse::{ArgParser, ArgType, date_parser};
+///
+/// let mut parser = ArgParser::new("report".into());
+/// parser.add_opt("since", Some("-1w"), 's', false,
+///     "First day to report on", ArgType::Option);
+/// parser.add_opt("until", Some("today"), 'u', false,
+///     "Last day to report on", ArgType::Option);
+///
+/// let test_1 = "./report --since 2024-01-31 --until yesterday".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// let since = p_res.get_with("since", date_parser).unwrap();
+/// assert_eq!(since.to_string(), "2024-01-31");
+/// assert_eq!(p_res.get_with("until", date_parser), date_parser("yesterday"));
+/// # }
+/// # #[cfg(not(feature = "chrono"))]
+/// # fn main() {}
+/// ```
+#[cfg(feature = "chrono")]
+pub fn date_parser(s: &str) -> Option<NaiveDate> {
+    parse_date(s, Local::now().date_naive())
+}
+
+/// Validator for dates, as read by `date_parser`. Requires the `chrono`
+/// feature.
+#[cfg(feature = "chrono")]
+pub fn date_validator(s: &str) -> Result<(), String> {
+    date_parser(s)
+        .map(|_| ())
+        .ok_or_else(|| "expected a date like `2024-01-31`, `yesterday` or `-2d`".into())
+}
+
+/// Parses a date given in any of the forms `date_parser` accepts,
+/// relative to `today`
+#[cfg(feature = "chrono")]
+fn parse_date(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let s = s.trim();
+    
+    match &*s.to_lowercase() {
+        "today" => return Some(today),
+        "yesterday" => return today.pred_opt(),
+        "tomorrow" => return today.succ_opt(),
+        _ => {}
+    }
+    
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Some(date);
+    }
+    
+    let (sign, rest) = match s.chars().next() {
+        Some('-') => (-1, &s[1..]),
+        Some('+') => (1, &s[1..]),
+        _ => return None,
+    };
+    let (days, per_unit) = match (rest.strip_suffix('d'), rest.strip_suffix('w')) {
+        (Some(days), _) => (days, 1),
+        (_, Some(weeks)) => (weeks, 7),
+        _ => return None,
+    };
+    
+    let days: i64 = days.parse().ok().filter(|_| days.chars().all(|c| c.is_ascii_digit()))?;
+    let offset = Days::try_days(days.checked_mul(per_unit)?.checked_mul(sign)?)?;
+    today.checked_add_signed(offset)
+}
+
+/// Function that parses times of day, as in `09:30` or `23:59:59.5`.
+/// Usable with `get_with`. Requires the `chrono` feature.
+#[cfg(feature = "chrono")]
+pub fn time_parser(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .ok()
+}
+
+/// Function that parses ISO 8601 (RFC 3339) dates and times with an
+/// offset from UTC, as in `2024-01-31T09:30:00+01:00` or
+/// `2024-01-31T08:30:00Z`. Usable with `get_with`. Requires the
+/// `chrono` feature.
+#[cfg(feature = "chrono")]
+pub fn datetime_parser(s: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(s).ok()
+}
+
+/// Function that parses ISO 8601 dates and times without an offset
+/// from UTC, as in `2024-01-31T09:30` or `2024-01-31 09:30:00`. Usable
+/// with `get_with`. Requires the `chrono` feature.
+#[cfg(feature = "chrono")]
+pub fn naive_datetime_parser(s: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"].iter()
+        .filter_map(|fmt| NaiveDateTime::parse_from_str(s, fmt).ok())
+        .next()
+}
+
+/// Validator for dates and times, with or without an offset from UTC,
+/// as read by `datetime_parser` and `naive_datetime_parser`. Requires
+/// the `chrono` feature.
+#[cfg(feature = "chrono")]
+pub fn datetime_validator(s: &str) -> Result<(), String> {
+    if datetime_parser(s).is_some() || naive_datetime_parser(s).is_some() {
+        Ok(())
+    } else {
+        Err("expected a date and time like `2024-01-31T09:30:00`".into())
+    }
+}
+
+/// Replaces `${VAR}` with the environment variable `VAR`, and `$${`
+/// with `${`, leaving `s` borrowed when there is nothing to replace
+fn interpolate(s: &str) -> Result<Cow<'_, str>, String> {
+    if !s.contains("${") {
+        return Ok(s.into());
+    }
+    
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    
+    while let Some(at) = rest.find("${") {
+        if rest[..at].ends_with('$') {
+            out.push_str(&rest[..at - 1]);
+            out.push_str("${");
+            rest = &rest[at + 2..];
+            continue;
+        }
+        
+        out.push_str(&rest[..at]);
+        let end = rest[at..].find('}').ok_or("`${` is never closed by `}`")?;
+        let var = &rest[at + 2..at + end];
+        let value = env::var(var).map_err(|_| format!("the environment variable `{}` is not set", var))?;
+        
+        out.push_str(&value);
+        rest = &rest[at + end + 1..];
+    }
+    
+    out.push_str(rest);
+    Ok(out.into())
+}
+
+/// Whether an argument was given on the command line, rather than
+/// only having a default
+fn given(def: &Arg, v: &Found) -> bool {
+    match def.type_ {
+        ArgType::Positional(_) | ArgType::PositionalList(_) | ArgType::Command | ArgType::Remainder => v.vals.is_some(),
+        _ => v.count > 0,
+    }
+}
+
+/// The choice of an argument a value matches, if it has choices
+fn choice<'d>(def: &'d Arg, val: &str) -> Option<&'d String> {
+    let exact = def.choices.iter().find(|&c| c == val);
+    
+    exact.or_else(|| def.choices.iter()
+        .find(|&c| def.ignore_case && c.to_lowercase() == val.to_lowercase()))
+}
+
+/// A flag as it is compared, lowercased unless case matters
+fn fold(flag: &str, case_sensitive: bool) -> Cow<'_, str> {
+    if case_sensitive {
+        Cow::Borrowed(flag)
+    } else {
+        Cow::Owned(flag.to_lowercase())
+    }
+}
+
+/// Names of arguments as a list for messages, as in "`x`, `y`"
+fn listed<S: AsRef<str>>(names: &[S]) -> String {
+    names.iter()
+        .map(|name| format!("`{}`", name.as_ref()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Whether giving an argument again replaces the values it was given
+fn replaces_values(def: &Arg) -> bool {
+    match def.type_ {
+        ArgType::Option => def.joiner.is_none(),
+        ArgType::Path => true,
+        ArgType::List => def.list_values == ListValues::Greedy,
+        ArgType::Json => true,
+        _ => false,
+    }
+}
+
+/// The values a `List` value stands for: the paths it matches if it is
+/// a glob pattern and the argument expands them, otherwise itself
+#[cfg(feature = "glob")]
+fn expand_glob<'a>(def: &Arg, val: &'a str) -> Result<Vec<Cow<'a, str>>, String> {
+    if !def.globs || def.type_ != ArgType::List || !val.contains(['*', '?', '[']) {
+        return Ok(vec![val.into()]);
+    }
+    
+    let paths = glob::glob(val).map_err(|e| e.to_string())?;
+    let matched: Vec<Cow<'a, str>> = paths
+        .filter_map(Result::ok)
+        .map(|path| match path.into_os_string().into_string() {
+            Ok(path) => Ok(path.into()),
+            Err(path) => Err(format!("it matched `{}`, which isn't UTF-8", path.to_string_lossy())),
+        })
+        .collect::<Result<_, _>>()?;
+    
+    if matched.is_empty() {
+        Ok(vec![val.into()])
+    } else {
+        Ok(matched)
+    }
+}
+
+#[cfg(not(feature = "glob"))]
+fn expand_glob<'a>(_: &Arg, val: &'a str) -> Result<Vec<Cow<'a, str>>, String> {
+    Ok(vec![val.into()])
+}
+
+/// Checks that the value of a `Json` argument is valid JSON
+#[cfg(feature = "json")]
+fn check_json(val: &str) -> Result<(), String> {
+    serde_json::from_str::<serde_json::Value>(val)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "json"))]
+fn check_json(_: &str) -> Result<(), String> {
+    Err("reading JSON needs the `json` feature".into())
+}
+
+/// The user's home directory, from `HOME` or, on Windows, `USERPROFILE`
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .filter(|h| !h.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Applies the normalizations asked for to a path, leaving it borrowed
+/// when there are none
+fn normalize_path(opts: PathOptions, val: &str) -> Result<Cow<'_, str>, String> {
+    if opts == PathOptions::default() {
+        return Ok(val.into());
+    }
+    
+    let mut path = PathBuf::from(val);
+    
+    if let (true, Some(tilde)) = (opts.expand_tilde, val.strip_prefix('~')) {
+        let (user, rest) = tilde.split_at(tilde.find(::std::path::is_separator).unwrap_or(tilde.len()));
+        let rest = rest.trim_start_matches(::std::path::is_separator);
+        
+        if !user.is_empty() {
+            return Err(format!("`~{}` can't be expanded, only `~` for the home directory", user));
+        }
+        
+        let home = home_dir().ok_or("the home directory is unknown")?;
+        path = if rest.is_empty() { home } else { home.join(rest) };
+    }
+    
+    if opts.canonicalize {
+        path = path.canonicalize().map_err(|e| e.to_string())?;
+    } else if opts.absolute && path.is_relative() {
+        path = env::current_dir().map_err(|e| e.to_string())?.join(path);
+    }
+    
+    // Parts that aren't Unicode are replaced, as values are strings
+    Ok(path.to_string_lossy().into_owned().into())
+}
+
+/// Width assumed for a tab when wrapping help
+const TAB_WIDTH: usize = 8;
+
+/// Narrowest column that help text will be squeezed into
+const MIN_WRAP: usize = 20;
+
+/// How far flags are indented in the options section of help
+const HELP_INDENT: usize = 2;
+
+/// Narrowest space between flags and their description
+const COLUMN_GAP: usize = 2;
+
+/// Furthest in that descriptions start by default
+const MAX_COLUMN: usize = 32;
+
+/// The number of columns `s` takes up on a terminal. With the
+/// `unicode-width` feature, wide characters (as in CJK text) count as
+/// two columns and combining marks as none; otherwise every `char`
+/// counts as one.
+#[cfg(feature = "unicode-width")]
+fn display_width(s: &str) -> usize {
+    use unicode_width::UnicodeWidthStr;
+    
+    UnicodeWidthStr::width(s)
+}
+
+#[cfg(not(feature = "unicode-width"))]
+fn display_width(s: &str) -> usize {
+    s.chars().count()
+}
+
+/// Greedily word-wraps `text`, allowing `first` columns on the first
+/// line and `rest` on the others. Words too long for a line get a line
+/// of their own rather than being split.
+fn wrap(text: &str, first: usize, rest: usize) -> Vec<String> {
+    wrap_words(text.split_whitespace(), first, rest)
+}
+
+/// Like `wrap`, for text already split into the pieces that must stay
+/// on one line, such as the arguments of the usage line
+fn wrap_words<'a, I>(words: I, first: usize, rest: usize) -> Vec<String>
+    where I: IntoIterator<Item = &'a str> {
+    let mut lines = vec![String::new()];
+    let mut limit = ::std::cmp::max(first, MIN_WRAP);
+    
+    for word in words {
+        let len = lines.last().map_or(0, |l| display_width(l));
+        let needed = display_width(word) + if len == 0 { 0 } else { 1 };
+        
+        if len > 0 && len + needed > limit {
+            lines.push(String::new());
+            limit = ::std::cmp::max(rest, MIN_WRAP);
+        }
+        
+        if let Some(line) = lines.last_mut() {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+    }
+    
+    lines
+}
+
+/// Number of lines the terminal can show. It is asked of the terminal
+/// with the `terminal_size` feature, and otherwise, or when standard
+/// output isn't a terminal, read from `LINES`, falling back to 24.
+fn terminal_height() -> usize {
+    queried_height()
+        .or_else(|| env::var("LINES").ok().and_then(|l| l.parse().ok()))
+        .unwrap_or(24)
+}
+
+#[cfg(feature = "terminal_size")]
+fn queried_height() -> Option<usize> {
+    terminal_size::terminal_size().map(|(_, terminal_size::Height(h))| h as usize)
+}
+
+#[cfg(not(feature = "terminal_size"))]
+fn queried_height() -> Option<usize> {
+    None
+}
+
+/// Shows `text` through the user's pager, returning whether that worked
+fn page(text: &str) -> bool {
+    let pager = env::var("PAGER").ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "less".into());
+    let mut words = pager.split_whitespace();
+    let program = match words.next() {
+        Some(program) => program,
+        None => return false,
+    };
+    
+    let mut cmd = Command::new(program);
+    cmd.args(words).stdin(Stdio::piped());
+    
+    // Like git, let less pass colors through and quit on short output
+    if env::var_os("LESS").is_none() {
+        cmd.env("LESS", "FRX");
+    }
+    
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(_) => return false,
+    };
+    
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user quitting the pager early closes the pipe, which is fine
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    
+    child.wait().is_ok()
+}
+
+/// How an argument appears in the usage line, given its long flag.
+/// Arguments that aren't required are bracketed, and positionals are
+/// shown as `<NAME>`.
+fn usage(a: &Arg, long: &str) -> String {
+    let upper = a.name.to_uppercase();
+    let item = match a.type_ {
+        ArgType::Flag => format!("--{}", long),
+        ArgType::Counter => match short(a) {
+            Some(c) => format!("-{}...", c),
+            None => format!("--{}...", long),
+        },
+        ArgType::Positional(_) => format!("<{}>", upper),
+        ArgType::PositionalList(0) | ArgType::Command | ArgType::Remainder => format!("<{}>...", upper),
+        ArgType::PositionalList(_) => format!("-- <{}>...", upper),
+        _ => format!("--{} {}", long, ops(a)),
+    };
+    
+    if a.required && a.type_ != ArgType::Counter {
+        item
+    } else {
+        format!("[{}]", item)
+    }
+}
+
+/// Where an argument goes in the usage line, relative to the others
+fn usage_rank(a: &Arg) -> (u8, u8) {
+    match a.type_ {
+        ArgType::Positional(idx) => (1, idx),
+        ArgType::PositionalList(0) => (2, 0),
+        ArgType::Command | ArgType::Remainder => (3, 0),
+        ArgType::PositionalList(seg) => (4, seg),
+        _ => (0, 0),
+    }
+}
+
+/// The short flag of an argument, unless it was added without one
+fn short(a: &Arg) -> Option<char> {
+    Some(a.flag).filter(|&c| c != '\0')
+}
+
+/// The flags of an argument as help shows them, given its long flag
+fn flag_names(a: &Arg, long: &str) -> String {
+    match short(a) {
+        Some(c) => format!("--{} (-{})", long, c),
+        None => format!("--{}", long),
+    }
+}
+
+fn ops(a: &Arg) -> String {
+    let name = &a.name;
+    
+    match a.type_ {
+        ArgType::Option | ArgType::Path => name.chars().map(|c| c.to_uppercase().next().unwrap_or(c)).collect::<String>(),
+        ArgType::List | ArgType::PositionalList(_) | ArgType::Command | ArgType::Remainder =>
+            name.chars().map(|c| c.to_uppercase().next().unwrap_or(c)).chain("...".chars()).collect::<String>(),
+        ArgType::Dict => "k:v k2:v2...".into(),
+        ArgType::Json => "JSON".into(),
+        ArgType::Flag | ArgType::Counter | ArgType::Positional(_) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ArgParser, ArgParseResults, ParseError, SharedParser, ArgType, vec_parser, hashset_parser, hashmap_parser,
+        try_vec_parser, lenient_vec_parser, try_hashmap_parser, btreemap_parser, tuple_parser, triple_parser, duration_parser,
+        bytesize_parser, int_parser, bool_parser, percentage_parser, percentage_validator, ip_validator, socket_addr_validator, host_port_parser, host_port_validator,
+        cidr_parser, cidr_validator, PathOptions, ArgDiff, ConfigError, GetError, Warning, ParseOptions, Inherit,
+        TooMany, ListValues, NegativeNumbers, Interpolate, KebabCase};
+    use super::{wrap, display_width, program_name, ParseResult};
+    use std::io;
+    use color::ColorChoice;
+    use messages::Messages;
+    use std::collections::HashMap;
+    use std::env;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::time::Duration;
+    const LONG_STR: &str = r#"Check your proxy settings or contact your network administrator to make sure the proxy server is working. If you don't believe you should be using a proxy server: Go to the Chromium menu > Settings > Show advanced settings... > Change proxy settings... and make sure your configuration is set to "no proxy" or "direct.""#;
+    
+    fn setup_1() -> ArgParser {
+        let mut parser = ArgParser::new("ArgParsers".into());
+        
+        parser.add_opt("length", None, 'l', true, LONG_STR, ArgType::Option);
+        parser.add_opt("height", None, 'h', true, "Height of user in centimeters", ArgType::Option);
+        parser.add_opt("name", None, 'n', true, "Name of user", ArgType::Option);
+        parser.add_opt("frequencies", None, 'f', false, "User's favorite frequencies", ArgType::List);
+        parser.add_opt("mao", Some("false"), 'm', false, "Is the User Chairman Mao?", ArgType::Flag);
+        
+        parser
+    }
+    
+    #[test]
+    fn test_parser() {
+        let parser = setup_1();
+    
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny --mao -f 1 2 3 4 5".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        assert!(p_res.get("length") == Some(-60));
+        assert_eq!(p_res.get("height"), Some(-6001.45e-2));
+        assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
+        assert_eq!(p_res.get_with("frequencies", vec_parser), 
+            Some(vec![1,2,3,4,5]));
+        assert_eq!(p_res.get("mao"), Some(true));
+        
+        parser.help();
+    }
+    
+    #[test]
+    fn test_parser_unrequired() {
+        let parser = setup_1();
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny -f 1 2 3 4 5".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        assert!(p_res.get("length") == Some(-60));
+        assert_eq!(p_res.get("height"), Some(-6001.45e-2));
+        assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
+        assert_eq!(p_res.get_with("frequencies", vec_parser), 
+            Some(vec![1,2,3,4,5]));
+        assert_eq!(p_res.get("mao"), Some(false));
+        
+        parser.help();
+    }
+    
+    #[test]
+    fn test_parser_unrequired_nodefault() {
+        let parser = setup_1();
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        assert!(p_res.get("length") == Some(-60));
+        assert_eq!(p_res.get("height"), Some(-6001.45e-2));
+        assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
+        assert_eq!(p_res.get_with::<Vec<u8>, _>("frequencies", vec_parser), None);
+        assert_eq!(p_res.get("mao"), Some(false));
+        
+        parser.help();
+    }
+    
+    #[test]
+    fn test_get_list() {
+        let parser = setup_1();
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny --mao -f 1 2 3 4 5".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        assert_eq!(p_res.get_list("frequencies"), Some(vec![1,2,3,4,5]));
+        assert_eq!(p_res.values("frequencies"),
+            Some(&["1", "2", "3", "4", "5"].iter().map(|s| s.to_string()).collect::<Vec<_>>()[..]));
+        
+        let test_2 = "./go -l -60 -h -6001.45e-2 -n Johnny".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        
+        let p_res = parser.parse(test_2.iter()).unwrap();
+        
+        assert_eq!(p_res.get_list::<u8>("frequencies"), None);
+    }
+    
+    #[test]
+    fn test_parser_dict() {
+        let mut parser = setup_1();
+        parser.add_opt("socks", None, 's', false, "If you wear socks that day", ArgType::Dict);
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny -s Monday:true Friday:false".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        assert!(p_res.get("length") == Some(-60));
+        assert_eq!(p_res.get("height"), Some(-6001.45e-2));
+        assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
+        assert_eq!(p_res.get_with::<Vec<u8>, _>("frequencies", vec_parser), None);
+        assert_eq!(p_res.get("mao"), Some(false));
+        
+        let h = [("Monday", true), ("Friday", false)]
+            .iter()
+            .map(|&(k, v)| (k.into(), v))
+            .collect();
+            
+        assert_eq!(p_res.get_with::<HashMap<String, bool>, _>("socks", hashmap_parser),
+            Some(h));
+        
+        parser.help();
+    }
+    
+    #[test]
+    fn test_parser_whitespace_values() {
+        let mut parser = setup_1();
+        parser.add_opt("cities", None, 'c', false, "Cities to visit", ArgType::List);
+        parser.add_opt("homes", None, 'o', false, "Where people live", ArgType::Dict);
+        parser.add_opt("motto", None, 'm', false, "Motto", ArgType::Option);
+        
+        let test_1: Vec<String> = vec!["./go", "-l", "-60", "-h", "-6001.45e-2", "-n", "Johnny Smith",
+            "-c", "New York", "Paris", "-o", "Jane:New York", "John:Los Angeles",
+            "-m", "-to be or not"]
+            .into_iter()
+            .map(|s| s.into())
+            .collect();
+            
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        assert_eq!(p_res.get::<String>("name"), Some("Johnny Smith".into()));
+        assert_eq!(p_res.get::<String>("motto"), Some("-to be or not".into()));
+        assert_eq!(p_res.get_list::<String>("cities"),
+            Some(vec!["New York".into(), "Paris".into()]));
+        
+        let homes: HashMap<String, String> = p_res.get_dict("homes").unwrap();
+        assert_eq!(homes["Jane"], "New York");
+        assert_eq!(homes["John"], "Los Angeles");
+        
+        let borrowed = parser.parse_borrowed(&test_1).unwrap();
+        assert_eq!(borrowed.values("cities").map(|v| v.to_vec()), Some(vec!["New York".into(), "Paris".into()]));
+        assert_eq!(borrowed.pairs("homes"),
+            Some(&[("Jane", "New York"), ("John", "Los Angeles")][..]));
+    }
+    
+    #[test]
+    fn test_parser_unicode_flags() {
+        let mut parser = ArgParser::new("go".into());
+        parser.color(ColorChoice::Never);
+        parser.add_opt("été", None, 'é', false, "Summer", ArgType::Flag);
+        parser.add_opt("lambda", None, 'λ', false, "Lambda", ArgType::Option);
+        parser.add_opt("crab", None, '🦀', false, "Crab", ArgType::Flag);
+        parser.add_opt("ascii", None, 'a', false, "Plain", ArgType::Flag);
+        
+        let test_1 = "./go -éa -λ Ünïcödé --crab".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        assert_eq!(p_res.get("été"), Some(true));
+        assert_eq!(p_res.get("ascii"), Some(true));
+        assert_eq!(p_res.get::<String>("lambda"), Some("Ünïcödé".into()));
+        assert_eq!(p_res.get("crab"), Some(true));
+        
+        let test_2 = "./go -🦀λ".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let err = parser.parse(test_2.iter()).unwrap_err();
+        
+        assert_eq!(err.position(), Some(1));
+        let carets = "^".repeat(display_width("-🦀λ"));
+        assert!(err.render().ends_with(&format!("./go -🦀λ\n       {}\n", carets)));
+    }
+    
+    #[test]
+    fn test_parser_dict_malformed() {
+        let mut parser = setup_1();
+        parser.add_opt("socks", None, 's', false, "If you wear socks that day", ArgType::Dict);
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny -s Monday:true Friday".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let err = parser.parse(test_1.iter()).unwrap_err();
+        
+        assert_eq!(err.position(), Some(9));
+        assert!(err.message().contains("`Friday`"));
+    }
+    
+    #[test]
+    fn test_parser_dict_pairs() {
+        let mut parser = setup_1();
+        parser.add_opt("socks", None, 's', false, "If you wear socks that day", ArgType::Dict);
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny -s Monday:true Friday:false".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        let h: HashMap<String, bool> = [("Monday", true), ("Friday", false)]
+            .iter()
+            .map(|&(k, v)| (k.into(), v))
+            .collect();
+            
+        assert_eq!(p_res.get_dict::<String, bool>("socks"), Some(h));
+        assert_eq!(p_res.pairs("socks"), Some(&[("Monday".into(), "true".into()),
+            ("Friday".into(), "false".into())][..]));
+    }
+    
+    #[test]
+    fn test_parser_positional() {
+        let mut parser = setup_1();
+        
+        parser.add_opt("csv", None, 'c', true, "csv input file",
+            ArgType::Positional(0));
+        parser.add_opt("json", None, 'j', true, "json output file",
+            ArgType::Positional(1));
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny crap.csv crap.json".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        assert!(p_res.get("length") == Some(-60));
+        assert_eq!(p_res.get("height"), Some(-6001.45e-2));
+        assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
+        assert_eq!(p_res.get_with::<Vec<u8>, _>("frequencies", vec_parser), None);
+        assert_eq!(p_res.get("mao"), Some(false));
+        assert_eq!(p_res.get::<String>("csv"), Some("crap.csv".into()));
+        assert_eq!(p_res.get::<String>("json"), Some("crap.json".into()));
+        
+        parser.help();
+    }
+    
+    #[test]
+    fn test_parser_error_position() {
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Never);
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -mn".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let err = parser.parse(test_1.iter()).unwrap_err();
+        
+        assert_eq!(err.position(), Some(5));
+        assert_eq!(err.render(), "error: This option `name` requires a value you have not provided\n  \
+            ./go -l -60 -h -6001.45e-2 -mn\n                             ^^^\n");
+    }
+    
+    #[test]
+    fn test_parser_error_missing() {
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Always);
+        
+        let test_1 = "./go -l -60".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let err = parser.parse(test_1.iter()).unwrap_err();
+        
+        assert_eq!(err.position(), None);
+        assert_eq!(err.message(), "Not all required arguments are found");
+        assert!(err.render().contains("\x1b[1m\x1b[31m            ^\x1b[0m"));
+    }
+    
+    #[test]
+    fn test_parser_shared_short_flag() {
+        let parser = setup_1();
+        
+        // `-h` belongs to `height`, which was added after `help`
+        let test_1 = "./go -h 180 -l 3 -n Johnny".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        assert_eq!(p_res.get("height"), Some(180));
+        assert_eq!(p_res.get("help"), Some(false));
+        
+        let test_2 = "./go --help -h 180 -l 3 -n Johnny".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let p_res = parser.parse(test_2.iter()).unwrap();
+        
+        assert_eq!(p_res.get("help"), Some(true));
+    }
+    
+    #[test]
+    fn test_parser_positional_after_flags() {
+        let mut parser = setup_1();
+        
+        parser.add_opt("csv", None, 'c', true, "csv input file",
+            ArgType::Positional(0));
+        
+        let test_1 = "./go in.csv -l -60 -x -h -6001.45e-2 -n Johnny".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        assert_eq!(p_res.get::<String>("csv"), Some("in.csv".into()));
+        assert_eq!(p_res.get("length"), Some(-60));
+    }
+    
+    #[test]
+    fn test_results_share_definitions() {
+        let parser = setup_1();
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let first = parser.parse(test_1.iter()).unwrap();
+        let second = parser.parse(test_1.iter()).unwrap();
+        
+        assert!(Arc::ptr_eq(&first.defs, &parser.defs));
+        assert!(Arc::ptr_eq(&second.defs, &parser.defs));
+        assert_eq!(first.get("mao"), Some(false));
+    }
+    
+    #[test]
+    fn test_parser_borrowed() {
+        let parser = setup_1();
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny -f 1 2 3".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let p_res = parser.parse_borrowed(&test_1).unwrap();
+        
+        assert_eq!(p_res.get("length"), Some(-60));
+        assert_eq!(p_res.get_str("name"), Some("Johnny"));
+        assert_eq!(p_res.get_str("mao"), Some("false"));
+        assert_eq!(p_res.values("frequencies").map(|v| v.to_vec()), Some(vec!["1".into(), "2".into(), "3".into()]));
+        assert_eq!(p_res.get_with("frequencies", vec_parser), Some(vec![1, 2, 3]));
+        
+        // The values point into the original arguments
+        assert_eq!(p_res.get_str("name").unwrap().as_ptr(), test_1[6].as_ptr());
+        
+        let owned = p_res.into_owned();
+        assert_eq!(owned.get::<String>("frequencies"), Some("1 2 3 ".into()));
+    }
+    
+    #[test]
+    #[cfg(unix)]
+    fn test_parse_os() {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+        
+        let mut parser = setup_1();
+        parser.add_opt("file", Some("none"), 'F', false, "File", ArgType::Option);
+        parser.add_opt("more", None, 'M', false, "More", ArgType::List);
+        
+        let args = |raw: &[&[u8]]| {
+            "./go -l 1 -h 2 -n x".split_whitespace()
+                .map(OsString::from)
+                .chain(raw.iter().map(|b| OsString::from_vec(b.to_vec())))
+                .collect::<Vec<_>>()
+        };
+        
+        let p_res = parser.parse_os(args(&[b"-F", b"a\xffb", b"-M", b"\xfe", b"c"])).unwrap();
+        assert_eq!(p_res.get::<String>("file"), Some("a\u{fffd}b".into()));
+        assert_eq!(p_res.get_raw_bytes("file"), Some(b"a\xffb".to_vec()));
+        assert_eq!(p_res.get_raw_bytes("more"), Some(b"\xfe".to_vec()));
+        assert_eq!(p_res.get_raw_bytes("name"), Some(b"x".to_vec()));
+        
+        let p_res = parser.parse_os(args(&[b"--file=\xff"])).unwrap();
+        assert_eq!(p_res.get_raw_bytes("file"), Some(b"\xff".to_vec()));
+        // Both read as `U+FFFD`, but each keeps the bytes it was given
+        let p_res = parser.parse_os(args(&[b"-F", b"\xff", b"-M", b"\xfe"])).unwrap();
+        assert_eq!(p_res.get_raw_bytes("file"), Some(b"\xff".to_vec()));
+        assert_eq!(p_res.get_raw_bytes("more"), Some(b"\xfe".to_vec()));
+        let p_res = parser.parse_os(args(&[b"--file=a\xfe", b"-M", b"\xff\xfe", b"-M", b"\xff"])).unwrap();
+        assert_eq!(p_res.get_raw_bytes("file"), Some(b"a\xfe".to_vec()));
+        assert_eq!(p_res.get_raw_bytes("more"), Some(b"\xff".to_vec()));
+        
+        let p_res = parser.parse_os(args(&[])).unwrap();
+        assert_eq!(p_res.get_raw_bytes("file"), Some(b"none".to_vec()));
+        assert_eq!(p_res.get_raw_bytes("more"), None);
+        assert_eq!(p_res.get_raw_bytes("nope"), None);
+    }
+    
+    #[test]
+    fn test_parser_attached_values() {
+        let mut parser = setup_1();
+        parser.add_opt("socks", None, 's', false, "If you wear socks that day", ArgType::Dict);
+        parser.add_opt("csv", None, 'c', false, "csv input file", ArgType::Positional(0));
+        
+        let test_1 = "./go --length=-60 -h=-6001.45e-2 --name=Johnny --mao=false -f=1 2 -s=a:b -- -c".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+            
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        
+        assert_eq!(p_res.get("length"), Some(-60));
+        assert_eq!(p_res.get("height"), Some(-6001.45e-2));
+        assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
+        assert_eq!(p_res.get("mao"), Some(false));
+        assert_eq!(p_res.get_list("frequencies"), Some(vec![1]));
+        assert_eq!(p_res.get_dict::<String, String>("socks").map(|d| d.len()), Some(1));
+        assert_eq!(p_res.get::<String>("csv"), Some("2".into()));
+    }
+    
+    #[test]
+    fn test_attached_flag_like_values() {
+        let mut parser = setup_1();
+        parser.add_opt("socks", None, 's', false, "Socks", ArgType::Dict);
+        parser.add_opt("out", None, 'o', false, "Out", ArgType::Path);
+        parser.add_opt("terms", None, 't', false, "Terms", ArgType::List);
+        parser.value_terminator("terms", "--x").unwrap();
+        parser.choices("name", &["--x", "-y"]).unwrap();
+        
+        let p_res = parser.parse_line("--length=-5 -h=--2 -mn=-y -f=--help -s=-a:--b -o=--out.txt --ter=--x").unwrap();
+        assert_eq!(p_res.get("length"), Some(-5));
+        assert_eq!(p_res.get::<String>("height"), Some("--2".into()));
+        assert_eq!(p_res.get::<String>("name"), Some("-y".into()));
+        assert_eq!(p_res.get_list::<String>("frequencies"), Some(vec!["--help".into()]));
+        assert_eq!(p_res.pairs("socks"), Some(&[("-a".into(), "--b".into())][..]));
+        assert_eq!(p_res.get::<String>("out"), Some("--out.txt".into()));
+        assert_eq!(p_res.get_list::<String>("terms"), None);
+        assert_eq!(p_res.get::<bool>("help"), Some(false));
+        
+        let opts = ParseOptions { abbreviations: true, permute: false, strict: true, ..ParseOptions::default() };
+        let args = "./go -l 1 -h=-1 --na=--x --ter=--x".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        let p_res = parser.parse_with(args.iter(), opts).unwrap();
+        assert_eq!(p_res.get::<String>("name"), Some("--x".into()));
+        assert_eq!(p_res.get_list::<String>("terms"), Some(vec!["--x".into()]));
+    }
+    
+    #[test]
+    fn test_negative_numbers() {
+        let mut parser = setup_1();
+        parser.add_opt("one", None, '1', false, "One", ArgType::Flag);
+        parser.add_opt("five", None, '5', false, "Five", ArgType::Counter);
+        parser.add_opt("at", None, 'a', false, "At", ArgType::Positional(0));
+        parser.add_opt("rest", None, 'r', false, "Rest", ArgType::PositionalList(0));
+        parser.negative_numbers("length").unwrap();
+        parser.negative_numbers("at").unwrap();
+        assert!(parser.negative_numbers("nope").is_err());
+        
+        let parse = |policy| {
+            let args = "./go -l -5 -h=-1 -n x -f 1 -15 2 -2 -1 -- -55".split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            let opts = ParseOptions { negative_numbers: policy, ..ParseOptions::default() };
+            parser.parse_with(args.iter(), opts).map(|res| {
+                (res.get::<String>("length"), res.get_list::<String>("frequencies"), res.get::<String>("at"),
+                    res.get_list::<String>("rest"), res.get::<bool>("one"), res.get::<u32>("five"))
+            })
+        };
+        
+        assert_eq!(parse(NegativeNumbers::Values).unwrap(), (Some("-5".into()),
+            Some(vec!["1".into(), "-15".into(), "2".into(), "-2".into(), "-1".into()]), Some("-55".into()),
+            None, None, Some(0)));
+        // `-l -5` has no value, as `-5` is a flag
+        assert!(parse(NegativeNumbers::Flags).is_err());
+        // `-15` ends the list as `-1 -5`, `2` is a positional and `-2` an unknown flag
+        assert_eq!(parse(NegativeNumbers::PerArg).unwrap(), (Some("-5".into()),
+            Some(vec!["1".into()]), Some("2".into()), None, Some(true), Some(1)));
+        
+        // Positionals taken by arguments without numbers are flags
+        parser.options(ParseOptions { negative_numbers: NegativeNumbers::PerArg, ..ParseOptions::default() });
+        let p_res = parser.parse_line("-l 1 -h 1 -n x -1 -1").unwrap();
+        assert_eq!(p_res.get::<String>("at"), Some("-1".into()));
+        assert_eq!(p_res.get::<bool>("one"), Some(true));
+    }
+    
+    #[test]
+    fn test_unquote_option() {
+        let mut parser = setup_1();
+        parser.options(ParseOptions { unquote: true, ..ParseOptions::default() });
+        
+        let args: Vec<String> = vec!["./go".into(), "-l".into(), "'1'".into(), "-h=\"2\"".into(),
+            "--name=\"Johnny \\\"J\\\" Smith\"".into(), "-f".into(), "\"C:\\dir\"".into(), "C:\\dir".into()];
+        let p_res = parser.parse(args.iter()).unwrap();
+        assert_eq!(p_res.get("length"), Some(1));
+        assert_eq!(p_res.get("height"), Some(2));
+        assert_eq!(p_res.get::<String>("name"), Some("Johnny \"J\" Smith".into()));
+        assert_eq!(p_res.get_list::<String>("frequencies"), Some(vec!["C:\\dir".into(), "C:\\dir".into()]));
+        
+        let opts = ParseOptions { unquote: false, ..ParseOptions::default() };
+        let p_res = parser.parse_with(args.iter(), opts).unwrap();
+        assert_eq!(p_res.get::<String>("length"), Some("'1'".into()));
+        assert_eq!(parser.parse_borrowed(&args).unwrap().get_str("length"), Some("'1'"));
+        
+        // Quotes were already removed from the line
+        let p_res = parser.parse_line(r#"-l 1 -h 2 -n "'x'""#).unwrap();
+        assert_eq!(p_res.get::<String>("name"), Some("'x'".into()));
+    }
+    
+    #[test]
+    fn test_concatenate() {
+        let mut parser = setup_1();
+        parser.concatenate("name", ",").unwrap();
+        parser.validator("name", |s| if s.contains(',') { Err("no commas".into()) } else { Ok(()) }).unwrap();
+        assert!(parser.concatenate("nope", ",").is_err());
+        
+        let args = "./go -l 1 -h 2 -n a -l 3 --name=b -n c".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        let p_res = parser.parse_borrowed(&args).unwrap();
+        assert_eq!(p_res.get_str("name"), Some("a,b,c"));
+        assert_eq!(p_res.get("length"), Some(3));
+        assert_eq!(p_res.warnings(), &[Warning::Overridden { name: "length".into(), position: 7 }]);
+        
+        assert!(parser.parse_line("-l 1 -h 2 -n a -n b,c").is_err());
+    }
+    
+    #[test]
+    fn test_interpolate() {
+        env::set_var("ARGPARSE_TEST_DIR", "/srv");
+        
+        let mut parser = setup_1();
+        parser.add_opt("cache", Some("${ARGPARSE_TEST_DIR}/cache"), 'c', false, "Cache", ArgType::Path);
+        parser.add_opt("price", Some("$${ARGPARSE_TEST_DIR} $5"), 'p', false, "Price", ArgType::Option);
+        parser.path_options("cache", PathOptions { absolute: true, ..PathOptions::default() }).unwrap();
+        
+        let args = "./go -l 1 -h 2 -n ${ARGPARSE_TEST_DIR}".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        
+        let p_res = parser.parse(args.iter()).unwrap();
+        assert_eq!(p_res.get("cache"), Some(env::current_dir().unwrap().join("${ARGPARSE_TEST_DIR}/cache")));
+        assert_eq!(p_res.get::<String>("name"), Some("${ARGPARSE_TEST_DIR}".into()));
+        
+        let defaults = ParseOptions { interpolate: Interpolate::Defaults, ..ParseOptions::default() };
+        let p_res = parser.parse_with(args.iter(), defaults).unwrap();
+        assert_eq!(p_res.get::<String>("cache"), Some("/srv/cache".into()));
+        assert_eq!(p_res.get::<String>("price"), Some("${ARGPARSE_TEST_DIR} $5".into()));
+        assert_eq!(p_res.get::<String>("name"), Some("${ARGPARSE_TEST_DIR}".into()));
+        
+        // A Flag's default read from the environment is read leniently
+        env::set_var("ARGPARSE_TEST_CACHE", "yes");
+        parser.add_opt("fresh", Some("${ARGPARSE_TEST_CACHE}"), 'F', false, "Fresh", ArgType::Flag);
+        assert_eq!(parser.parse_with(args.iter(), defaults).unwrap().get("fresh"), Some(true));
+        
+        let all = ParseOptions { interpolate: Interpolate::All, ..ParseOptions::default() };
+        let p_res = parser.parse_with(args.iter(), all).unwrap();
+        assert_eq!(p_res.get::<String>("name"), Some("/srv".into()));
+        
+        let args = "./go -l 1 -h 2 -n ${ARGPARSE_TEST_UNSET}".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        let err = parser.parse_with(args.iter(), all).unwrap_err();
+        assert_eq!(err.position(), Some(6));
+        assert!(err.message().ends_with("the environment variable `ARGPARSE_TEST_UNSET` is not set"));
+        
+        parser.add_opt("bad", Some("${oops"), 'b', false, "Bad", ArgType::Option);
+        assert!(parser.parse_with(args.iter(), defaults).is_err());
+    }
+    
+    #[test]
+    fn test_parser_reuse() {
+        let parser = setup_1();
+        
+        for (cmd, name) in &[("./go -l 1 -h 2 -n Johnny", "Johnny"), ("./go -l 3 -h 4 -n Tina", "Tina")] {
+            let args = cmd.split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            
+            let p_res = parser.parse(args.iter()).unwrap();
+            assert_eq!(p_res.get::<String>("name"), Some((*name).into()));
+        }
+        
+        let bad = ["./go".to_string()];
+        assert!(parser.parse(bad.iter()).is_err());
+        
+        let good = "./go -l 1 -h 2 -n Johnny".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        assert!(parser.parse(good.iter()).is_ok());
+    }
+    
+    #[test]
+    fn test_help_text() {
+        let mut parser = setup_1();
+        
+        assert!(!parser.help_text().contains('\x1b'));
+        parser.color(ColorChoice::Always);
+        assert!(parser.help_text().contains('\x1b'));
+        assert!(parser.help_text().contains("--length (-l)"));
+        
+        parser.add_opt("json", None, 'j', false, "json output file", ArgType::Positional(1));
+        parser.add_opt("csv", None, 'c', true, "csv input file", ArgType::Positional(0));
+        parser.add_opt("verbose", None, 'v', false, "Be verbose", ArgType::Counter);
+        parser.color(ColorChoice::Never);
+        parser.max_width(500);
+        assert!(parser.help_text().contains("--length LENGTH --height HEIGHT --name NAME [--frequencies FREQUENCIES...] [--mao] "));
+        assert!(parser.help_text().contains(" [-v...] <CSV> [<JSON>]\n"));
+        
+        let mut out = Vec::new();
+        parser.write_help(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).ok(), Some(parser.help_text()));
+    }
+    
+    #[test]
+    fn test_hashset_parser() {
+        assert_eq!(hashset_parser::<u8>("1 2 1").map(|h| h.len()), Some(2));
+        assert_eq!(hashset_parser::<u8>("1 x"), None);
+        assert_eq!(hashset_parser::<u8>(""), None);
+    }
+    
+    #[test]
+    fn test_tuple_parser() {
+        let size = tuple_parser::<u32, u32>("x");
+        assert_eq!(size("1920x1080"), Some((1920, 1080)));
+        assert_eq!(size("1920"), None);
+        assert_eq!(size("1920xabc"), None);
+        
+        let pair = tuple_parser::<String, String>(":");
+        assert_eq!(pair("a:b:c"), Some(("a".into(), "b:c".into())));
+        
+        let version = triple_parser::<u8, u8, String>(".");
+        assert_eq!(version("1.2.3-rc.1"), Some((1, 2, "3-rc.1".into())));
+        
+        let mut parser = setup_1();
+        parser.add_opt("sizes", None, 's', false, "Window sizes", ArgType::List);
+        let test_1 = "./go -l 1 -h 2 -n x -s 1x2 3x4".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        let p_res = parser.parse(test_1.iter()).unwrap();
+        assert_eq!(p_res.get_list_with("sizes", tuple_parser::<u8, u8>("x")), Some(vec![(1, 2), (3, 4)]));
+    }
+    
+    #[test]
+    fn test_duration_parser() {
+        assert_eq!(duration_parser("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(duration_parser("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(duration_parser("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(duration_parser("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(duration_parser("1.5h"), Some(Duration::from_secs(5400)));
+        assert_eq!(duration_parser("2d1ns"), Some(Duration::new(172_800, 1)));
+        assert_eq!(duration_parser("10µs"), Some(Duration::from_micros(10)));
+        
+        for bad in &["", "30", "s", "5x", "1..5s", "-5s", "1h 30m", "99999999999999999999s", "1e300s"] {
+            assert_eq!(duration_parser(bad), None, "{}", bad);
+        }
+    }
+    
+    #[test]
+    fn test_bytesize_parser() {
+        assert_eq!(bytesize_parser("10MB"), Some(10_000_000));
+        assert_eq!(bytesize_parser("4GiB"), Some(4 << 30));
+        assert_eq!(bytesize_parser("512k"), Some(512_000));
+        assert_eq!(bytesize_parser("512Ki"), Some(512 << 10));
+        assert_eq!(bytesize_parser("1.5kB"), Some(1_500));
+        assert_eq!(bytesize_parser("42"), Some(42));
+        assert_eq!(bytesize_parser("42b"), Some(42));
+        assert_eq!(bytesize_parser("2 TiB"), Some(2 << 40));
+        
+        for bad in &["", "MB", "10XB", "10iB", "-1k", "1..5k", "20EB", "1e3k"] {
+            assert_eq!(bytesize_parser(bad), None, "{}", bad);
+        }
+    }
+    
+    #[test]
+    fn test_network_validators() {
+        assert!(ip_validator("10.0.0.1").is_ok());
+        assert!(ip_validator("::1").is_ok());
+        assert!(ip_validator("10.0.0.256").is_err());
+        
+        assert!(socket_addr_validator("[::1]:80").is_ok());
+        assert_eq!(socket_addr_validator("10.0.0.1:65536"), Err("port out of range".into()));
+        assert_eq!(socket_addr_validator("10.0.0.1"), Err("missing `:port`".into()));
+        assert_eq!(socket_addr_validator("::1:80"), Err("IPv6 addresses need brackets, as in `[::1]:80`".into()));
+        assert_eq!(socket_addr_validator("localhost:80"), Err("`localhost` is not an IP address".into()));
+        assert_eq!(socket_addr_validator(":80"), Err("missing host".into()));
+        assert_eq!(socket_addr_validator("a:http"), Err("port `http` is not a number".into()));
+        
+        assert!(host_port_validator("localhost:80").is_ok());
+        assert!(host_port_validator("db-1.example.com:5432").is_ok());
+        assert!(host_port_validator("-bad-:80").is_err());
+        assert_eq!(host_port_parser("a..b:1"), None);
+        
+        assert!(cidr_validator("fe80::/10").is_ok());
+        assert_eq!(cidr_parser("fe80::/129"), None);
+        assert_eq!(cidr_validator("10.0.0.0"), Err("missing `/prefix`".into()));
+        assert_eq!(cidr_validator("10.0.0.0/33"), Err("prefix length `33` out of range (at most 32)".into()));
+    }
+    
+    #[test]
+    fn test_validator() {
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Never);
+        parser.add_opt("peers", None, 'p', false, "Peers to connect to", ArgType::List);
+        parser.add_opt("bind", Some("nope"), 'b', false, "Address to bind", ArgType::Positional(0));
+        assert!(parser.validator("peers", host_port_validator).is_ok());
+        assert!(parser.validator("bind", ip_validator).is_ok());
+        assert!(parser.validator("nope", ip_validator).is_err());
+        
+        let parse = |cmd: &str| {
+            let args = cmd.split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            parser.parse(args.iter())
+        };
+        
+        let p_res = parse("./go -l 1 -h 2 -n x -p a:1 b:2").unwrap();
+        assert_eq!(p_res.get_list_with("peers", host_port_parser), Some(vec![("a".into(), 1), ("b".into(), 2)]));
+        assert_eq!(p_res.get::<String>("bind"), Some("nope".into()));
+        
+        let err = parse("./go -l 1 -h 2 -n x -p a:1 b:99999").unwrap_err();
+        assert_eq!(err.position(), Some(9));
+        assert_eq!(err.message(), "The value `b:99999` is not valid for `peers`: port out of range");
+        
+        let err = parse("./go -l 1 -h 2 -n x 10.0.0.300").unwrap_err();
+        assert_eq!(err.position(), Some(7));
+    }
+    
+    #[test]
+    #[cfg(feature = "url")]
+    fn test_url_helpers() {
+        use super::{url_parser, url_validator, url_scheme_validator};
+        
+        assert_eq!(url_parser("https://example.com:8443/x").and_then(|u| u.port()), Some(8443));
+        assert!(url_validator("http://[::1]/").is_ok());
+        assert_eq!(url_validator("mailto:someone@example.com"), Err("missing host".into()));
+        assert_eq!(url_validator("example.com"), Err("relative URL without a base".into()));
+        assert_eq!(url_parser("file:///tmp"), None);
+        
+        let web = url_scheme_validator(&["HTTP", "https"]);
+        assert!(web("http://example.com").is_ok());
+        assert!(web("ws://example.com").is_err());
+    }
+    
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn test_date_helpers() {
+        use chrono::{NaiveDate, NaiveTime, Timelike};
+        use super::{parse_date, time_parser, datetime_parser, naive_datetime_parser, datetime_validator};
+        
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d);
+        
+        assert_eq!(parse_date("2024-01-31", today), date(2024, 1, 31));
+        assert_eq!(parse_date("Today", today), Some(today));
+        assert_eq!(parse_date("yesterday", today), date(2024, 2, 29));
+        assert_eq!(parse_date("tomorrow", today), date(2024, 3, 2));
+        assert_eq!(parse_date("-2d", today), date(2024, 2, 28));
+        assert_eq!(parse_date("+1w", today), date(2024, 3, 8));
+        
+        for bad in &["", "-", "d", "-d", "--2d", "-+2d", "2d", "-2m", "2024-02-30", "-99999999999999999w", "+é", "-2é"] {
+            assert_eq!(parse_date(bad, today), None, "{}", bad);
+        }
+        
+        assert_eq!(time_parser("09:30"), NaiveTime::from_hms_opt(9, 30, 0));
+        assert_eq!(time_parser("23:59:59.5").map(|t| t.nanosecond()), Some(500_000_000));
+        assert_eq!(time_parser("24:00"), None);
+        
+        assert_eq!(datetime_parser("2024-01-31T09:30:00+01:00").map(|d| d.offset().local_minus_utc()), Some(3600));
+        assert_eq!(datetime_parser("2024-01-31T09:30:00"), None);
+        assert_eq!(naive_datetime_parser("2024-01-31 09:30").map(|d| d.hour()), Some(9));
+        assert!(datetime_validator("2024-01-31T08:30:00Z").is_ok());
+        assert!(datetime_validator("2024-01-31").is_err());
+    }
+    
+    #[test]
+    fn test_bool_parser() {
+        for yes in &["true", "TRUE", "yes", "Y", "on", "1"] {
+            assert_eq!(bool_parser(yes), Some(true), "{}", yes);
+        }
+        for no in &["false", "No", "n", "OFF", "0"] {
+            assert_eq!(bool_parser(no), Some(false), "{}", no);
+        }
+        for bad in &["", "2", "yess", "nope"] {
+            assert_eq!(bool_parser(bad), None, "{}", bad);
+        }
+        
+        let mut parser = setup_1();
+        let args = "./go -l 1 -h 2 -n x --mao=off".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        assert_eq!(parser.parse(args.iter()).unwrap().get("mao"), Some(false));
+        
+        parser.add_opt("cache", Some("On"), 'c', false, "Cache results", ArgType::Flag);
+        parser.add_opt("color", Some("0"), 'C', false, "Color output", ArgType::Flag);
+        let p_res = parser.parse_line("-l 1 -h 2 -n x").unwrap();
+        assert_eq!(p_res.get("cache"), Some(true));
+        assert_eq!(p_res.get("color"), Some(false));
+        parser.modify_opt("color", |arg| { arg.default(Some("yes")); }).unwrap();
+        assert_eq!(parser.parse_line("-l 1 -h 2 -n x").unwrap().get("color"), Some(true));
+        
+        let args = "./go -l 1 -h 2 -n x --mao=maybe".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        assert_eq!(parser.parse(args.iter()).unwrap_err().position(), Some(7));
+    }
+    
+    #[test]
+    fn test_percentage_parser() {
+        assert_eq!(percentage_parser("75%"), Some(0.75));
+        assert_eq!(percentage_parser("0.75"), Some(0.75));
+        assert_eq!(percentage_parser("100 %"), Some(1.0));
+        assert_eq!(percentage_parser("0"), Some(0.0));
+        
+        assert_eq!(percentage_validator("101%"), Err("must be between 0% and 100%".into()));
+        assert!(percentage_validator("75").is_err());
+        assert!(percentage_validator("-0.1").is_err());
+        
+        for bad in &["", "%", "abc%", "NaN", "inf%", "50%%"] {
+            assert_eq!(percentage_parser(bad), None, "{}", bad);
+        }
+    }
+    
+    #[test]
+    #[cfg(feature = "glob")]
+    fn test_expand_globs() {
+        use std::path::PathBuf;
+        
+        let dir = env::temp_dir().join(format!("argparse-globs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in &["one.rs", "two.rs", "three.txt"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        let within = |pattern: &str| dir.join(pattern).to_string_lossy().into_owned();
+        
+        let mut parser = setup_1();
+        parser.add_opt("inputs", None, 'i', false, "Files to read", ArgType::List);
+        parser.add_opt("raw", None, 'r', false, "Patterns kept as given", ArgType::List);
+        assert!(parser.expand_globs("inputs").is_ok());
+        assert!(parser.expand_globs("nope").is_err());
+        
+        let mut args = "./go -l 1 -h 2 -n x -i Cargo.toml".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        args.extend(vec![within("*.rs"), within("nothing*.here"), "-r".into(), within("*.rs")]);
+        let p_res = parser.parse_borrowed(&args).unwrap();
+        
+        assert_eq!(p_res.get_list::<PathBuf>("inputs"),
+            Some(vec!["Cargo.toml".into(), dir.join("one.rs"), dir.join("two.rs"), dir.join("nothing*.here")]));
+        assert_eq!(p_res.get_list::<String>("raw"), Some(vec![within("*.rs")]));
+        
+        assert_eq!(parser.parse_line("-l 1 -h 2 -n x -i [").unwrap_err().position(), Some(8));
+        
+        // Names that aren't UTF-8 never come out changed: `glob` doesn't
+        // match them, and a match that isn't UTF-8 would fail to parse
+        #[cfg(unix)]
+        {
+            use std::ffi::OsStr;
+            use std::os::unix::ffi::OsStrExt;
+            
+            if std::fs::write(dir.join(OsStr::from_bytes(b"caf\xe9.bin")), "").is_ok() {
+                let args = vec!["./go".into(), "-l".into(), "1".into(), "-h".into(), "2".into(), "-n".into(), "x".into(),
+                    "-i".into(), within("*.bin")];
+                let p_res = parser.parse(args.iter()).unwrap();
+                assert_eq!(p_res.get_list::<String>("inputs"), Some(vec![within("*.bin")]));
+            }
+        }
+        
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_json() {
+        let mut parser = setup_1();
+        parser.add_opt("payload", Some("[1]"), 'p', false, "Body of the request", ArgType::Json);
+        
+        let mut args = "./go -l 1 -h 2 -n x".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        
+        let p_res = parser.parse(args.iter()).unwrap();
+        assert_eq!(p_res.get_json::<Vec<u8>>("payload"), Some(vec![1]));
+        
+        args.push("--payload".into());
+        args.push(r#"{"a": [true, null]}"#.into());
+        let p_res = parser.parse_borrowed(&args).unwrap();
+        assert_eq!(p_res.get_json::<serde_json::Value>("payload").map(|v| v["a"][0].clone()),
+            Some(serde_json::Value::Bool(true)));
+        assert_eq!(p_res.get::<serde_json::Value>("payload").map(|v| v["a"].is_array()), Some(true));
+        assert!(parser.help_text().contains("--payload JSON"));
+        
+        args[8] = "{oops}".into();
+        let err = parser.parse(args.iter()).unwrap_err();
+        assert_eq!(err.position(), Some(8));
+        assert!(err.message().starts_with("The value `{oops}` is not valid for `payload`: key must be a string"));
+    }
+    
+    #[test]
+    #[cfg(not(feature = "json"))]
+    fn test_json_without_feature() {
+        let mut parser = setup_1();
+        parser.add_opt("payload", Some("[1]"), 'p', false, "Body of the request", ArgType::Json);
+        
+        assert!(parser.parse_line("-l 1 -h 2 -n x").is_ok());
+        let err = parser.parse_line("-l 1 -h 2 -n x -p [2]").unwrap_err();
+        assert_eq!(err.position(), Some(8));
+        assert!(err.message().ends_with("reading JSON needs the `json` feature"));
+    }
+    
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_json_schema() {
+        let mut parser = setup_1();
+        parser.value_type::<f64>("height").unwrap();
+        parser.add_opt("format", Some("json"), 'o', false, "Output format", ArgType::Option);
+        parser.choices("format", &["json", "yaml"]).unwrap();
+        parser.add_opt("set", None, 's', false, "Overrides", ArgType::Dict);
+        parser.add_opt("verbose", None, 'v', false, "Verbosity", ArgType::Counter);
+        parser.add_opt("payload", Some("[1]"), 'p', false, "Body of the request", ArgType::Json);
+        parser.deprecated("payload", "use --set").unwrap();
+        
+        let schema = parser.json_schema();
+        let props = &schema["properties"];
+        
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(schema["required"], serde_json::json!(["length", "height", "name"]));
+        assert!(props.get("help").is_none());
+        
+        assert_eq!(props["height"], serde_json::json!({"type": "number", "description": "Height of user in centimeters"}));
+        assert_eq!(props["mao"]["type"], "boolean");
+        assert_eq!(props["mao"]["default"], false);
+        assert_eq!(props["frequencies"]["type"], "array");
+        assert_eq!(props["frequencies"]["items"]["type"], "string");
+        assert_eq!(props["format"]["enum"], serde_json::json!(["json", "yaml"]));
+        assert_eq!(props["format"]["default"], "json");
+        assert_eq!(props["set"]["additionalProperties"]["type"], "string");
+        assert_eq!(props["verbose"]["type"], "integer");
+        assert_eq!(props["payload"]["default"], serde_json::json!([1]));
+        assert_eq!(props["payload"]["deprecated"], true);
+        assert!(props["payload"].get("type").is_none());
+    }
+    
+    #[test]
+    #[cfg(all(feature = "json", feature = "url"))]
+    fn test_json_schema_tagged() {
+        let mut parser = ArgParser::new("fetch".into());
+        parser.add_opt("url", None, 'u', false, "Where to fetch from", ArgType::Option);
+        parser.value_type::<url::Url>("url").unwrap();
+        parser.add_opt("retries", None, 'r', false, "Retries", ArgType::Option);
+        parser.value_type::<u16>("retries").unwrap();
+        parser.add_opt("scale", None, 's', false, "Scale", ArgType::Option);
+        parser.value_type::<i128>("scale").unwrap();
+        
+        let schema = parser.json_schema();
+        let props = &schema["properties"];
+        
+        assert_eq!(props["url"]["type"], "string");
+        assert_eq!(props["retries"]["type"], "integer");
+        assert_eq!(props["scale"]["type"], "integer");
+    }
+    
+    #[test]
+    #[cfg(feature = "clap")]
+    fn test_to_clap() {
+        let mut parser = setup_1();
+        parser.add_opt("format", Some("json"), 'o', false, "Output format", ArgType::Option);
+        parser.choices("format", &["json", "yaml"]).unwrap();
+        parser.ignore_case("format").unwrap();
+        parser.add_opt("verbose", None, 'v', false, "Verbosity", ArgType::Counter);
+        parser.add_opt("input", None, 'i', false, "File to read", ArgType::Positional(0));
+        parser.require_at_least(1, &["verbose", "input"]).unwrap();
+        
+        let command = parser.to_clap();
+        command.clone().debug_assert();
+        
+        let args = "./go -l -60 -h -60.5 -n Johnny -m -f 1 2 3 -o YAML -vv in.txt".split_whitespace();
+        let matches = command.clone().try_get_matches_from(args).unwrap();
+        let one = |name| matches.get_one::<String>(name).map(|s| s.as_str());
+        
+        assert_eq!(one("length"), Some("-60"));
+        assert_eq!(one("height"), Some("-60.5"));
+        assert_eq!(one("name"), Some("Johnny"));
+        assert_eq!(one("format"), Some("YAML"));
+        assert_eq!(one("input"), Some("in.txt"));
+        assert!(matches.get_flag("mao"));
+        assert_eq!(matches.get_count("verbose"), 2);
+        assert_eq!(matches.get_many::<String>("frequencies").map(|v| v.count()), Some(3));
+        
+        let missing = "./go -l 1 -h 2 -n x".split_whitespace();
+        assert!(command.clone().try_get_matches_from(missing).is_err());
+        let bad = "./go -l 1 -h 2 -n x -v -o xml".split_whitespace();
+        assert!(command.try_get_matches_from(bad).is_err());
+    }
+    
+    #[test]
+    fn test_nested_dict() {
+        use std::collections::BTreeMap;
+        use super::DictNode;
+        
+        let mut parser = setup_1();
+        parser.add_opt("set", Some("a.b:1"), 's', false, "Overrides", ArgType::Dict);
+        
+        let parse = |cmd: &str| {
+            let args = cmd.split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            parser.parse(args.iter()).unwrap().get_nested("set")
+        };
+        
+        let leaf = |s: &str| DictNode::Leaf(s.into());
+        let branch = |entries: Vec<(&str, DictNode)>| {
+            DictNode::Branch(entries.into_iter().map(|(k, v)| (k.to_string(), v)).collect::<BTreeMap<_, _>>())
+        };
+        
+        assert_eq!(parse("./go -l 1 -h 2 -n x"), Some(branch(vec![("a", branch(vec![("b", leaf("1"))]))])));
+        
+        let set = parse("./go -l 1 -h 2 -n x -s s.port:80 s.tls:true s.port:8080 top:x:y").unwrap();
+        assert_eq!(set, branch(vec![
+            ("s", branch(vec![("port", leaf("8080")), ("tls", leaf("true"))])),
+            ("top", leaf("x:y")),
+        ]));
+        assert_eq!(set.get("s.tls"), Some(&leaf("true")));
+        assert_eq!(set.get("s.tls.x"), None);
+        assert_eq!(set.get("s").and_then(DictNode::as_str), None);
+        
+        // Later pairs win, whether they are values or prefixes
+        assert_eq!(parse("./go -l 1 -h 2 -n x -s a:1 a.b:2").unwrap().get("a.b"), Some(&leaf("2")));
+        assert_eq!(parse("./go -l 1 -h 2 -n x -s a.b:2 a:1").unwrap().get("a"), Some(&leaf("1")));
+        
+        assert_eq!(parse("./go -l 1 -h 2 -n x -s a..b:1"), None);
+        assert_eq!(parse("./go -l 1 -h 2 -n x -s .a:1"), None);
+    }
+    
+    #[test]
+    fn test_dict_merging() {
+        use super::DuplicateKeys;
+        
+        let mut parser = setup_1();
+        parser.add_opt("define", None, 'D', false, "Macros to define", ArgType::Dict);
+        
+        let args = "./go -l 1 -h 2 -n x -D a:1 b:2 -D a:3 c:4 a:5".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        let pairs = |parser: &ArgParser| {
+            parser.parse(args.iter()).map(|p_res| {
+                p_res.pairs("define").unwrap().iter()
+                    .map(|(k, v)| format!("{}:{}", k, v))
+                    .collect::<Vec<_>>()
+            })
+        };
+        
+        assert_eq!(pairs(&parser).unwrap(), vec!["a:5", "b:2", "c:4"]);
+        
+        parser.duplicate_keys("define", DuplicateKeys::FirstWins).unwrap();
+        assert_eq!(pairs(&parser).unwrap(), vec!["a:1", "b:2", "c:4"]);
+        let p_res = parser.parse(args.iter()).unwrap();
+        assert_eq!(p_res.get_with("define", hashmap_parser::<String, u8>).map(|h| h["a"]), Some(1));
+        
+        parser.duplicate_keys("define", DuplicateKeys::Error).unwrap();
+        let err = pairs(&parser).unwrap_err();
+        assert_eq!(err.position(), Some(11));
+        assert_eq!(err.message(), "The key `a` was given more than once for `define`");
+        
+        assert!(parser.duplicate_keys("nope", DuplicateKeys::Error).is_err());
+    }
+    
+    #[test]
+    fn test_multimap() {
+        use super::{DuplicateKeys, multimap_parser};
+        
+        let mut parser = setup_1();
+        parser.add_opt("label", Some("env:dev"), 'L', false, "Labels to select by", ArgType::Dict);
+        
+        let labels = |p_res: &ArgParseResults| {
+            let mut labels = p_res.get_multimap::<String, String>("label").unwrap().into_iter().collect::<Vec<_>>();
+            labels.sort();
+            labels
+        };
+        let strs = |vals: &[&str]| vals.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        
+        let p_res = parser.parse_line("-l 1 -h 2 -n x").unwrap();
+        assert_eq!(labels(&p_res), vec![("env".into(), strs(&["dev"]))]);
+        
+        let p_res = parser.parse_line("-l 1 -h 2 -n x -L env:prod,staging tier:web -L env:qa").unwrap();
+        assert_eq!(labels(&p_res), vec![("env".into(), strs(&["qa"])), ("tier".into(), strs(&["web"]))]);
+        
+        parser.duplicate_keys("label", DuplicateKeys::Keep).unwrap();
+        let p_res = parser.parse_line("-l 1 -h 2 -n x -L env:prod,staging tier:web -L env:qa tier:").unwrap();
+        assert_eq!(labels(&p_res), vec![
+            ("env".into(), strs(&["prod", "staging", "qa"])),
+            ("tier".into(), strs(&["web"])),
+        ]);
+        assert_eq!(p_res.pairs("label").map(|p| p.len()), Some(4));
+        
+        let ports: HashMap<String, Vec<u16>> = multimap_parser("web:80,443 db:5432 web:8080").unwrap();
+        assert_eq!(ports["web"], [80, 443, 8080]);
+        assert_eq!(multimap_parser::<String, u16>("web:http"), None);
+        assert_eq!(multimap_parser::<String, u16>("web"), None);
+        assert_eq!(multimap_parser::<String, u16>(""), None);
+    }
+    
+    #[test]
+    fn test_ordered_dict() {
+        use super::{DuplicateKeys, ordered_dict_parser};
+        
+        let mut parser = setup_1();
+        parser.add_opt("header", Some("Accept:*"), 'H', false, "Headers to send", ArgType::Dict);
+        
+        let headers = |p_res: ArgParseResults| p_res.get_ordered_dict::<String, String>("header");
+        let pairs = |pairs: &[(&str, &str)]| {
+            Some(pairs.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect::<Vec<_>>())
+        };
+        
+        assert_eq!(headers(parser.parse_line("-l 1 -h 2 -n x").unwrap()), pairs(&[("Accept", "*")]));
+        assert_eq!(headers(parser.parse_line("-l 1 -h 2 -n x -H z:1 -H y:2 x:3 z:4").unwrap()),
+            pairs(&[("z", "4"), ("y", "2"), ("x", "3")]));
+        
+        parser.duplicate_keys("header", DuplicateKeys::Keep).unwrap();
+        assert_eq!(headers(parser.parse_line("-l 1 -h 2 -n x -H z:1 -H y:2 x:3 z:4").unwrap()),
+            pairs(&[("z", "1"), ("y", "2"), ("x", "3"), ("z", "4")]));
+        
+        assert_eq!(ordered_dict_parser::<u8, bool>("3:true 1:false"), Some(vec![(3, true), (1, false)]));
+        assert_eq!(ordered_dict_parser::<u8, bool>("3:maybe"), None);
+    }
+    
+    #[test]
+    fn test_positional_lists() {
+        let mut parser = setup_1();
+        parser.add_opt("inputs", None, 'i', true, "Files to read", ArgType::PositionalList(0));
+        parser.add_opt("outputs", Some("out.txt"), 'o', false, "Files to write", ArgType::PositionalList(1));
+        
+        let parse = |parser: &ArgParser, cmd: &str| {
+            let args = cmd.split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            parser.parse(args.iter())
+        };
+        
+        let p_res = parse(&parser, "./go a -l 1 b -h 2 -n x c -- d -e").unwrap();
+        assert_eq!(p_res.get_list::<String>("inputs"), Some(vec!["a".into(), "b".into(), "c".into()]));
+        assert_eq!(p_res.get_list::<String>("outputs"), Some(vec!["d".into(), "-e".into()]));
+        assert_eq!(p_res.get::<String>("inputs"), Some("a b c ".into()));
+        
+        let p_res = parse(&parser, "./go a -l 1 -h 2 -n x").unwrap();
+        assert_eq!(p_res.get_list::<String>("outputs"), Some(vec!["out.txt".into()]));
+        
+        assert!(parse(&parser, "./go -l 1 -h 2 -n x -- a").is_err());
+        
+        // A `Positional` takes its value out of the group
+        parser.add_opt("mode", None, 'm', false, "What to do", ArgType::Positional(0));
+        let p_res = parse(&parser, "./go copy a b -l 1 -h 2 -n x").unwrap();
+        assert_eq!(p_res.get::<String>("mode"), Some("copy".into()));
+        assert_eq!(p_res.get_list::<String>("inputs"), Some(vec!["a".into(), "b".into()]));
+    }
+    
+    #[test]
+    fn test_path_options() {
+        let mut parser = ArgParser::new("concat".into());
+        parser.add_opt("input", None, 'i', false, "File to read", ArgType::Path);
+        parser.add_opt("output", Some("out.txt"), 'o', false, "File to write", ArgType::Path);
+        parser.add_opt("config", Some("~/.concat"), 'c', false, "Configuration", ArgType::Path);
+        
+        let parse = |parser: &ArgParser, cmd: &str| {
+            let args = cmd.split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            parser.parse(args.iter())
+        };
+        
+        let p_res = parse(&parser, "./concat -i ~/a.txt").unwrap();
+        assert_eq!(p_res.get("input"), Some(PathBuf::from("~/a.txt")));
+        assert_eq!(p_res.get("output"), Some(PathBuf::from("out.txt")));
+        
+        let tilde = PathOptions { expand_tilde: true, ..PathOptions::default() };
+        parser.path_options("input", tilde).unwrap();
+        parser.path_options("config", tilde).unwrap();
+        parser.path_options("output", PathOptions { absolute: true, ..PathOptions::default() }).unwrap();
+        assert!(parser.path_options("nope", tilde).is_err());
+        
+        let home = super::home_dir().unwrap();
+        let cwd = env::current_dir().unwrap();
+        let p_res = parse(&parser, "./concat -i ~/a.txt").unwrap();
+        assert_eq!(p_res.get("input"), Some(home.join("a.txt")));
+        assert_eq!(p_res.get("config"), Some(home.join(".concat")));
+        assert_eq!(p_res.get("output"), Some(cwd.join("out.txt")));
+        
+        let p_res = parse(&parser, "./concat --input=~ -o /tmp/b.txt").unwrap();
+        assert_eq!(p_res.get("input"), Some(home.clone()));
+        assert_eq!(p_res.get("output"), Some(PathBuf::from("/tmp/b.txt")));
+        
+        let err = parse(&parser, "./concat -i ~root/a.txt").unwrap_err();
+        assert!(err.message().contains("`~root` can't be expanded"));
+        assert_eq!(err.position(), Some(2));
+        let err = parse(&parser, "./concat -i a.txt -c=~x").unwrap_err();
+        assert_eq!(err.position(), Some(3));
+        
+        parser.path_options("input", PathOptions { canonicalize: true, ..PathOptions::default() }).unwrap();
+        let p_res = parse(&parser, "./concat -i ./src/../src/lib.rs").unwrap();
+        assert_eq!(p_res.get("input"), Some(cwd.join("src/lib.rs").canonicalize().unwrap()));
+        
+        let err = parse(&parser, "./concat -i ./no/such/file").unwrap_err();
+        assert_eq!(err.position(), Some(2));
+    }
+    
+    #[test]
+    fn test_counter() {
+        let mut parser = setup_1();
+        parser.add_opt("verbose", None, 'v', false, "Be verbose", ArgType::Counter);
+        parser.add_opt("quiet", Some("1"), 'q', false, "Be quiet", ArgType::Counter);
+        parser.color(ColorChoice::Never);
+        
+        let parse = |parser: &ArgParser, cmd: &str| {
+            let args = cmd.split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            parser.parse(args.iter())
+        };
+        
+        let p_res = parse(&parser, "./go -vvv -l 1 -h 2 -n x --verbose").unwrap();
+        assert_eq!(p_res.get::<u32>("verbose"), Some(4));
+        assert_eq!(p_res.get::<u32>("quiet"), Some(1));
+        
+        let p_res = parse(&parser, "./go -l 1 -h 2 -n x -q").unwrap();
+        assert_eq!(p_res.get::<u32>("verbose"), Some(0));
+        assert_eq!(p_res.get::<u32>("quiet"), Some(1));
+        
+        let err = parse(&parser, "./go -l 1 -h 2 -n x --verbose=2").unwrap_err();
+        assert_eq!(err.position(), Some(7));
+        
+        assert!(parser.help_text().contains("[-v...] [-q...]"));
+    }
+    
+    #[test]
+    fn test_command() {
+        let mut parser = setup_1();
+        parser.add_opt("exec", None, 'e', false, "Command to run", ArgType::Command);
+        
+        let parse = |parser: &ArgParser, cmd: &str| {
+            let args = cmd.split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            parser.parse(args.iter())
+        };
+        let argv = |p_res: &ArgParseResults| p_res.get_list::<String>("exec");
+        
+        let p_res = parse(&parser, "./go -l 1 -h 2 -n x -- ls -l -- -a").unwrap();
+        assert_eq!(argv(&p_res), Some(vec!["ls".into(), "-l".into(), "--".into(), "-a".into()]));
+        assert_eq!(p_res.get::<u32>("length"), Some(1));
+        
+        let p_res = parse(&parser, "./go -l 1 -h 2 -n x ls -n 5").unwrap();
+        assert_eq!(argv(&p_res), Some(vec!["ls".into(), "-n".into(), "5".into()]));
+        assert_eq!(p_res.get::<String>("name"), Some("x".into()));
+        
+        let p_res = parse(&parser, "./go -l 1 -h 2 -n x --exec=ls -l").unwrap();
+        assert_eq!(argv(&p_res), Some(vec!["ls".into(), "-l".into()]));
+        
+        let p_res = parse(&parser, "./go -l 1 -h 2 -n x --").unwrap();
+        assert_eq!(argv(&p_res), None);
+        assert!(p_res.get_command("exec").is_none());
+        
+        assert!(parse(&parser, "./go -l 1 -h 2 -n x -e").is_err());
+        
+        // Positionals are filled before the command starts
+        parser.add_opt("dir", None, 'd', false, "Where to run", ArgType::Positional(0));
+        let p_res = parse(&parser, "./go /tmp -l 1 -h 2 -n x make all").unwrap();
+        assert_eq!(p_res.get::<String>("dir"), Some("/tmp".into()));
+        
+        let cmd = p_res.get_command("exec").unwrap();
+        assert_eq!(cmd.get_program(), "make");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["all"]);
+    }
+    
+    #[test]
+    fn test_remainder() {
+        let mut parser = setup_1();
+        parser.add_opt("host", None, 'H', false, "Host to connect to", ArgType::Positional(0));
+        parser.add_opt("rest", None, 'r', false, "Passed to the remote", ArgType::Remainder);
+        
+        let parse = |parser: &ArgParser, cmd: &str| {
+            let args = cmd.split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            parser.parse(args.iter())
+        };
+        
+        let p_res = parse(&parser, "./go -l 1 -h 2 box -n x top -d 1").unwrap();
+        assert_eq!(p_res.get::<String>("host"), Some("box".into()));
+        assert_eq!(p_res.get::<String>("name"), Some("x".into()));
+        assert_eq!(p_res.values("rest"), Some(&["top".to_string(), "-d".into(), "1".into()][..]));
+        
+        let p_res = parse(&parser, "./go -l 1 -h 2 -n x --unknown -l 5 box").unwrap();
+        assert_eq!(p_res.get::<u32>("length"), Some(1));
+        assert_eq!(p_res.get::<String>("host"), None);
+        assert_eq!(p_res.get_list::<String>("rest"), Some(vec!["--unknown".into(), "-l".into(), "5".into(), "box".into()]));
+        
+        let p_res = parse(&parser, "./go box -l 1 -h 2 -n x").unwrap();
+        assert_eq!(p_res.values("rest"), None);
+        
+        let p_res = parse(&parser, "./go box -l 1 -h 2 -n x -r -n y").unwrap();
+        assert_eq!(p_res.get_list::<String>("rest"), Some(vec!["-n".into(), "y".into()]));
+        assert_eq!(p_res.get::<String>("name"), Some("x".into()));
+    }
+    
+    #[test]
+    fn test_results_iter() {
+        let parser = setup_1();
+        let args = "./go -l 1 -h 2 -n x -f 1 2 -f 3 -m".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        let p_res = parser.parse(args.iter()).unwrap();
+        
+        let all: Vec<_> = p_res.iter().map(|(n, v, c)| (n, v.map(|v| v.into_owned()), c)).collect();
+        assert_eq!(all, vec![
+            ("help", Some("false".into()), 0),
+            ("length", Some("1".into()), 1),
+            ("height", Some("2".into()), 1),
+            ("name", Some("x".into()), 1),
+            ("frequencies", Some("3 ".into()), 2),
+            ("mao", Some("true".into()), 1),
+        ]);
+        
+        let mut iter = (&p_res).into_iter();
+        assert_eq!(iter.len(), 6);
+        iter.next();
+        assert_eq!(iter.len(), 5);
+        assert_eq!((&p_res).into_iter().count(), 6);
+        
+        let map = p_res.to_map();
+        assert_eq!(map.len(), 6);
+        assert_eq!(map["length"], Some("1".into()));
+        assert_eq!(map["frequencies"], Some("3 ".into()));
+    }
+    
+    #[test]
+    fn test_diff() {
+        let parse = |parser: &ArgParser, cmd: &str| {
+            let args = cmd.split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            parser.parse(args.iter()).unwrap()
+        };
+        
+        let parser = setup_1();
+        let a = parse(&parser, "./go -l 1 -h 2 -n x -f 1 2");
+        let b = parse(&parser, "./go -n x -h 2 -l 1 -f 1 2 -m=false");
+        let c = parse(&parser, "./go -l 1 -h 3 -n x -m");
+        
+        assert!(a.diff(&b).is_empty());
+        assert_eq!(a.diff(&c), vec![
+            ArgDiff { name: "height".into(), before: Some("2".into()), after: Some("3".into()) },
+            ArgDiff { name: "frequencies".into(), before: Some("1 2 ".into()), after: None },
+            ArgDiff { name: "mao".into(), before: Some("false".into()), after: Some("true".into()) },
+        ]);
+        
+        let mut other = setup_1();
+        other.remove_opt("frequencies").unwrap();
+        other.add_opt("extra", None, 'e', false, "Extra", ArgType::Option);
+        other.add_opt("color", Some("red"), 'c', false, "Color", ArgType::Option);
+        let d = parse(&other, "./go -l 1 -h 2 -n x");
+        
+        assert_eq!(a.diff(&d), vec![
+            ArgDiff { name: "color".into(), before: None, after: Some("red".into()) },
+            ArgDiff { name: "frequencies".into(), before: Some("1 2 ".into()), after: None },
+        ]);
+    }
+    
+    #[test]
+    fn test_parse_line() {
+        let parser = setup_1();
+        
+        let p_res = assert_parse_ok!(parser, "-l 1 -h 2 -n 'Mao Zedong' -m");
+        assert_eq!(p_res.get::<String>("name"), Some("Mao Zedong".into()));
+        assert_eq!(p_res.get("mao"), Some(true));
+        
+        assert_parse_err!(parser, "");
+        assert_parse_err!(parser, "-l 1 -h 2", "required");
+        
+        let err = parser.parse_line("-l 1 -h 2 -n").unwrap_err();
+        assert_eq!(err.position(), Some(5));
+    }
+    
+    #[test]
+    fn test_parse_lines() {
+        let parser = setup_1();
+        let input = io::Cursor::new("-l 1 -h 2 -n x -m -f 1 2\n  \n-l 3 -h 4 -n 'y z'\r\n-l\n-l 5 -h 6 -n w");
+        
+        let results: Vec<ParseResult> = parser.parse_lines(input).map(|r| r.unwrap()).collect();
+        assert_eq!(results.len(), 4);
+        
+        let first = results[0].as_ref().unwrap();
+        assert_eq!(first.get("mao"), Some(true));
+        assert_eq!(first.get_list("frequencies"), Some(vec![1, 2]));
+        
+        // Nothing from the first line leaks into the second
+        let second = results[1].as_ref().unwrap();
+        assert_eq!(second.get::<String>("name"), Some("y z".into()));
+        assert_eq!(second.get("mao"), Some(false));
+        assert_eq!(second.get_list::<u32>("frequencies"), None);
+        
+        assert!(results[2].is_err());
+        assert_eq!(results[3].as_ref().unwrap().get::<u32>("height"), Some(6));
+    }
+    
+    #[test]
+    fn test_parse_iter() {
+        use super::ParseEvent;
+        
+        let mut parser = setup_1();
+        parser.add_opt("format", Some("json"), 'F', false, "Format", ArgType::Option);
+        parser.add_opt("formats", None, 'S', false, "Formats", ArgType::List);
+        parser.choices("format", &["json", "yaml"]).unwrap();
+        parser.list_values("formats", ListValues::AtMost(2)).unwrap();
+        
+        let events = |parser: &ArgParser, cmd: &str| {
+            let args = cmd.split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            parser.parse_iter(&args).map(|e| match e {
+                ParseEvent::ArgMatched { name, values, position } => format!("{}{:?}@{}", name, values, position),
+                ParseEvent::PositionalFound { value, position } => format!("{}@{}", value, position),
+                ParseEvent::Unknown { flag, position } => format!("?{}@{}", flag, position),
+                ParseEvent::Error(e) => format!("!{}@{:?}", e.message(), e.position()),
+            }).collect::<Vec<_>>()
+        };
+        
+        assert_eq!(events(&parser, "./go -vm -l -60 --formats=a b c -- -x"), vec![
+            "?-v@1", "mao[]@1", "length[\"-60\"]@2", "formats[\"a\"]@4", "b@5", "c@6", "-x@8",
+        ]);
+        assert_eq!(events(&parser, "./go -S a b c"), vec!["formats[\"a\", \"b\"]@1", "c@4"]);
+        assert_eq!(events(&parser, "./go --format yaml -F xml -m"), vec![
+            "format[\"yaml\"]@1",
+            "!The value `xml` is not valid for `format`: expected one of `json`, `yaml`@Some(4)",
+        ]);
+        assert_eq!(events(&parser, "./go in.txt -l"), vec!["in.txt@1", "!This option `length` requires a value you have not provided@Some(2)"]);
+        assert!(events(&parser, "./go").is_empty());
+        
+        parser.options(ParseOptions { abbreviations: true, ..ParseOptions::default() });
+        assert_eq!(events(&parser, "./go --form json")[0], "!The flag `--form` is ambiguous, it could be `--format`, `--formats`@Some(1)");
+        assert_eq!(events(&parser, "./go --fre 1 2"), vec!["frequencies[\"1\", \"2\"]@1"]);
+        
+        // The same rules as `parse` for commands, negative numbers and order
+        parser.add_opt("exec", None, 'e', false, "Command to run", ArgType::Command);
+        parser.negative_numbers("length").unwrap();
+        parser.options(ParseOptions { negative_numbers: NegativeNumbers::PerArg, ..ParseOptions::default() });
+        assert_eq!(events(&parser, "./go -e ls -l"), vec!["exec[\"ls\", \"-l\"]@1"]);
+        assert_eq!(events(&parser, "./go in.txt ls -l"), vec!["exec[\"in.txt\", \"ls\", \"-l\"]@1"]);
+        assert_eq!(events(&parser, "./go -l -5 -F -m"), vec![
+            "length[\"-5\"]@1",
+            "!This option `format` requires a value you have not provided@Some(3)",
+        ]);
+        assert_eq!(events(&parser, "./go -S -5"), vec!["formats[]@1", "?-5@2"]);
+        
+        parser.options(ParseOptions { permute: false, ..ParseOptions::default() });
+        parser.remove_opt("exec").unwrap();
+        assert_eq!(events(&parser, "./go -m a -l 1"), vec!["mao[]@1", "a@2", "-l@3", "1@4"]);
+        
+        let mut commands = ArgParser::new("go".into());
+        commands.add_opt("rest", None, 'r', false, "The rest", ArgType::Remainder);
+        assert_eq!(events(&commands, "./go -h --what -x"), vec!["help[]@1", "rest[\"--what\", \"-x\"]@2"]);
+    }
+    
+    #[test]
+    fn test_validate() {
+        let mut parser = ArgParser::new("go".into());
+        parser.add_opt("length", None, 'l', true, "Length", ArgType::Option);
+        parser.add_opt("csv", None, 'c', false, "csv input file", ArgType::Positional(0));
+        // Arguments without a short flag don't share one
+        parser.add_opt("dry_run", None, '\0', false, "Dry run", ArgType::Flag);
+        parser.add_opt("quiet", None, '\0', false, "Quiet", ArgType::Flag);
+        assert_eq!(parser.validate(), Ok(()));
+        
+        parser.add_opt("json", None, 'j', false, "json output file", ArgType::Positional(2));
+        parser.add_opt("yaml", None, 'y', false, "yaml output file", ArgType::Positional(2));
+        parser.add_opt("level", Some("1"), 'l', true, "Level", ArgType::Option);
+        
+        let errors = parser.validate().unwrap_err();
+        assert_eq!(errors, vec![
+            ConfigError::DuplicateShort { flag: 'l', names: ("length".into(), "level".into()) },
+            ConfigError::DuplicatePosition { index: 2, names: ("json".into(), "yaml".into()) },
+            ConfigError::RequiredWithDefault { name: "level".into() },
+            ConfigError::PositionalGap { index: 1 },
+        ]);
+        assert_eq!(errors[3].to_string(), "there is no positional argument 1, though there are later ones");
+    }
+    
+    #[test]
+    fn test_value_type() {
+        let mut parser = setup_1();
+        parser.value_type::<u32>("length").unwrap();
+        parser.value_type::<f64>("frequencies").unwrap();
+        assert!(parser.value_type::<u32>("nope").is_err());
+        
+        let args = "./go -l 1 -h 2 -n x -f 1 2.5".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        let p_res = parser.parse(args.iter()).unwrap();
+        
+        assert_eq!(p_res.try_get::<u32>("length"), Ok(1));
+        assert_eq!(p_res.get::<i64>("length"), None);
+        assert_eq!(p_res.try_get::<i64>("length").unwrap_err().to_string(),
+            "`length` holds a `u32`, but was read as a `i64`");
+        assert_eq!(p_res.get::<i64>("height"), Some(2));
+        assert_eq!(p_res.get_list::<f64>("frequencies"), Some(vec![1.0, 2.5]));
+        assert_eq!(p_res.get_list::<f32>("frequencies"), None);
+        
+        let borrowed = parser.parse_borrowed(&args).unwrap();
+        assert_eq!(borrowed.try_get::<u32>("length"), Ok(1));
+        assert!(borrowed.try_get::<u8>("length").is_err());
+        assert_eq!(borrowed.get_list::<f64>("frequencies"), Some(vec![1.0, 2.5]));
+        
+        let args = "./go -l 1 -h 2 -n x".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        let p_res = parser.parse(args.iter()).unwrap();
+        assert_eq!(p_res.try_get::<f64>("frequencies"), Err(GetError::Missing("frequencies".into())));
+        assert_eq!(p_res.try_get::<u8>("name"), Err(GetError::Invalid {
+            name: "name".into(),
+            value: "x".into(),
+            requested: "u8",
+        }));
+    }
+    
+    #[test]
+    fn test_parse_with() {
+        let mut parser = setup_1();
+        parser.add_opt("file", None, 'F', false, "File", ArgType::Positional(0));
+        parser.add_opt("mode", None, 'M', false, "Mode", ArgType::Option);
+        parser.add_opt("modern", None, 'N', false, "Modern", ArgType::Flag);
+        parser.color(ColorChoice::Always);
+        
+        let args = "./go --LEN 1 -H 2 --nam x in.txt -m --mdoe y".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        
+        let default = ParseOptions::default();
+        assert!(parser.parse_with(args.iter(), default).is_err());
+        
+        let loose = ParseOptions { abbreviations: true, case_sensitive: false, ..default };
+        let p_res = parser.parse_with(args.iter(), loose).unwrap();
+        assert_eq!(p_res.get("length"), Some(1));
+        // `-H` is `-h` in another case
+        assert_eq!(p_res.get("height"), Some(2));
+        assert_eq!(p_res.get::<String>("name"), Some("x".into()));
+        assert_eq!(p_res.get::<String>("file"), Some("in.txt".into()));
+        assert_eq!(p_res.get("mao"), Some(true));
+        assert_eq!(p_res.get::<String>("mode"), None);
+        assert_eq!(p_res.warnings(), &[Warning::UnknownFlag { flag: "--mdoe".into(), position: 9 }]);
+        
+        let strict = ParseOptions { strict: true, color: Some(ColorChoice::Never), ..loose };
+        let err = parser.parse_with(args.iter(), strict).unwrap_err();
+        assert_eq!(err.message(), "Unknown flag `--mdoe`");
+        assert_eq!(err.position(), Some(9));
+        assert!(!err.render().contains('\x1b'));
+        
+        // `--mod` could be `--mode` or `--modern`, which fails even when
+        // unknown flags don't
+        let ambiguous = "./go -l 1 -h 2 -n x --mod y".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        let err = parser.parse_with(ambiguous.iter(), loose).unwrap_err();
+        assert_eq!(err.message(), "The flag `--mod` is ambiguous, it could be `--mode`, `--modern`");
+        assert_eq!(err.position(), Some(7));
+        assert!(parser.parse_with(ambiguous.iter(), default).is_ok());
+        
+        let posix = ParseOptions { permute: false, ..loose };
+        let p_res = parser.parse_with(args.iter(), posix).unwrap();
+        assert_eq!(p_res.get::<bool>("mao"), Some(false));
+        assert!(p_res.warnings().is_empty());
+        
+        let args = "./go -l 1 -h 2 -n x in.txt -mm".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        parser.add_opt("rest", None, 'R', false, "Rest", ArgType::PositionalList(0));
+        let p_res = parser.parse_with(args.iter(), posix).unwrap();
+        assert_eq!(p_res.get_list::<String>("rest"), Some(vec!["-mm".into()]));
+    }
+    
+    #[test]
+    fn test_kebab_case() {
+        let mut parser = setup_1();
+        parser.add_opt("max_retries", Some("3"), 'r', false, "Retries", ArgType::Option);
+        parser.add_opt("dry_run", Some("false"), 'd', false, "Dry run", ArgType::Flag);
+        
+        let parse = |parser: &ArgParser, cmd: &str, opts: ParseOptions| {
+            let args = cmd.split_whitespace()
+                .map(|s| s.into())
+                .collect::<Vec<String>>();
+            parser.parse_with(args.iter(), opts).ok().and_then(|p_res| p_res.get::<u8>("max_retries"))
+        };
+        let with = |kebab_case| ParseOptions { kebab_case, strict: true, ..ParseOptions::default() };
+        
+        assert_eq!(parse(&parser, "./go -l 1 -h 2 -n x --max_retries 5", with(KebabCase::Off)), Some(5));
+        assert_eq!(parse(&parser, "./go -l 1 -h 2 -n x --max-retries 5", with(KebabCase::Off)), None);
+        assert_eq!(parse(&parser, "./go -l 1 -h 2 -n x --max-retries 5", with(KebabCase::On)), Some(5));
+        assert_eq!(parse(&parser, "./go -l 1 -h 2 -n x --max_retries 5", with(KebabCase::On)), None);
+        assert_eq!(parse(&parser, "./go -l 1 -h 2 -n x --max_retries 5", with(KebabCase::Both)), Some(5));
+        assert_eq!(parse(&parser, "./go -l 1 -h 2 -n x --max-retries=5", with(KebabCase::Both)), Some(5));
+        assert_eq!(parse(&parser, "./go -l 1 -h 2 -n x --length 1", with(KebabCase::On)), Some(3));
+        
+        // Both forms of one name are still a single match
+        let abbreviated = ParseOptions { abbreviations: true, ..with(KebabCase::Both) };
+        assert_eq!(parse(&parser, "./go -l 1 -h 2 -n x --max 5", abbreviated), Some(5));
+        
+        assert!(!parser.help_text().contains("--max-retries"));
+        parser.options(with(KebabCase::On));
+        let help = parser.help_text();
+        assert!(help.contains("--max-retries (-r)"));
+        assert!(help.contains("[--dry-run]"));
+        assert!(parser.bash_completion().contains("--max-retries|-r) COMPREPLY=(); return ;;"));
+        assert!(parser.markdown_usage().contains("| `--max-retries (-r)` |"));
+        
+        // Removing an argument moves the ones after it
+        parser.add_opt("last_one", Some("0"), 'z', false, "Last", ArgType::Option);
+        parser.remove_opt("max_retries").unwrap();
+        assert_eq!(parse(&parser, "./go -l 1 -h 2 -n x --max-retries 5", with(KebabCase::On)), None);
+        let args = "./go -l 1 -h 2 -n x --last-one 5 --dry-run".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        let p_res = parser.parse_with(args.iter(), with(KebabCase::On)).unwrap();
+        assert_eq!(p_res.get("last_one"), Some(5));
+        assert_eq!(p_res.get("dry_run"), Some(true));
+    }
+    
+    #[test]
+    fn test_inherit() {
+        let mut parent = setup_1();
+        parent.color(ColorChoice::Always);
+        parent.max_width(60);
+        parent.help_column(20);
+        parent.pager(true);
+        parent.options(ParseOptions { strict: true, ..ParseOptions::default() });
+        parent.messages(Messages { help: "Aide".into(), ..Messages::default() });
+        
+        let mut child = ArgParser::new("child".into());
+        child.color(ColorChoice::Never);
+        child.inherit(&parent, Inherit { color: false, pager: false, ..Inherit::default() });
+        assert_eq!(child.color, ColorChoice::Never);
+        assert!(!child.pager);
+        assert_eq!((child.max_width, child.help_column), (Some(60), Some(20)));
+        assert!(child.parse_line("--nope").is_err());
+        assert!(child.help_text().contains("Aide"));
+        
+        // Later settings of the child win
+        child.options(ParseOptions::default());
+        assert!(child.parse_line("--nope").is_ok());
+        
+        let mut child = ArgParser::new("child".into());
+        child.inherit(&parent, Inherit { color: true, messages: false, pager: false, layout: false, options: false });
+        assert_eq!(child.color, ColorChoice::Always);
+        assert_eq!(child.max_width, None);
+        assert!(child.parse_line("--nope").is_ok());
+        assert!(!child.help_text().contains("Aide"));
+    }
+    
+    #[test]
+    fn test_require_at_least() {
+        let mut parser = ArgParser::new("plot".into());
+        parser.add_opt("x", None, 'x', false, "X", ArgType::Option);
+        parser.add_opt("y", None, 'y', false, "Y", ArgType::Flag);
+        parser.add_opt("z", None, 'z', false, "Z", ArgType::Positional(0));
+        parser.add_opt("v", None, 'v', false, "V", ArgType::Counter);
+        assert!(parser.require_at_least(1, &["x", "w"]).is_err());
+        parser.require_at_least(2, &["x", "y", "z"]).unwrap();
+        parser.require_at_least(1, &["v"]).unwrap();
+        
+        assert!(parser.parse_line("-x 1 -y -v").is_ok());
+        assert!(parser.parse_line("-y f.txt -v").is_ok());
+        
+        let err = parser.parse_line("-y -v").unwrap_err();
+        assert_eq!(err.message(), "At least 2 of `x`, `y`, `z` must be given, but got 1 (`y`)");
+        let err = parser.parse_line("-x 1 -y").unwrap_err();
+        assert_eq!(err.message(), "At least 1 of `v` must be given, but got 0");
+        
+        parser.require_at_least(4, &["x", "y", "z"]).unwrap();
+        parser.remove_opt("y").unwrap();
+        assert_eq!(parser.validate(), Err(vec![
+            ConfigError::UnknownInGroup { name: "y".into() },
+            ConfigError::UnknownInGroup { name: "y".into() },
+            ConfigError::UnsatisfiableGroup { min: 4, names: vec!["x".into(), "y".into(), "z".into()] },
+        ]));
     }
     
-    let v: Vec<char> = s.chars().collect();
-    
-    if v[0] == '-' {
-        if v[1].is_alphabetic() {
-            return true;
+    #[test]
+    fn test_exclusive() {
+        let mut parser = setup_1();
+        parser.add_opt("init", None, 'i', false, "Init", ArgType::Option);
+        parser.add_opt("file", None, 'F', false, "File", ArgType::Positional(0));
+        parser.exclusive("init").unwrap();
+        parser.exclusive("mao").unwrap();
+        assert!(parser.exclusive("nope").is_err());
+        parser.require_at_least(1, &["file"]).unwrap();
+        
+        let p_res = parser.parse_line("--init=here").unwrap();
+        assert_eq!(p_res.get::<String>("init"), Some("here".into()));
+        assert_eq!(p_res.get::<u32>("length"), None);
+        assert!(parser.parse_line("-mm").is_ok());
+        
+        for bad in &["--init a -l 1", "-i a f.txt", "-i a -Z", "-i a -m"] {
+            let err = parser.parse_line(bad).unwrap_err();
+            assert!(err.message().ends_with("must be the only argument given"), "{}", bad);
         }
+        assert!(parser.parse_line("-l 1 -h 2 -n x f.txt").is_ok());
     }
     
-    false
-}
-
-fn is_long_flag(s: &str) -> bool {
-    if s.len() < 3 {
-        return false;
+    #[test]
+    fn test_terminates() {
+        let mut parser = setup_1();
+        parser.add_opt("print-config", None, 'P', false, "Print", ArgType::Option);
+        parser.terminates("print-config").unwrap();
+        parser.terminates("mao").unwrap();
+        assert!(parser.terminates("nope").is_err());
+        
+        let p_res = parser.parse_line("-n x --print-config json -l 1 --bad").unwrap();
+        assert_eq!(p_res.terminated_by(), Some("print-config"));
+        assert_eq!(p_res.get::<String>("print-config"), Some("json".into()));
+        assert_eq!(p_res.get::<String>("name"), Some("x".into()));
+        assert_eq!(p_res.get::<u32>("length"), None);
+        assert!(p_res.warnings().is_empty());
+        
+        let args: Vec<String> = vec!["./go".into(), "-mf".into(), "1".into()];
+        let p_res = parser.parse_borrowed(&args).unwrap();
+        assert_eq!(p_res.terminated_by(), Some("mao"));
+        assert_eq!(p_res.get_str("frequencies"), None);
+        
+        assert!(parser.parse_line("-n x -l 1 -h 2").unwrap().terminated_by().is_none());
+        assert!(parser.parse_line("-n x --print-config").is_err());
     }
     
-    let v: Vec<char> = s.chars().collect();
-    
-    if v[0] == v[1] && v[1] == '-' {
-        return true;
+    #[test]
+    fn test_overrides_with() {
+        let mut parser = setup_1();
+        parser.add_opt("quiet", None, 'q', false, "Quiet", ArgType::Counter);
+        parser.add_opt("verbose", None, 'v', false, "Verbose", ArgType::Counter);
+        parser.add_opt("color", Some("auto"), 'c', false, "Color", ArgType::Option);
+        parser.overrides_with("quiet", "verbose").unwrap();
+        parser.overrides_with("mao", "color").unwrap();
+        assert!(parser.overrides_with("quiet", "nope").is_err());
+        assert!(parser.overrides_with("nope", "quiet").is_err());
+        
+        let p_res = parser.parse_line("-l 1 -h 2 -n x -vv -q -c red -m").unwrap();
+        assert_eq!(p_res.get("quiet"), Some(1));
+        assert_eq!(p_res.get("verbose"), Some(0));
+        assert_eq!(p_res.get("mao"), Some(true));
+        assert_eq!(p_res.get::<String>("color"), Some("auto".into()));
+        
+        let p_res = parser.parse_line("-l 1 -h 2 -n x -q -vv -m -c red").unwrap();
+        assert_eq!(p_res.get("quiet"), Some(0));
+        assert_eq!(p_res.get("verbose"), Some(2));
+        assert_eq!(p_res.get::<bool>("mao"), Some(false));
+        assert!(p_res.warnings().is_empty());
     }
     
-    false
-}
-
-fn separate_flags(og: Vec<String>) -> Vec<String> {
-    let mut separated = Vec::new();
+    #[test]
+    fn test_max_occurrences() {
+        let mut parser = setup_1();
+        parser.add_opt("verbose", None, 'v', false, "Verbose", ArgType::Counter);
+        parser.max_occurrences("verbose", 2, TooMany::Error).unwrap();
+        parser.max_occurrences("name", 1, TooMany::Warn).unwrap();
+        assert!(parser.max_occurrences("nope", 1, TooMany::Warn).is_err());
+        
+        let p_res = parser.parse_line("-l 1 -h 2 -n x -vv -n y -n z").unwrap();
+        assert_eq!(p_res.get("verbose"), Some(2));
+        assert_eq!(p_res.get::<String>("name"), Some("z".into()));
+        assert_eq!(p_res.warnings(), &[
+            Warning::TooMany { name: "name".into(), max: 1, position: 8 },
+            Warning::TooMany { name: "name".into(), max: 1, position: 10 },
+        ]);
+        assert_eq!(p_res.warnings()[0].to_string(), "`name` was given more than 1 times");
+        
+        let err = parser.parse_line("-l 1 -h 2 -n x -v -vv").unwrap_err();
+        assert_eq!(err.message(), "The option `verbose` can be given at most 2 times");
+        assert_eq!(err.position(), Some(8));
+    }
     
-    for x in og {
-        if is_long_flag(&x) {
-            separated.push(x);
-        } else if is_flag(&x) {
-            if x.len() == 2 {
-                separated.push(x);
-            } else {
-                for short_flag in x.chars().skip(1) {
-                    separated.push(format!("-{}", short_flag));
-                }
-            }
-        } else {
-            separated.push(x);
-        }
+    #[test]
+    fn test_choices() {
+        let mut parser = setup_1();
+        parser.add_opt("format", None, 'F', false, "Format", ArgType::Option);
+        parser.add_opt("kinds", None, 'k', false, "Kinds", ArgType::List);
+        parser.add_opt("mode", None, 'M', false, "Mode", ArgType::Positional(0));
+        parser.choices("format", &["json", "YAML"]).unwrap();
+        parser.choices("kinds", &["a", "b"]).unwrap();
+        parser.choices("mode", &["Fast", "slow"]).unwrap();
+        parser.ignore_case("kinds").unwrap();
+        parser.ignore_case("mode").unwrap();
+        assert!(parser.choices("nope", &[]).is_err());
+        assert!(parser.ignore_case("nope").is_err());
+        
+        let args = "./go -l 1 -h 2 fast -n x -F YAML -k A b B".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        let p_res = parser.parse_borrowed(&args).unwrap();
+        assert_eq!(p_res.get_str("format"), Some("YAML"));
+        assert_eq!(p_res.get_list::<String>("kinds"), Some(vec!["a".into(), "b".into(), "b".into()]));
+        assert_eq!(p_res.get_str("mode"), Some("Fast"));
+        
+        let err = parser.parse_line("-l 1 -h 2 -n x -F yaml").unwrap_err();
+        assert_eq!(err.message(), "The value `yaml` is not valid for `format`: expected one of `json`, `YAML`");
+        assert_eq!(err.position(), Some(8));
+        assert!(parser.parse_line("-l 1 -h 2 -n x -k a c").is_err());
+        assert!(parser.parse_line("-l 1 -h 2 -n x medium").is_err());
     }
     
-    return separated;
-}
-
-#[cfg(test)]
-mod test {
-    use super::{ArgParser, ArgType, vec_parser, hashmap_parser};
-    use std::collections::HashMap;
-    const LONG_STR: &'static str = r#"Check your proxy settings or contact your network administrator to make sure the proxy server is working. If you don't believe you should be using a proxy server: Go to the Chromium menu > Settings > Show advanced settings... > Change proxy settings... and make sure your configuration is set to "no proxy" or "direct.""#;
+    #[test]
+    fn test_value_terminator() {
+        let mut parser = setup_1();
+        parser.add_opt("socks", None, 's', false, "Socks", ArgType::Dict);
+        parser.add_opt("files", None, 'F', false, "Files", ArgType::PositionalList(0));
+        parser.value_terminator("frequencies", ";").unwrap();
+        parser.value_terminator("socks", "end").unwrap();
+        assert!(parser.value_terminator("nope", ";").is_err());
+        
+        let p_res = parser.parse_line("-l 1 -h 2 -n x -f 1 2 ; a -s mon:1 end b -f=3 ; -f ; c").unwrap();
+        assert_eq!(p_res.get_list::<u32>("frequencies"), Some(vec![]));
+        assert_eq!(p_res.get_list::<String>("files"), Some(vec!["a".into(), "b".into(), ";".into(), "c".into()]));
+        assert_eq!(p_res.pairs("socks"), Some(&[("mon".into(), "1".into())][..]));
+        
+        // Other arguments' values may equal the terminator
+        let p_res = parser.parse_line("-l 1 -h 2 -n ; -f 1 ;").unwrap();
+        assert_eq!(p_res.get::<String>("name"), Some(";".into()));
+        assert_eq!(p_res.get_list("frequencies"), Some(vec![1]));
+    }
     
-    fn setup_1() -> ArgParser {
-        let mut parser = ArgParser::new("ArgParsers".into());
+    #[test]
+    fn test_list_values() {
+        let mut parser = setup_1();
+        parser.add_opt("socks", None, 's', false, "Socks", ArgType::Dict);
+        parser.add_opt("files", None, 'F', false, "Files", ArgType::PositionalList(0));
+        parser.list_values("frequencies", ListValues::AtMost(2)).unwrap();
+        parser.list_values("socks", ListValues::AtMost(1)).unwrap();
+        assert!(parser.list_values("nope", ListValues::Greedy).is_err());
         
-        parser.add_opt("length", None, 'l', true, LONG_STR, ArgType::Option);
-        parser.add_opt("height", None, 'h', true, "Height of user in centimeters", ArgType::Option);
-        parser.add_opt("name", None, 'n', true, "Name of user", ArgType::Option);
-        parser.add_opt("frequencies", None, 'f', false, "User's favorite frequencies", ArgType::List);
-        parser.add_opt("mao", Some("false"), 'm', false, "Is the User Chairman Mao?", ArgType::Flag);
+        let p_res = parser.parse_line("-l 1 -h 2 -n x -f 1 2 3 -s a:1 b:2 -f 4 -s c:3").unwrap();
+        assert_eq!(p_res.get_list("frequencies"), Some(vec![1, 2, 4]));
+        assert_eq!(p_res.pairs("socks"), Some(&[("a".into(), "1".into()), ("c".into(), "3".into())][..]));
+        assert_eq!(p_res.get_list::<String>("files"), Some(vec!["3".into(), "b:2".into()]));
+        assert!(p_res.warnings().is_empty());
         
-        parser
+        parser.list_values("frequencies", ListValues::Greedy).unwrap();
+        let p_res = parser.parse_line("-l 1 -h 2 -n x -f 1 2 3 -s a:1 b:2 -f 4").unwrap();
+        assert_eq!(p_res.get_list("frequencies"), Some(vec![4]));
+        assert_eq!(p_res.warnings().len(), 1);
     }
     
     #[test]
-    fn test_parser() {
-        let parser = setup_1();
+    fn test_list_reserves_positionals() {
+        let mut parser = setup_1();
+        parser.add_opt("input", None, 'i', false, "Input", ArgType::Positional(0));
+        parser.add_opt("output", None, 'o', false, "Output", ArgType::Positional(1));
+        parser.add_opt("rest", None, 'r', false, "Rest", ArgType::PositionalList(0));
+        parser.add_opt("extra", Some("e"), 'e', false, "Extra", ArgType::Positional(2));
+        
+        let p_res = parser.parse_line("-l 1 -h 2 -n x -f 1 2 3 in.csv out.csv").unwrap();
+        assert_eq!(p_res.get_list("frequencies"), Some(vec![1, 2, 3]));
+        assert_eq!(p_res.get::<String>("input"), Some("in.csv".into()));
+        assert_eq!(p_res.get::<String>("output"), Some("out.csv".into()));
+        assert_eq!(p_res.get_list::<String>("rest"), None);
+        assert_eq!(p_res.get::<String>("extra"), Some("e".into()));
+        
+        let p_res = parser.parse_line("-l 1 -h 2 -n x in.csv -f 1 2 3 out.csv").unwrap();
+        assert_eq!(p_res.get_list("frequencies"), Some(vec![1, 2, 3]));
+        assert_eq!(p_res.get::<String>("output"), Some("out.csv".into()));
+        
+        // The list keeps at least one value, and values before a flag
+        let p_res = parser.parse_line("-l 1 -h 2 -n x -f 1 -f 2 3 -m in.csv").unwrap();
+        assert_eq!(p_res.get_list("frequencies"), Some(vec![2, 3]));
+        assert_eq!(p_res.get::<String>("input"), Some("in.csv".into()));
+        assert_eq!(p_res.get::<String>("output"), None);
+    }
     
-        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny --mao -f 1 2 3 4 5".split_whitespace()
+    #[test]
+    fn test_middleware() {
+        use super::ParseEvent;
+        
+        let mut parser = setup_1();
+        parser.before_parse(|args| args.retain(|a| a != "--legacy"));
+        parser.before_parse(|args| if !args.iter().any(|a| a == "-l") {
+            args.splice(1..1, vec!["-l".to_string(), "1".into(), "-h".into(), "2".into()]);
+        });
+        parser.after_parse(|p_res| {
+            let name: String = p_res.get("name").unwrap_or_default();
+            p_res.set("name", &[&name.to_uppercase()])
+                .map_err(|e| e.to_string())
+        });
+        parser.after_parse(|p_res| match p_res.set("nope", &[]) {
+            Ok(_) => Err("set an unknown argument".into()),
+            Err(_) if p_res.get::<String>("name") == Some("EVE".into()) => Err("Eve is not allowed".into()),
+            Err(_) => Ok(()),
+        });
+        
+        let args = "./go -n johnny --legacy".split_whitespace()
             .map(|s| s.into())
             .collect::<Vec<String>>();
         
-        let p_res = parser.parse(test_1.iter()).unwrap();
+        let p_res = parser.parse(args.iter()).unwrap();
+        assert_eq!(p_res.get::<String>("name"), Some("JOHNNY".into()));
+        assert_eq!(p_res.get("length"), Some(1));
         
-        assert!(p_res.get("length") == Some(-60));
-        assert_eq!(p_res.get("height"), Some(-6001.45e-2));
-        assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
-        assert_eq!(p_res.get_with("frequencies", vec_parser), 
-            Some(vec![1,2,3,4,5]));
-        assert_eq!(p_res.get("mao"), Some(true));
+        let p_res = parser.parse_with(args.iter(), ParseOptions::default()).unwrap();
+        assert_eq!(p_res.get::<String>("name"), Some("JOHNNY".into()));
         
-        parser.help();
+        let err = parser.parse_line("-n eve").unwrap_err();
+        assert_eq!(err.message(), "Eve is not allowed");
+        assert_eq!(err.position(), None);
+        
+        // Borrowed parsing can't run the middleware, so it refuses to
+        let err = parser.parse_borrowed(&args).unwrap_err();
+        assert_eq!(err.message(), Messages::default().middleware);
+        let events: Vec<_> = parser.parse_iter(&args).collect();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], ParseEvent::Error(ref e) if e.message() == Messages::default().middleware));
     }
     
     #[test]
-    fn test_parser_unrequired() {
-        let parser = setup_1();
+    fn test_set_dict() {
+        use super::DuplicateKeys;
         
-        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny -f 1 2 3 4 5".split_whitespace()
-            .map(|s| s.into())
-            .collect::<Vec<String>>();
-            
-        let p_res = parser.parse(test_1.iter()).unwrap();
+        let mut parser = ArgParser::new("runner".into());
+        parser.add_opt("d", None, 'd', false, "Dict", ArgType::Dict);
+        parser.duplicate_keys("d", DuplicateKeys::Error).unwrap();
         
-        assert!(p_res.get("length") == Some(-60));
-        assert_eq!(p_res.get("height"), Some(-6001.45e-2));
-        assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
-        assert_eq!(p_res.get_with("frequencies", vec_parser), 
-            Some(vec![1,2,3,4,5]));
-        assert_eq!(p_res.get("mao"), Some(false));
+        let mut p_res = parser.parse_line("-d a:1 b:2 -d c:3").unwrap();
+        p_res.set("d", &["x:1", "y:a:b"]).unwrap();
         
-        parser.help();
+        let h: HashMap<String, String> = p_res.get_dict("d").unwrap();
+        assert_eq!(h, [("x".to_string(), "1".to_string()), ("y".into(), "a:b".into())].iter().cloned().collect());
+        assert_eq!(p_res.get_ordered_dict::<String, String>("d").unwrap()[0], ("x".into(), "1".into()));
+        assert_eq!(p_res.pairs("d").unwrap().len(), 2);
+        assert_eq!(p_res.values("d").unwrap(), ["x:1", "y:a:b"]);
+        assert_eq!(p_res.iter().find(|&(name, _, _)| name == "d").map(|(_, _, count)| count), Some(2));
+        
+        assert_eq!(p_res.set("d", &["x:1", "oops"]), Err("Expected a key:value pair"));
+        assert_eq!(p_res.set("d", &["x:1", "x:2"]), Err("Duplicate key"));
+        assert_eq!(p_res.pairs("d").unwrap().len(), 2);
     }
     
     #[test]
-    fn test_parser_unrequired_nodefault() {
+    fn test_set_counts() {
         let parser = setup_1();
+        let mut p_res = parser.parse_line("-l 1 -h 2 -n x -n y").unwrap();
         
-        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny".split_whitespace()
+        p_res.set("frequencies", &["1", "2"]).unwrap();
+        p_res.set("name", &["z"]).unwrap();
+        
+        let counts: HashMap<&str, u16> = p_res.iter().map(|(name, _, count)| (name, count)).collect();
+        assert_eq!(counts["frequencies"], 1);
+        assert_eq!(counts["name"], 2);
+        assert_eq!(p_res.to_map()["frequencies"], Some("1 2 ".into()));
+        assert_eq!(p_res.set("nope", &[]), Err("No such Option"));
+    }
+    
+    #[test]
+    fn test_warnings() {
+        let mut parser = setup_1();
+        parser.deprecated("mao", "there is only one").unwrap();
+        assert!(parser.deprecated("nope", "").is_err());
+        
+        let args = "./go -l 1 -h 2 -n x -mm -f 1 -Z -f 2 --zz=1".split_whitespace()
             .map(|s| s.into())
             .collect::<Vec<String>>();
-            
-        let p_res = parser.parse(test_1.iter()).unwrap();
+        let p_res = parser.parse(args.iter()).unwrap();
         
-        assert!(p_res.get("length") == Some(-60));
-        assert_eq!(p_res.get("height"), Some(-6001.45e-2));
-        assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
-        assert_eq!(p_res.get_with::<Vec<u8>, _>("frequencies", vec_parser), None);
-        assert_eq!(p_res.get("mao"), Some(false));
+        assert_eq!(p_res.warnings(), &[
+            Warning::Deprecated { name: "mao".into(), note: "there is only one".into() },
+            Warning::UnknownFlag { flag: "-Z".into(), position: 10 },
+            Warning::Overridden { name: "frequencies".into(), position: 11 },
+            Warning::UnknownFlag { flag: "--zz".into(), position: 13 },
+        ]);
+        assert_eq!(parser.parse_borrowed(&args).unwrap().warnings().len(), 4);
         
-        parser.help();
+        let args = "./go -l 1 -h 2 -n x".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        assert!(parser.parse(args.iter()).unwrap().warnings().is_empty());
     }
     
     #[test]
-    fn test_parser_dict() {
+    fn test_int_parser() {
+        assert_eq!(int_parser::<u8>("0x1F"), Some(31));
+        assert_eq!(int_parser::<u8>("0X1f"), Some(31));
+        assert_eq!(int_parser::<u32>("0o755"), Some(493));
+        assert_eq!(int_parser::<u8>("0b1010"), Some(10));
+        assert_eq!(int_parser::<u32>("1_000_000"), Some(1_000_000));
+        assert_eq!(int_parser::<u32>("0b_1010"), None);
+        assert_eq!(int_parser::<i16>("-0x10"), Some(-16));
+        assert_eq!(int_parser::<i16>("+12"), Some(12));
+        
+        for bad in &["", "-", "0x", "_1", "1_", "0x-1", "--1", "+-1", "0x1G", "0b2", "256", "1 2"] {
+            assert_eq!(int_parser::<u8>(bad), None, "{}", bad);
+        }
+    }
+    
+    #[test]
+    fn test_vec_parser_variants() {
+        assert_eq!(vec_parser::<i8>("1 -2 x 300"), None);
+        assert_eq!(try_vec_parser::<i8>("1 -2 x 300"), Err((2, "x".into())));
+        assert_eq!(try_vec_parser::<i8>("1 -2 300"), Err((2, "300".into())));
+        assert_eq!(try_vec_parser::<i8>(""), Ok(vec![]));
+        assert_eq!(lenient_vec_parser::<i8>("1 -2 x 300"), Some(vec![1, -2]));
+        assert_eq!(lenient_vec_parser::<i8>("x"), Some(vec![]));
+    }
+    
+    #[test]
+    fn test_hashmap_parser_no_separator() {
+        assert_eq!(hashmap_parser::<String, String>("a:b c"), None);
+        assert_eq!(hashmap_parser::<String, String>("c a:b"), None);
+        assert_eq!(hashmap_parser::<String, String>(""), None);
+        assert_eq!(try_hashmap_parser::<String, String>("a:b c"), Err("c"));
+        assert_eq!(try_hashmap_parser::<String, String>("").map(|h| h.len()), Ok(0));
+        
+        assert_eq!(btreemap_parser::<String, String>("a:b c"), None);
+        assert_eq!(btreemap_parser::<String, String>(""), None);
+        assert_eq!(btreemap_parser::<u8, bool>("2:true 1:false").map(|b| b.into_iter().collect::<Vec<_>>()),
+            Some(vec![(1, false), (2, true)]));
+    }
+    
+    #[test]
+    fn test_parse_never_panics() {
+        const PIECES: &[&str] = &["-", "--", "-l", "--length", "-h", "-n", "--name", "-f", "--frequencies",
+            "-s", "--socks", "-v", "=", ":", "a:b", "x", "-60", "-.5", "1", "é", "-🦀", "--=", "-=",
+            "a b", "", " ", "-l=", "--socks=:", "-fsn", "--help", "\u{0}", "-w", "+é", "-2é", "-2d"];
+        
         let mut parser = setup_1();
         parser.add_opt("socks", None, 's', false, "If you wear socks that day", ArgType::Dict);
+        parser.add_opt("verbose", None, 'v', false, "Be verbose", ArgType::Flag);
+        parser.add_opt("csv", None, 'c', false, "csv input file", ArgType::Positional(0));
+        parser.add_opt("json", None, 'j', false, "json output file", ArgType::Positional(255));
+        parser.add_opt("when", None, 'w', false, "When", ArgType::Option);
+        #[cfg(feature = "chrono")]
+        parser.validator("when", super::date_validator).unwrap();
         
-        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny -s Monday:true Friday:false".split_whitespace()
+        // A small xorshift generator keeps the test deterministic
+        let mut state: u32 = 0x9e37_79b9;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as usize
+        };
+        
+        for _ in 0..2000 {
+            let len = next() % 12;
+            let args: Vec<String> = (0..len)
+                .map(|_| {
+                    let piece = PIECES[next() % PIECES.len()];
+                    if next() % 4 == 0 {
+                        format!("{}{}", piece, PIECES[next() % PIECES.len()])
+                    } else {
+                        piece.into()
+                    }
+                })
+                .collect();
+            
+            match parser.parse(args.iter()) {
+                Ok(p_res) => {
+                    for name in &["length", "height", "name", "frequencies", "socks", "verbose", "csv", "json", "nope"] {
+                        let _ = p_res.get::<String>(name);
+                        let _ = p_res.get::<f64>(name);
+                        let _ = p_res.get_list::<i32>(name);
+                        let _ = p_res.get_dict::<String, String>(name);
+                        let _ = p_res.get_with(name, hashmap_parser::<String, i32>);
+                    }
+                }
+                Err(err) => {
+                    let _ = err.render();
+                }
+            }
+            
+            let _ = parser.parse_borrowed(&args);
+        }
+    }
+    
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        
+        assert_send_sync::<ArgParser>();
+        assert_send_sync::<SharedParser>();
+        assert_send_sync::<ArgParseResults>();
+        assert_send_sync::<ParseError>();
+    }
+    
+    #[test]
+    fn test_parser_terminator() {
+        let mut parser = setup_1();
+        parser.add_opt("csv", None, 'c', false, "csv input file", ArgType::Positional(0));
+        parser.add_opt("json", None, 'j', false, "json output file", ArgType::Positional(1));
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny -f 1 2 -- -3 --mao".split_whitespace()
             .map(|s| s.into())
             .collect::<Vec<String>>();
             
         let p_res = parser.parse(test_1.iter()).unwrap();
         
-        assert!(p_res.get("length") == Some(-60));
-        assert_eq!(p_res.get("height"), Some(-6001.45e-2));
-        assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
-        assert_eq!(p_res.get_with::<Vec<u8>, _>("frequencies", vec_parser), None);
+        assert_eq!(p_res.get_list("frequencies"), Some(vec![1, 2]));
+        assert_eq!(p_res.get::<String>("csv"), Some("-3".into()));
+        assert_eq!(p_res.get::<String>("json"), Some("--mao".into()));
         assert_eq!(p_res.get("mao"), Some(false));
+    }
+    
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("", 30, 20), vec![String::new()]);
+        assert_eq!(wrap("a b c", 30, 20), vec!["a b c".to_string()]);
         
-        let h = [("Monday", true), ("Friday", false)]
-            .iter()
-            .map(|&(k, v)| (k.into(), v))
+        let lines = wrap(LONG_STR, 40, 30);
+        assert!(lines[0].chars().count() <= 40);
+        assert!(lines[1..].iter().all(|l| l.chars().count() <= 30));
+        assert_eq!(lines.join(" "), LONG_STR);
+    }
+    
+    #[test]
+    #[cfg(feature = "unicode-width")]
+    fn test_wrap_wide_chars() {
+        // Each of these characters takes up two columns
+        let text = "日本語 日本語 日本語 日本語 日本語 日本語";
+        
+        let lines = wrap(text, 20, 20);
+        assert_eq!(lines, vec!["日本語 日本語 日本語", "日本語 日本語 日本語"]);
+        
+        // Combining marks take up no room of their own
+        assert_eq!(display_width("e\u{301}te\u{301}"), 3);
+    }
+    
+    #[test]
+    fn test_program_name() {
+        let name = |p: &str| program_name(Path::new(p));
+        
+        assert_eq!(name("./target/debug/tool"), Some("tool".into()));
+        assert_eq!(name("/usr/local/bin/tool.v2"), Some("tool.v2".into()));
+        assert_eq!(name("tool.exe"), Some("tool".into()));
+        assert_eq!(name(".exe"), Some(".exe".into()));
+        assert_eq!(name("/"), None);
+        
+        let parser = ArgParser::from_argv0();
+        let exe = env::current_exe().unwrap();
+        assert_eq!(parser.name, program_name(&exe).unwrap());
+    }
+    
+    #[test]
+    fn test_title() {
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Never);
+        assert!(parser.help_text().starts_with("Usage:"));
+        
+        parser.title("Argument Parser");
+        assert!(parser.help_text().starts_with("Argument Parser\n\nUsage:\t./ArgParsers "));
+        
+        parser.max_width(30);
+        parser.description("Parses the arguments of users, whoever they may be");
+        assert!(parser.help_text().starts_with("Argument Parser\nParses the arguments of users,\nwhoever they may be\n\nUsage:"));
+    }
+    
+    #[test]
+    fn test_examples() {
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Never);
+        assert!(!parser.help_text().contains("Examples:"));
+        
+        parser.example("go -l 1 -h 2 -n Johnny", "Johnny, who is short");
+        parser.example("go -l 9 -h 9 -n Tina -m", "Tina, who is Chairman Mao");
+        
+        let help = parser.help_text();
+        assert!(help.ends_with("Examples:\n\n  \
+            go -l 1 -h 2 -n Johnny\n    Johnny, who is short\n\n  \
+            go -l 9 -h 9 -n Tina -m\n    Tina, who is Chairman Mao\n\n"));
+    }
+    
+    #[test]
+    fn test_long_help() {
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Never);
+        parser.long_help("height", "Measured barefoot, standing up straight").unwrap();
+        assert!(parser.long_help("nope", "Nothing").is_err());
+        
+        assert!(parser.help_text().contains("Type: Option\n\n  --name"));
+        assert!(parser.long_help_text().contains(&format!(
+            "Type: Option\n\n{:22}Measured barefoot, standing up straight\n\n  --name", "")));
+    }
+    
+    #[test]
+    fn test_display_order() {
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Never);
+        parser.max_width(500);
+        parser.add_opt("csv", None, 'c', false, "csv input file", ArgType::Positional(0));
+        parser.display_order("mao", 1).unwrap();
+        parser.display_order("name", 1).unwrap();
+        parser.display_order("csv", 0).unwrap();
+        parser.display_order("frequencies", 0).unwrap();
+        assert!(parser.display_order("nope", 0).is_err());
+        
+        let help = parser.help_text();
+        let options: Vec<&str> = help.lines()
+            .filter(|l| l.starts_with("  --"))
+            .map(|l| l.split_whitespace().next().unwrap_or(""))
             .collect();
-            
-        assert_eq!(p_res.get_with::<HashMap<String, bool>, _>("socks", hashmap_parser),
-            Some(h));
+        assert_eq!(options, vec!["--frequencies", "--csv", "--name", "--mao", "--help", "--length", "--height"]);
         
-        parser.help();
+        assert!(help.contains("./ArgParsers [--frequencies FREQUENCIES...] --name NAME [--mao] \
+            [--help] --length LENGTH --height HEIGHT [<CSV>]\n"));
     }
     
     #[test]
-    fn test_parser_positional() {
+    fn test_help_columns() {
         let mut parser = setup_1();
+        parser.color(ColorChoice::Never);
+        parser.max_width(80);
         
-        parser.add_opt("csv", None, 'c', true, "csv input file",
-            ArgType::Positional(0));
-        parser.add_opt("json", None, 'j', true, "json output file",
-            ArgType::Positional(1));
+        let help = parser.help_text();
+        assert!(help.contains(&format!("\n  --height (-h)       Height of user in centimeters\n\
+            {:22}Required: true  Type: Option\n\n", "")));
+        assert!(help.contains("\n  --frequencies (-f)  User's favorite frequencies\n"));
         
-        let test_1 = "./go -l -60 -h -6001.45e-2 -n Johnny crap.csv crap.json".split_whitespace()
+        parser.help_column(16);
+        let help = parser.help_text();
+        assert!(help.contains("\n  --mao (-m)    Is the User Chairman Mao?\n"));
+        assert!(help.contains("\n  --frequencies (-f)\n                User's favorite frequencies\n"));
+        
+        // By default, descriptions start no further in than `MAX_COLUMN`
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Never);
+        parser.add_opt("a-very-long-option-name", None, 'a', false, "Long", ArgType::Flag);
+        assert!(parser.help_text().contains("\n  --a-very-long-option-name (-a)\n                                Long\n"));
+    }
+    
+    #[test]
+    fn test_visible_if() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        
+        static SHOWN: AtomicBool = AtomicBool::new(false);
+        
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Never);
+        parser.visible_if("mao", || SHOWN.load(Ordering::SeqCst)).unwrap();
+        assert!(parser.visible_if("nope", || true).is_err());
+        
+        let help = parser.help_text();
+        assert!(!help.contains("--mao"));
+        assert!(!help.contains("Chairman"));
+        
+        SHOWN.store(true, Ordering::SeqCst);
+        let help = parser.help_text();
+        assert!(help.contains("[--mao]"));
+        assert!(help.contains("--mao (-m)"));
+        
+        SHOWN.store(false, Ordering::SeqCst);
+        let args = "./go -l 1 -h 2 -n x -m".split_whitespace()
+            .map(|s| s.into())
+            .collect::<Vec<String>>();
+        assert_eq!(parser.parse(args.iter()).unwrap().get("mao"), Some(true));
+    }
+    
+    #[test]
+    fn test_usage_wrapping() {
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Never);
+        parser.max_width(60);
+        
+        let help = parser.help_text();
+        let usage: Vec<&str> = help.lines().take_while(|l| !l.is_empty() && !l.starts_with("Options")).collect();
+        
+        assert_eq!(usage, vec![
+            "Usage:\t./ArgParsers [--help] --length LENGTH",
+            "                     --height HEIGHT --name NAME",
+            "                     [--frequencies FREQUENCIES...] [--mao]",
+        ]);
+        
+        assert_eq!(parser.render_usage(), usage.join("\n"));
+        
+        parser.max_width(200);
+        assert_eq!(parser.help_text().lines().next().map(|l| l.ends_with("[--mao]")), Some(true));
+        assert!(!parser.render_usage().contains('\n'));
+    }
+    
+    #[test]
+    fn test_help_max_width() {
+        let mut parser = setup_1();
+        parser.max_width(50);
+        
+        let help = parser.render_help(false, false);
+        
+        for line in help.lines().skip_while(|l| !l.starts_with("Options")) {
+            assert!(line.chars().count() <= 50, "{:?}", line);
+        }
+    }
+    
+    #[test]
+    fn test_markdown_usage() {
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Always);
+        parser.max_width(60);
+        parser.add_opt("mode", Some("a"), 'o', false,
+            "Either a|b,\nor c", ArgType::Option);
+        
+        let snippet = parser.markdown_usage();
+        let lines: Vec<&str> = snippet.lines().collect();
+        
+        assert!(!snippet.contains('\x1b'));
+        assert_eq!(lines[0], "```text");
+        assert_eq!(lines[1..5].join("\n"), parser.usage_text(false));
+        assert_eq!(lines[5], "```");
+        assert_eq!(lines[6], "");
+        assert_eq!(lines[7], "| Options | | Required | Type |");
+        assert_eq!(lines[9], "| `--help (-h)` | Show this help message | false | Flag |");
+        assert_eq!(lines.last(), Some(&"| `--mode (-o)` | Either a\\|b, or c | false | Option |"));
+        assert_eq!(lines.len(), 9 + parser.displayed().len());
+    }
+    
+    #[test]
+    fn test_bash_completion() {
+        let mut parser = setup_1();
+        parser.add_opt("format", Some("json"), 'o', false,
+            "Output format", ArgType::Option);
+        parser.choices("format", &["json", "yaml", "it's"]).unwrap();
+        parser.add_opt("input", None, 'i', false,
+            "File to read", ArgType::Path);
+        parser.add_opt("action", None, 'a', true,
+            "What to do", ArgType::Positional(0));
+        parser.choices("action", &["start", "stop"]).unwrap();
+        
+        let script = parser.bash_completion();
+        let lines: Vec<&str> = script.lines().collect();
+        
+        assert_eq!(lines[0], "_ArgParsers() {");
+        assert!(lines.contains(&"        --format|-o) COMPREPLY=($(compgen -W 'json yaml it\\'\\''s' -- \"$cur\")); return ;;"));
+        assert!(lines.contains(&"        --input|-i) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;"));
+        assert!(lines.contains(&"        --name|-n) COMPREPLY=(); return ;;"));
+        assert!(!script.contains("--mao|-m)"));
+        assert!(!script.contains("--action"));
+        
+        let default = lines.iter().find(|l| l.starts_with("    COMPREPLY=")).unwrap();
+        assert!(default.contains(" --mao -m "));
+        assert!(default.contains(" start stop'"));
+        assert_eq!(lines.last(), Some(&"complete -o default -F _ArgParsers ArgParsers"));
+    }
+    
+    #[test]
+    fn test_parser_messages() {
+        let mut parser = setup_1();
+        parser.color(ColorChoice::Never);
+        parser.messages(Messages {
+            error: "erreur".into(),
+            missing_value: "L'option `{name}` attend une valeur".into(),
+            ..Messages::default()
+        });
+        
+        let test_1 = "./go -l -60 -h -6001.45e-2 -n".split_whitespace()
             .map(|s| s.into())
             .collect::<Vec<String>>();
             
-        let p_res = parser.parse(test_1.iter()).unwrap();
+        let err = parser.parse(test_1.iter()).unwrap_err();
         
-        assert!(p_res.get("length") == Some(-60));
-        assert_eq!(p_res.get("height"), Some(-6001.45e-2));
-        assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
-        assert_eq!(p_res.get_with::<Vec<u8>, _>("frequencies", vec_parser), None);
-        assert_eq!(p_res.get("mao"), Some(false));
-        assert_eq!(p_res.get::<String>("csv"), Some("crap.csv".into()));
-        assert_eq!(p_res.get::<String>("json"), Some("crap.json".into()));
+        assert_eq!(err.message(), "L'option `name` attend une valeur");
+        assert!(err.render().starts_with("erreur: L'option `name` attend une valeur\n"));
+    }
+    
+    #[test]
+    fn test_modify_opt() {
+        let mut parser = ArgParser::new("runner".into());
+        parser.add_opt("jobs", Some("1"), 'j', false, "Jobs", ArgType::Option);
+        parser.add_opt("out", None, 'o', false, "Output", ArgType::Option);
+        parser.choices("jobs", &["1", "2"]).unwrap();
         
-        parser.help();
+        parser.modify_opt("jobs", |arg| {
+            assert_eq!(arg.name(), "jobs");
+            arg.default(None).required(true).help("How many jobs");
+        }).unwrap();
+        assert!(parser.modify_opt("nope", |_| {}).is_err());
+        assert!(parser.parse_line("").is_err());
+        assert!(parser.parse_line("-j 4").is_err());
+        assert_eq!(parser.parse_line("-j 2").unwrap().get("jobs"), Some(2));
+        assert!(parser.help_text().contains("How many jobs"));
+        
+        // The old short flag goes, and the option keeps its place
+        parser.replace_opt("jobs", Some("3"), 'J', false, "Jobs", ArgType::Option).unwrap();
+        assert_eq!(parser.parse_line("-j 4").unwrap().get("jobs"), Some(3));
+        assert_eq!(parser.parse_line("-J 4").unwrap().get("jobs"), Some(4));
+        assert_eq!(parser.parse_line("").unwrap().get("jobs"), Some(3));
+        let help = parser.help_text();
+        assert!(help.find("--jobs").unwrap() < help.find("--out").unwrap());
+        assert!(parser.replace_opt("nope", None, 'n', false, "Nope", ArgType::Flag).is_err());
     }
 }
\ No newline at end of file
@@ -0,0 +1,195 @@
+//! This module defines the color settings shared by help and error
+//! output, along with the small set of ANSI helpers used to apply them.
+//! It also defines `Rgb`, for arguments whose values are colors.
+
+use std::env;
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+
+/// Whether help and error output should be colorized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Colorize only when writing to a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    /// Always emit ANSI color codes
+    Always,
+    /// Never emit ANSI color codes
+    Never,
+}
+
+/// Which standard stream a piece of output is destined for, used to
+/// resolve `ColorChoice::Auto`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl ColorChoice {
+    /// Resolves the choice into a yes/no answer for the given stream
+    pub(crate) fn enabled(self, stream: Stream) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                if env::var_os("NO_COLOR").is_some() {
+                    return false;
+                }
+
+                match stream {
+                    Stream::Stdout => io::stdout().is_terminal(),
+                    Stream::Stderr => io::stderr().is_terminal(),
+                }
+            }
+        }
+    }
+}
+
+pub(crate) const BOLD: &str = "\x1b[1m";
+pub(crate) const UNDERLINE: &str = "\x1b[4m";
+pub(crate) const RED: &str = "\x1b[31m";
+pub(crate) const GREEN: &str = "\x1b[32m";
+pub(crate) const RESET: &str = "\x1b[0m";
+
+/// Wraps `s` in the given ANSI style when `enabled`, otherwise returns it as is
+pub(crate) fn paint(s: &str, style: &str, enabled: bool) -> String {
+    if enabled {
+        format!("{}{}{}", style, s, RESET)
+    } else {
+        s.into()
+    }
+}
+
+/// A color given as an argument value, as `#RRGGBB` (or the short
+/// `#RGB`), `rgb(r, g, b)` or one of the basic CSS color names such as
+/// `red` or `teal`. As it implements `FromStr`, it can be read with
+/// `get`.
+/// # Example
+/// ```
+/// use argparse::{ArgParser, ArgType, Rgb};
+///
+/// let mut parser = ArgParser::new("paint".into());
+/// parser.add_opt("fg", Some("white"), 'f', false,
+///     "Text color", ArgType::Option);
+/// parser.add_opt("bg", Some("black"), 'b', false,
+///     "Background color", ArgType::Option);
+///
+/// let test_1 = "./paint --fg #ff8000 --bg rgb(0,0,128)".split_whitespace()
+///     .map(|s| s.into())
+///     .collect::<Vec<String>>();
+///
+/// let p_res = parser.parse(test_1.iter()).unwrap();
+/// assert_eq!(p_res.get("fg"), Some(Rgb { r: 255, g: 128, b: 0 }));
+/// assert_eq!(p_res.get("bg"), Some(Rgb { r: 0, g: 0, b: 128 }));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb {
+    /// The red component
+    pub r: u8,
+    /// The green component
+    pub g: u8,
+    /// The blue component
+    pub b: u8,
+}
+
+/// The basic CSS colors, plus `orange`
+const NAMED: &[(&str, Rgb)] = &[
+    ("black", Rgb { r: 0, g: 0, b: 0 }),
+    ("silver", Rgb { r: 192, g: 192, b: 192 }),
+    ("gray", Rgb { r: 128, g: 128, b: 128 }),
+    ("grey", Rgb { r: 128, g: 128, b: 128 }),
+    ("white", Rgb { r: 255, g: 255, b: 255 }),
+    ("maroon", Rgb { r: 128, g: 0, b: 0 }),
+    ("red", Rgb { r: 255, g: 0, b: 0 }),
+    ("purple", Rgb { r: 128, g: 0, b: 128 }),
+    ("fuchsia", Rgb { r: 255, g: 0, b: 255 }),
+    ("magenta", Rgb { r: 255, g: 0, b: 255 }),
+    ("green", Rgb { r: 0, g: 128, b: 0 }),
+    ("lime", Rgb { r: 0, g: 255, b: 0 }),
+    ("olive", Rgb { r: 128, g: 128, b: 0 }),
+    ("yellow", Rgb { r: 255, g: 255, b: 0 }),
+    ("navy", Rgb { r: 0, g: 0, b: 128 }),
+    ("blue", Rgb { r: 0, g: 0, b: 255 }),
+    ("teal", Rgb { r: 0, g: 128, b: 128 }),
+    ("aqua", Rgb { r: 0, g: 255, b: 255 }),
+    ("cyan", Rgb { r: 0, g: 255, b: 255 }),
+    ("orange", Rgb { r: 255, g: 165, b: 0 }),
+];
+
+impl FromStr for Rgb {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Rgb, String> {
+        let s = s.trim();
+        let lower = s.to_ascii_lowercase();
+        
+        if let Some(&(_, rgb)) = NAMED.iter().find(|&&(name, _)| name == lower) {
+            return Ok(rgb);
+        }
+        
+        if let Some(hex) = s.strip_prefix('#') {
+            let digits: Option<Vec<u8>> = hex.chars()
+                .map(|c| c.to_digit(16).map(|d| d as u8))
+                .collect();
+            
+            return match digits.as_deref() {
+                Some(&[r, g, b]) => Ok(Rgb { r: r * 17, g: g * 17, b: b * 17 }),
+                Some(&[r1, r2, g1, g2, b1, b2]) => Ok(Rgb { r: r1 * 16 + r2, g: g1 * 16 + g2, b: b1 * 16 + b2 }),
+                _ => Err("expected `#RRGGBB` or `#RGB` with hexadecimal digits".into()),
+            };
+        }
+        
+        if let Some(inner) = lower.strip_prefix("rgb(").and_then(|rest| rest.strip_suffix(')')) {
+            let parts: Option<Vec<u8>> = inner.split(',')
+                .map(|part| part.trim().parse().ok())
+                .collect();
+            
+            return match parts.as_deref() {
+                Some(&[r, g, b]) => Ok(Rgb { r, g, b }),
+                _ => Err("expected `rgb(r, g, b)` with components from 0 to 255".into()),
+            };
+        }
+        
+        Err(format!("`{}` is not a color name, `#RRGGBB` or `rgb(r, g, b)`", s))
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Function that parses colors into `Rgb`s, the same as `get::<Rgb>`.
+/// Usable with `get_with`.
+pub fn rgb_parser(s: &str) -> Option<Rgb> {
+    s.parse().ok()
+}
+
+/// Validator for colors, as read by `rgb_parser`
+pub fn rgb_validator(s: &str) -> Result<(), String> {
+    s.parse::<Rgb>().map(|_| ())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Rgb, rgb_parser, rgb_validator};
+    
+    #[test]
+    fn test_rgb() {
+        let rgb = |r, g, b| Some(Rgb { r, g, b });
+        
+        assert_eq!(rgb_parser("#FF8000"), rgb(255, 128, 0));
+        assert_eq!(rgb_parser("#f80"), rgb(255, 136, 0));
+        assert_eq!(rgb_parser("rgb(1, 2, 3)"), rgb(1, 2, 3));
+        assert_eq!(rgb_parser("RGB(1,2,3)"), rgb(1, 2, 3));
+        assert_eq!(rgb_parser("Teal"), rgb(0, 128, 128));
+        assert_eq!(Rgb { r: 255, g: 128, b: 0 }.to_string(), "#ff8000");
+        
+        for bad in &["", "#", "#12", "#12345g", "#+1+2+3", "rgb(1,2)", "rgb(1,2,256)", "rgb(1,2,3", "reddish"] {
+            assert!(rgb_validator(bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! This module defines a `Dispatcher`, which routes a command line to
+//! the parser and handler of a subcommand, as in `git commit -m msg`.
+//! The program's own parser reads the arguments before the subcommand's
+//! name, and the subcommand's parser those after it. Handlers are given
+//! both results, the program's first.
+//!
+//! # Example
+//! ```
+//! use argparse::{ArgParser, ArgType};
+//! use argparse::dispatch::{DispatchError, Dispatcher};
+//!
+//! let mut add = ArgParser::new("todo add".into());
+//! add.add_opt("item", None, 'i', true,
+//!     "What to do", ArgType::Positional(0));
+//!
+//! let mut dispatcher = Dispatcher::new(ArgParser::new("todo".into()));
+//! dispatcher.command("add", add, |_, p_res| {
+//!     match p_res.get::<String>("item") {
+//!         Some(ref item) if item == "nothing" => Err("nothing to add"),
+//!         _ => Ok(()),
+//!     }
+//! });
+//!
+//! let args = |line: &str| line.split_whitespace().map(|s| s.into()).collect::<Vec<String>>();
+//!
+//! assert!(dispatcher.run(args("./todo add milk").iter()).is_ok());
+//! assert!(dispatcher.run(args("./todo add nothing").iter()).is_err());
+//! match dispatcher.run(args("./todo remove milk").iter()) {
+//!     Err(DispatchError::UnknownCommand(name)) => assert_eq!(name, "remove"),
+//!     _ => panic!("expected an unknown command"),
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+use argparser::{ArgParseResults, ArgParser, ArgType, ParseError};
+
+/// The name the subcommand and its arguments are kept under in the
+/// program's parser
+const COMMAND: &str = "command";
+
+type Handler<E> = Box<dyn Fn(&ArgParseResults, &ArgParseResults) -> Result<(), E>>;
+
+/// Why `Dispatcher::run` failed
+#[derive(Debug)]
+pub enum DispatchError<E> {
+    /// The program's arguments, or those of the subcommand, failed to
+    /// parse
+    Parse(ParseError),
+    /// No subcommand of that name was registered
+    UnknownCommand(String),
+    /// The subcommand's handler failed
+    Handler(E),
+}
+
+impl<E: fmt::Display> fmt::Display for DispatchError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DispatchError::Parse(ref e) => write!(f, "{}", e),
+            DispatchError::UnknownCommand(ref name) => write!(f, "Unknown command `{}`", name),
+            DispatchError::Handler(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for DispatchError<E> {}
+
+impl<E> From<ParseError> for DispatchError<E> {
+    fn from(e: ParseError) -> DispatchError<E> {
+        DispatchError::Parse(e)
+    }
+}
+
+/// A program's parser together with the subcommands it dispatches to,
+/// each with a parser and a handler
+pub struct Dispatcher<E> {
+    parser: ArgParser,
+    commands: Vec<(String, ArgParser, Handler<E>)>,
+}
+
+impl<E> fmt::Debug for Dispatcher<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Dispatcher")
+            .field("parser", &self.parser)
+            .field("commands", &self.commands.iter().map(|c| &c.0).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<E> Dispatcher<E> {
+    /// Creates a dispatcher for the program with the given parser, to
+    /// which a hidden `command` argument is added that takes the name of
+    /// the subcommand and every argument after it
+    pub fn new(mut parser: ArgParser) -> Dispatcher<E> {
+        parser.add_opt(COMMAND, None, '\0', true, "The command to run, and its arguments", ArgType::Command);
+        let _ = parser.visible_if(COMMAND, || false);
+        
+        Dispatcher { parser, commands: Vec::new() }
+    }
+    
+    /// Registers a subcommand, replacing any of the same name. The
+    /// handler is given the results of the program's parser, for the
+    /// options given before the subcommand, then those of the
+    /// subcommand's parser.
+    pub fn command<F>(&mut self, name: &str, parser: ArgParser, handler: F) -> &mut Dispatcher<E>
+        where F: Fn(&ArgParseResults, &ArgParseResults) -> Result<(), E> + 'static {
+        
+        self.commands.retain(|c| c.0 != name);
+        self.commands.push((name.into(), parser, Box::new(handler)));
+        self
+    }
+    
+    /// The names of the registered subcommands, in the order they were
+    /// registered
+    pub fn commands(&self) -> Vec<&str> {
+        self.commands.iter().map(|c| c.0.as_str()).collect()
+    }
+    
+    /// The program's parser
+    pub fn parser(&self) -> &ArgParser {
+        &self.parser
+    }
+    
+    /// Parses the arguments, the first being the program name as with
+    /// `ArgParser::parse`, and runs the handler of the subcommand they
+    /// name with the program's results and its own.
+    pub fn run<'a, I: Iterator<Item = &'a String>>(&self, args: I) -> Result<(), DispatchError<E>> {
+        let p_res = self.parser.parse(args)?;
+        let given = p_res.get_list::<String>(COMMAND).unwrap_or_default();
+        
+        let name = given.first().cloned().unwrap_or_default();
+        let (_, parser, handler) = self.commands.iter()
+            .find(|c| c.0 == name)
+            .ok_or(DispatchError::UnknownCommand(name))?;
+        
+        // The subcommand's name stands in for the program name
+        let sub_res = parser.parse(given.iter())?;
+        handler(&p_res, &sub_res).map_err(DispatchError::Handler)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    
+    use super::{DispatchError, Dispatcher};
+    use argparser::{ArgParser, ArgType};
+    
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|s| s.into()).collect()
+    }
+    
+    fn setup(ran: Rc<Cell<u32>>, verbose: Rc<Cell<bool>>) -> Dispatcher<String> {
+        let mut app = ArgParser::new("tool".into());
+        app.add_opt("verbose", Some("false"), 'v', false, "Verbose output", ArgType::Flag);
+        
+        let mut build = ArgParser::new("tool build".into());
+        build.add_opt("jobs", Some("1"), 'j', false, "Jobs to run at once", ArgType::Option);
+        let mut clean = ArgParser::new("tool clean".into());
+        clean.add_opt("all", Some("false"), 'a', false, "Remove everything", ArgType::Flag);
+        
+        let mut dispatcher = Dispatcher::new(app);
+        let counter = ran.clone();
+        dispatcher.command("build", build, move |global, p_res| {
+            counter.set(p_res.get("jobs").unwrap_or(0));
+            verbose.set(global.get("verbose").unwrap_or(false));
+            Ok(())
+        });
+        dispatcher.command("clean", clean, |_, p_res| match p_res.get("all") {
+            Some(true) => Err("refusing to remove everything".to_string()),
+            _ => Ok(()),
+        });
+        dispatcher
+    }
+    
+    #[test]
+    fn test_dispatch() {
+        let ran = Rc::new(Cell::new(0));
+        let verbose = Rc::new(Cell::new(false));
+        let dispatcher = setup(ran.clone(), verbose.clone());
+        
+        assert_eq!(dispatcher.commands(), ["build", "clean"]);
+        assert!(dispatcher.run(args("./tool -v build -j 4").iter()).is_ok());
+        assert_eq!(ran.get(), 4);
+        assert!(verbose.get());
+        assert!(dispatcher.run(args("./tool build").iter()).is_ok());
+        assert!(!verbose.get());
+        assert!(dispatcher.run(args("./tool clean").iter()).is_ok());
+        
+        match dispatcher.run(args("./tool clean --all").iter()) {
+            Err(DispatchError::Handler(e)) => assert_eq!(e, "refusing to remove everything"),
+            other => panic!("unexpected {:?}", other),
+        }
+        match dispatcher.run(args("./tool test").iter()) {
+            Err(e @ DispatchError::UnknownCommand(_)) => assert_eq!(e.to_string(), "Unknown command `test`"),
+            other => panic!("unexpected {:?}", other),
+        }
+        match dispatcher.run(args("./tool build -j").iter()) {
+            Err(DispatchError::Parse(e)) => assert!(e.message().contains("`jobs`")),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(dispatcher.run(args("./tool -v").iter()).is_err());
+        assert!(!dispatcher.parser().help_text().contains("--command"));
+    }
+}
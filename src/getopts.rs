@@ -0,0 +1,277 @@
+//! This module defines an adapter with the interface of the `getopts`
+//! crate, `Options` and `Matches`, so that code written against it can
+//! switch to this parser without a rewrite. Options are looked up by
+//! either their short or their long name, as in `getopts`.
+//!
+//! # Example
+//! ```
+//! use argparse::getopts::Options;
+//!
+//! let mut opts = Options::new();
+//! opts.optopt("o", "output", "Where to write", "FILE");
+//! opts.optflag("v", "verbose", "Say what is done");
+//! opts.optmulti("I", "include", "Where to look for headers", "DIR");
+//!
+//! let matches = opts.parse(&["-o", "out.txt", "-I", "a", "--include", "b", "main.c"]).unwrap();
+//! assert_eq!(matches.opt_str("output"), Some("out.txt".into()));
+//! assert!(!matches.opt_present("v"));
+//! assert_eq!(matches.opt_strs("I"), ["a", "b"]);
+//! assert_eq!(matches.free, ["main.c"]);
+//! ```
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use argparser::{ArgParseResults, ArgParser, ArgType, ListValues, ParseError, ParseEvent};
+
+/// Why parsing failed, in place of the `getopts` enum of that name
+pub type Fail = ParseError;
+
+/// What kind of option one of `Options`' methods adds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Flag,
+    FlagMulti,
+    Opt,
+    Multi,
+}
+
+#[derive(Debug, Clone)]
+struct Opt {
+    short: String,
+    long: String,
+    desc: String,
+    kind: Kind,
+    required: bool,
+}
+
+impl Opt {
+    /// Its name in `ArgParser`, the long name if it has one
+    fn name(&self) -> &str {
+        if self.long.is_empty() { &self.short } else { &self.long }
+    }
+}
+
+/// A set of options to parse, as in `getopts`. The value hints of
+/// `optopt` and the like are accepted for compatibility, but help shows
+/// the names of options instead.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    opts: Vec<Opt>,
+}
+
+impl Options {
+    /// Creates an empty set of options
+    pub fn new() -> Options {
+        Options::default()
+    }
+    
+    fn add(&mut self, short: &str, long: &str, desc: &str, kind: Kind, required: bool) -> &mut Options {
+        self.opts.push(Opt { short: short.into(), long: long.into(), desc: desc.into(), kind, required });
+        self
+    }
+    
+    /// Adds a flag, which takes no value and may be given once
+    pub fn optflag(&mut self, short: &str, long: &str, desc: &str) -> &mut Options {
+        self.add(short, long, desc, Kind::Flag, false)
+    }
+    
+    /// Adds a flag that may be given several times, see `opt_count`
+    pub fn optflagmulti(&mut self, short: &str, long: &str, desc: &str) -> &mut Options {
+        self.add(short, long, desc, Kind::FlagMulti, false)
+    }
+    
+    /// Adds an option taking a value, which may be left out
+    pub fn optopt(&mut self, short: &str, long: &str, desc: &str, _hint: &str) -> &mut Options {
+        self.add(short, long, desc, Kind::Opt, false)
+    }
+    
+    /// Adds an option taking a value, which must be given
+    pub fn reqopt(&mut self, short: &str, long: &str, desc: &str, _hint: &str) -> &mut Options {
+        self.add(short, long, desc, Kind::Opt, true)
+    }
+    
+    /// Adds an option taking a value that may be given several times,
+    /// see `opt_strs`
+    pub fn optmulti(&mut self, short: &str, long: &str, desc: &str, _hint: &str) -> &mut Options {
+        self.add(short, long, desc, Kind::Multi, false)
+    }
+    
+    /// Builds the `ArgParser` the options are parsed with. Options with
+    /// no short name have no short flag there either.
+    pub fn parser(&self) -> ArgParser {
+        let mut parser = ArgParser::new(String::new());
+        
+        for opt in self.opts.iter() {
+            let flag = opt.short.chars().next().unwrap_or('\0');
+            
+            let type_ = match opt.kind {
+                Kind::Flag => ArgType::Flag,
+                Kind::FlagMulti => ArgType::Counter,
+                Kind::Opt => ArgType::Option,
+                Kind::Multi => ArgType::List,
+            };
+            parser.add_opt(opt.name(), None, flag, opt.required, &opt.desc, type_);
+            
+            if opt.kind == Kind::Multi {
+                let _ = parser.list_values(opt.name(), ListValues::AtMost(1));
+            }
+        }
+        
+        parser
+    }
+    
+    /// Parses the arguments, which unlike with `ArgParser::parse` don't
+    /// start with the program name
+    pub fn parse<S: AsRef<str>>(&self, args: &[S]) -> Result<Matches, Fail> {
+        let args: Vec<String> = Some(String::new()).into_iter()
+            .chain(args.iter().map(|s| s.as_ref().into()))
+            .collect();
+        let parser = self.parser();
+        let results = parser.parse(args.iter())?;
+        
+        let mut names = HashMap::new();
+        for opt in self.opts.iter() {
+            for key in [&opt.short, &opt.long] {
+                if !key.is_empty() {
+                    names.insert(key.clone(), opt.name().to_string());
+                }
+            }
+        }
+        
+        Ok(Matches {
+            free: parser.parse_iter(&args)
+                .filter_map(|event| match event {
+                    ParseEvent::PositionalFound { value, .. } => Some(value.to_string()),
+                    _ => None,
+                })
+                .collect(),
+            counts: results.iter().map(|(name, _, count)| (name.to_string(), count as usize)).collect(),
+            names,
+            results,
+        })
+    }
+    
+    /// Help for the options, after `brief`, which is typically a usage
+    /// line
+    pub fn usage(&self, brief: &str) -> String {
+        let help = self.parser().help_text();
+        let options = help.find("Options:").map(|at| &help[at..]).unwrap_or("");
+        
+        format!("{}\n\n{}", brief, options)
+    }
+}
+
+/// The options found by `Options::parse`
+#[derive(Debug, Clone)]
+pub struct Matches {
+    /// The arguments that are not options or their values
+    pub free: Vec<String>,
+    counts: HashMap<String, usize>,
+    names: HashMap<String, String>,
+    results: ArgParseResults,
+}
+
+impl Matches {
+    fn name<'a>(&'a self, nm: &'a str) -> &'a str {
+        self.names.get(nm).map(|s| s.as_str()).unwrap_or(nm)
+    }
+    
+    /// Whether an option was given
+    pub fn opt_present(&self, nm: &str) -> bool {
+        self.opt_count(nm) > 0
+    }
+    
+    /// Whether any of the options was given
+    pub fn opts_present(&self, names: &[String]) -> bool {
+        names.iter().any(|nm| self.opt_present(nm))
+    }
+    
+    /// How many times an option was given
+    pub fn opt_count(&self, nm: &str) -> usize {
+        self.counts.get(self.name(nm)).cloned().unwrap_or(0)
+    }
+    
+    /// The first value of an option, if it was given
+    pub fn opt_str(&self, nm: &str) -> Option<String> {
+        self.opt_strs(nm).into_iter().next()
+    }
+    
+    /// Every value given for an option
+    pub fn opt_strs(&self, nm: &str) -> Vec<String> {
+        self.results.values(self.name(nm)).map(|v| v.to_vec()).unwrap_or_default()
+    }
+    
+    /// The first value of an option parsed with `FromStr`, if it was given
+    pub fn opt_get<T: FromStr>(&self, nm: &str) -> Result<Option<T>, T::Err> {
+        self.opt_str(nm).map(|s| s.parse()).transpose()
+    }
+    
+    /// The first value of an option parsed with `FromStr`, or `def` if
+    /// it wasn't given
+    pub fn opt_get_default<T: FromStr>(&self, nm: &str, def: T) -> Result<T, T::Err> {
+        self.opt_get(nm).map(|v| v.unwrap_or(def))
+    }
+    
+    /// The results of the `ArgParser`, whose names are the long names
+    /// of options where they have one
+    pub fn results(&self) -> &ArgParseResults {
+        &self.results
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Options;
+    
+    fn setup() -> Options {
+        let mut opts = Options::new();
+        opts.optflag("h", "help", "Print this help menu")
+            .optflagmulti("v", "", "Verbosity")
+            .optopt("", "jobs", "Jobs to run at once", "N")
+            .reqopt("o", "output", "Where to write", "FILE")
+            .optmulti("D", "define", "Definitions", "NAME");
+        opts
+    }
+    
+    #[test]
+    fn test_matches() {
+        let opts = setup();
+        
+        let m = opts.parse(&["-vvv", "a.c", "--jobs", "4", "-o", "a.out", "-D", "X", "b.c", "-D", "Y"]).unwrap();
+        assert!(!m.opt_present("help"));
+        assert!(m.opt_present("o"));
+        assert_eq!(m.opt_count("v"), 3);
+        assert_eq!(m.opt_get::<u32>("jobs"), Ok(Some(4)));
+        assert_eq!(m.opt_get_default("jobs", 1), Ok(4));
+        assert_eq!(m.opt_str("output"), Some("a.out".into()));
+        assert_eq!(m.opt_strs("D"), ["X", "Y"]);
+        assert_eq!(m.free, ["a.c", "b.c"]);
+        assert!(m.opts_present(&["x".into(), "h".into(), "D".into()]));
+        
+        let m = opts.parse(&["-h", "-o", "x"]).unwrap();
+        assert!(m.opt_present("h"));
+        assert_eq!(m.opt_count("v"), 0);
+        assert_eq!(m.opt_get::<u32>("jobs"), Ok(None));
+        assert!(m.opt_strs("define").is_empty());
+        assert!(m.free.is_empty());
+        
+        let m = opts.parse(&["-o", "x", "-j", "4", "a.c", "--", "-v"]).unwrap();
+        assert_eq!(m.opt_get::<u32>("jobs"), Ok(None));
+        assert_eq!(m.opt_count("v"), 0);
+        assert_eq!(m.free, ["4", "a.c", "-v"]);
+        
+        assert!(opts.parse(&["-v"]).is_err());
+        assert!(opts.parse(&["-o", "x", "--jobs", "many"]).unwrap().opt_get::<u32>("jobs").is_err());
+    }
+    
+    #[test]
+    fn test_usage() {
+        let usage = setup().usage("Usage: cc [options] FILE...");
+        
+        assert!(usage.starts_with("Usage: cc [options] FILE...\n\nOptions:"));
+        assert!(usage.contains("--jobs "));
+        assert!(!usage.contains("(-j)"));
+        assert!(!usage.contains("(-\0)"));
+    }
+}
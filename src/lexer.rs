@@ -0,0 +1,373 @@
+//! This module defines the lexer that splits a command line into the
+//! tokens the parser works with. It is public so that the same rules
+//! can be reused, e.g. to pre-process arguments before handing them to
+//! an `ArgParser`.
+
+use std::borrow::Cow;
+use std::str::Chars;
+
+/// The name of a flag, without its leading dashes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag<'a> {
+    /// A short flag, as in `-v`
+    Short(char),
+    /// A long flag, as in `--verbose`
+    Long(&'a str),
+}
+
+/// A single token of a command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// A short flag, as in `-v`. Combined short flags (`-abc`) produce
+    /// one token each.
+    ShortFlag(char),
+    /// A long flag, without its dashes, as in `--verbose`
+    LongFlag(&'a str),
+    /// Anything that isn't a flag
+    Value(&'a str),
+    /// A lone `--`, after which every argument is a `Value`
+    Terminator,
+    /// A flag with its value attached by `=`, as in `--name=Johnny` or
+    /// `-n=Johnny`. The value is never mistaken for a flag.
+    Attached {
+        /// The flag the value belongs to
+        flag: Flag<'a>,
+        /// Everything after the `=`
+        value: &'a str,
+    },
+}
+
+/// Iterator over the tokens of a command line. Each token comes with
+/// the index of the argument it was found in, since combined short
+/// flags produce several tokens from one argument.
+///
+/// # Example
+/// ```
+/// use argparse::lexer::{Flag, Lexer, Token};
+///
+/// let args = ["-vx", "--name=Johnny", "-60", "--", "-f"];
+/// let tokens: Vec<_> = Lexer::new(&args).collect();
+///
+/// assert_eq!(tokens, vec![
+///     (0, Token::ShortFlag('v')),
+///     (0, Token::ShortFlag('x')),
+///     (1, Token::Attached { flag: Flag::Long("name"), value: "Johnny" }),
+///     (2, Token::Value("-60")),
+///     (3, Token::Terminator),
+///     (4, Token::Value("-f")),
+/// ]);
+/// ```
+#[derive(Debug)]
+pub struct Lexer<'a, S: 'a> {
+    args: &'a [S],
+    pos: usize,
+    shorts: Option<(usize, Chars<'a>)>,
+    terminated: bool,
+    numbers_as_flags: bool,
+}
+
+impl<'a, S: AsRef<str>> Lexer<'a, S> {
+    /// Creates a lexer over the given arguments
+    pub fn new(args: &'a [S]) -> Lexer<'a, S> {
+        Lexer { args, pos: 0, shorts: None, terminated: false, numbers_as_flags: false }
+    }
+    
+    /// Sets whether negative numbers, such as `-60`, are short flags
+    /// (`-6` and `-0`) rather than values, which they are by default
+    pub fn numbers_as_flags(mut self, yes: bool) -> Lexer<'a, S> {
+        self.numbers_as_flags = yes;
+        self
+    }
+}
+
+impl<'a, S: AsRef<str>> Iterator for Lexer<'a, S> {
+    type Item = (usize, Token<'a>);
+
+    fn next(&mut self) -> Option<(usize, Token<'a>)> {
+        if let Some((i, ref mut chars)) = self.shorts {
+            if let Some(c) = chars.next() {
+                let rest = chars.as_str();
+
+                if let Some(value) = rest.strip_prefix('=') {
+                    self.shorts = None;
+                    return Some((i, Token::Attached { flag: Flag::Short(c), value }));
+                }
+
+                return Some((i, Token::ShortFlag(c)));
+            }
+
+            self.shorts = None;
+        }
+
+        let i = self.pos;
+        let arg = self.args.get(i)?.as_ref();
+        self.pos += 1;
+
+        if self.terminated {
+            return Some((i, Token::Value(arg)));
+        }
+
+        if arg == "--" {
+            self.terminated = true;
+            return Some((i, Token::Terminator));
+        }
+
+        let (flag, value) = match arg.find('=') {
+            Some(eq) => (&arg[..eq], Some(&arg[eq + 1..])),
+            None => (arg, None),
+        };
+
+        if is_long_flag(flag) {
+            let name = &flag[2..];
+
+            return Some((i, match value {
+                Some(value) => Token::Attached { flag: Flag::Long(name), value },
+                None => Token::LongFlag(name),
+            }));
+        }
+
+        if is_flag(flag) || (self.numbers_as_flags && is_negative_number(flag)) {
+            self.shorts = Some((i, arg[1..].chars()));
+            return self.next();
+        }
+
+        Some((i, Token::Value(arg)))
+    }
+}
+
+/// Splits a line into arguments the way a shell would, for parsing
+/// lines that didn't come from one. Arguments are separated by
+/// whitespace; single quotes keep everything up to the next single
+/// quote as it is, double quotes keep whitespace and allow `\"` and
+/// `\\` inside, and a backslash outside quotes escapes the character
+/// after it. A quote left open runs to the end of the line.
+///
+/// # Example
+/// ```
+/// use argparse::lexer::split_line;
+///
+/// assert_eq!(split_line(r#"--name "Johnny Smith" -t 'a b' c\ d"#),
+///     vec!["--name", "Johnny Smith", "-t", "a b", "c d"]);
+/// ```
+pub fn split_line(line: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut arg: Option<String> = None;
+    let mut chars = line.chars();
+    
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => args.extend(arg.take()),
+            '\'' => {
+                let word = arg.get_or_insert_with(String::new);
+                word.extend(chars.by_ref().take_while(|&c| c != '\''));
+            }
+            '"' => {
+                let word = arg.get_or_insert_with(String::new);
+                
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some(c @ '"') | Some(c @ '\\') => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => word.push('\\'),
+                        },
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                let word = arg.get_or_insert_with(String::new);
+                word.push(chars.next().unwrap_or('\\'));
+            }
+            c => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+    
+    args.extend(arg);
+    args
+}
+
+/// Removes the quotes from an argument that kept them, as arguments
+/// from config files, response files or Windows-style command lines
+/// can. Quotes may be anywhere in the argument, as in `--name="a b"`.
+/// Inside double quotes, `\"` and `\\` stand for `"` and `\`; single
+/// quotes keep everything up to the next single quote as it is.
+/// Backslashes outside quotes are kept, so Windows paths survive. A
+/// quote left open runs to the end of the argument. Arguments without
+/// quotes are returned as they are.
+///
+/// # Example
+/// ```
+/// use argparse::lexer::unquote;
+///
+/// assert_eq!(unquote(r#"--name="Johnny \"J\" Smith""#), r#"--name=Johnny "J" Smith"#);
+/// assert_eq!(unquote(r#"'C:\Program Files'"#), r"C:\Program Files");
+/// assert_eq!(unquote(r"C:\Users"), r"C:\Users");
+/// ```
+pub fn unquote(arg: &str) -> Cow<'_, str> {
+    if !arg.contains(['"', '\'']) {
+        return Cow::Borrowed(arg);
+    }
+    
+    let mut out = String::with_capacity(arg.len());
+    let mut chars = arg.chars();
+    
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => out.extend(chars.by_ref().take_while(|&c| c != '\'')),
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some(c @ '"') | Some(c @ '\\') => out.push(c),
+                            Some(c) => {
+                                out.push('\\');
+                                out.push(c);
+                            }
+                            None => out.push('\\'),
+                        },
+                        c => out.push(c),
+                    }
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    
+    Cow::Owned(out)
+}
+
+/// Whether an argument is a short flag, or several combined. Any Unicode
+/// scalar value can be a short flag, except those that would make the
+/// argument a negative number (`-60`, `-.5`). Arguments containing
+/// whitespace can only have been quoted on the command line, so they
+/// are values even if they start with a dash.
+pub fn is_flag(s: &str) -> bool {
+    let mut chars = s.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some('-'), Some(c)) => {
+            let number = c.is_ascii_digit() || c == '.';
+
+            !(number || c == '-' || s.contains(char::is_whitespace))
+        }
+        _ => false,
+    }
+}
+
+/// Whether an argument is a negative number, as in `-60` or
+/// `-6001.45e-2`, which `is_flag` doesn't count as a flag. Only the
+/// start is looked at: a dash then a digit or `.`.
+pub fn is_negative_number(s: &str) -> bool {
+    let mut chars = s.chars();
+    
+    match (chars.next(), chars.next()) {
+        (Some('-'), Some(c)) => (c.is_ascii_digit() || c == '.') && !s.contains(char::is_whitespace),
+        _ => false,
+    }
+}
+
+/// Whether an argument is a long flag, as in `--verbose`
+pub fn is_long_flag(s: &str) -> bool {
+    s.len() > 2 && s.starts_with("--") && !s.contains(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Flag, Lexer, Token, is_flag, is_long_flag, is_negative_number, split_line, unquote};
+
+    #[test]
+    fn test_classification() {
+        for flag in &["-a", "-abc", "-é", "-🦀"] {
+            assert!(is_flag(flag), "{}", flag);
+        }
+        for not_flag in &["", "-", "a", "-1", "-60", "-.5", "--", "--a", "-a b", "é"] {
+            assert!(!is_flag(not_flag), "{}", not_flag);
+        }
+
+        for flag in &["--a", "--name", "--été"] {
+            assert!(is_long_flag(flag), "{}", flag);
+        }
+        for not_flag in &["", "-", "--", "-a", "--a b", "a--"] {
+            assert!(!is_long_flag(not_flag), "{}", not_flag);
+        }
+    }
+
+    #[test]
+    fn test_attached() {
+        let args = ["--name=Johnny Smith", "-n=-5", "-ab=c", "--empty=", "x=y"];
+        let tokens: Vec<_> = Lexer::new(&args).collect();
+
+        assert_eq!(tokens, vec![
+            (0, Token::Attached { flag: Flag::Long("name"), value: "Johnny Smith" }),
+            (1, Token::Attached { flag: Flag::Short('n'), value: "-5" }),
+            (2, Token::ShortFlag('a')),
+            (2, Token::Attached { flag: Flag::Short('b'), value: "c" }),
+            (3, Token::Attached { flag: Flag::Long("empty"), value: "" }),
+            (4, Token::Value("x=y")),
+        ]);
+    }
+
+    #[test]
+    fn test_numbers_as_flags() {
+        for number in &["-1", "-60", "-.5", "-6001.45e-2"] {
+            assert!(is_negative_number(number), "{}", number);
+        }
+        for not_number in &["", "-", "1", "-a", "--1", "-1 2"] {
+            assert!(!is_negative_number(not_number), "{}", not_number);
+        }
+        
+        let args = ["-60", "-1=x", "--", "-2"];
+        let tokens: Vec<_> = Lexer::new(&args).numbers_as_flags(true).map(|(_, t)| t).collect();
+        
+        assert_eq!(tokens, vec![
+            Token::ShortFlag('6'),
+            Token::ShortFlag('0'),
+            Token::Attached { flag: Flag::Short('1'), value: "x" },
+            Token::Terminator,
+            Token::Value("-2"),
+        ]);
+    }
+    
+    #[test]
+    fn test_split_line() {
+        let empty: Vec<String> = Vec::new();
+        assert_eq!(split_line(""), empty);
+        assert_eq!(split_line("  \t "), empty);
+        assert_eq!(split_line(" -a  b\tc "), vec!["-a", "b", "c"]);
+        assert_eq!(split_line(r#"--x="a b"c '' """#), vec!["--x=a bc", "", ""]);
+        assert_eq!(split_line(r#""say \"hi\" \n" 'it\'s'"#), vec![r#"say "hi" \n"#, r"it\s"]);
+        assert_eq!(split_line(r"a\ b c\"), vec!["a b", "c\\"]);
+        assert_eq!(split_line("'open ended"), vec!["open ended"]);
+    }
+    
+    #[test]
+    fn test_unquote() {
+        assert_eq!(unquote("plain"), "plain");
+        assert_eq!(unquote(r#""a b""#), "a b");
+        assert_eq!(unquote(r#"-n="x"'y'z"#), "-n=xyz");
+        assert_eq!(unquote(r#""\"\\\n""#), r#""\\n"#);
+        assert_eq!(unquote(r#"'it''s'"#), "its");
+        assert_eq!(unquote(r#""open \"#), r"open \");
+        assert_eq!(unquote("''"), "");
+    }
+    
+    #[test]
+    fn test_terminator() {
+        let args = ["-a", "--", "--", "-b", "--c"];
+        let tokens: Vec<_> = Lexer::new(&args).map(|(_, t)| t).collect();
+
+        assert_eq!(tokens, vec![
+            Token::ShortFlag('a'),
+            Token::Terminator,
+            Token::Value("--"),
+            Token::Value("-b"),
+            Token::Value("--c"),
+        ]);
+    }
+}
@@ -7,12 +7,25 @@
 //! Simple class to parse arguments. The Highlights are:
 //!  * A configurable `add_opt` method, to tell it what to look for.
 //!  * A generic `get` method, which will return the argument you want with any type that implements `FromStr`.
+//!  * `List` and `Dict` values are kept exactly as given, so quoted values containing spaces survive `get_list` and `get_dict`.
 //!  * The arguments are stored in maps, and so are accessed by the name you give them in the `add_opt` method.
 //!  * The `parse` method can be called on an `Iterator` that produces `&String`s.
 //!  * There are no static or global variables so you can have as many parsers as you want.
 //!  * The parser can take any values that implement `FromStr` or for which you can provide a closure to parse them from String
 //!  * You can specify if any argument is required or not, as well as default values for all
 //!  * It also prints a default help message, similar to the one Python's argparser prints
+//!  * `markdown_usage` renders the same usage and options as Markdown, to keep a README in step with the code
+//!  * `app::App` runs a program around its parser, handling `--help`, `--version`, errors and exit codes
+//!  * The `dispatch` module routes subcommands, as in `git commit`, to their own parsers and handlers
+//!  * The `segments` module splits one command line at a sentinel like `++` and parses each part with its own
+//!    parser, for launchers that pass arguments on to what they run
+//!  * `bash_completion` generates a completion script, which offers the declared `choices` of each option
+//!  * The `python` module has a facade shaped like Python's `argparse`, for porting Python programs, and the
+//!    `getopts` module one shaped like the `getopts` crate
+//!  * Parse errors point at the offending argument, optionally in color, much like `rustc` diagnostics
+//!  * All user-facing strings come from a `Messages` catalog that can be replaced for other languages
+//!  * Values can be attached with `=` (`--name=Johnny`, `-l=-60`), which makes them values even when they look
+//!    like flags (`--offset=-x`), and everything after `--` is positional
 //! 
 //! Example use:
 //! 
@@ -22,7 +35,7 @@
 //! use std::collections::HashMap;
 //! 
 //! use argparse::{ArgParser, ArgType, hashmap_parser, vec_parser};
-//! const LONG_STR: &'static str = r#"Check your proxy settings or contact your network administrator to make sure the proxy server is working. If you don't believe you should be using a proxy server: Go to the Chromium menu > Settings > Show advanced settings... > Change proxy settings... and make sure your configuration is set to "no proxy" or "direct.""#;
+//! const LONG_STR: &str = r#"Check your proxy settings or contact your network administrator to make sure the proxy server is working. If you don't believe you should be using a proxy server: Go to the Chromium menu > Settings > Show advanced settings... > Change proxy settings... and make sure your configuration is set to "no proxy" or "direct.""#;
 //! 
 //! fn main() {
 //!     let mut parser = ArgParser::new("argparse".into());
@@ -58,6 +71,7 @@
 //!         Some(vec![1,2,3,4,5]));
 //!     assert_eq!(p_res.get_with("frequencies", vec_parser), 
 //!         Some(vec![1,2,3,4,5]));
+//!     assert_eq!(p_res.get_list("frequencies"), Some(vec![1,2,3,4,5]));
 //!     assert_eq!(p_res.get("mao"), Some(true));
 //!     
 //!     let h = [("Monday", true), ("Friday", false)]
@@ -71,10 +85,68 @@
 //!     parser.help();
 //! }
 //! ```
+//!
+//! Optional features:
+//!
+//!  * `unicode-width` wraps help text by display width, so that wide (e.g. CJK) characters line up
+//!  * `url` adds `url_parser` and URL validators, returning the `url` crate's `Url`
+//!  * `chrono` adds parsers for dates (including relative ones like `yesterday`) and times, returning `chrono` types
+//!  * `glob` adds `ArgParser::expand_globs`, expanding patterns like `*.csv` in `List` values where the shell didn't
+//!  * `json` checks the values of `ArgType::Json` arguments, which otherwise fail to parse, and reads them with
+//!    `get_json`, and adds `ArgParser::json_schema` for editors to check config files mirroring the arguments
+//!  * `clap` adds `ArgParser::to_clap`, building an equivalent `clap::Command`
+//!  * `rustc-hash` looks arguments up with the faster, non-randomized hasher of the `rustc-hash` crate, for
+//!    programs with hundreds of options that are parsed where latency matters
+//!  * `terminal_size` asks the terminal how many lines it has when deciding whether help needs a pager, instead of
+//!    relying on the `LINES` variable, which most shells don't export
 #![warn(missing_docs)]
 
+#[cfg(feature = "unicode-width")]
+extern crate unicode_width;
+#[cfg(feature = "url")]
+extern crate url;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "glob")]
+extern crate glob;
+#[cfg(feature = "json")]
+extern crate serde;
+#[cfg(feature = "json")]
+extern crate serde_json;
+#[cfg(feature = "clap")]
+extern crate clap;
+#[cfg(feature = "rustc-hash")]
+extern crate rustc_hash;
+#[cfg(feature = "terminal_size")]
+extern crate terminal_size;
+
+#[macro_use]
+mod macros;
+
+pub mod app;
 pub mod argparser;
+pub mod color;
+pub mod dispatch;
+pub mod getopts;
+pub mod lexer;
+pub mod messages;
+pub mod python;
+pub mod segments;
 pub mod slide;
+pub mod testing;
 
-pub use argparser::{ArgParser, ArgParseResults, ParseResult,
-    ArgType, ArgGetter, hashmap_parser, vec_parser};
\ No newline at end of file
+pub use argparser::{ArgParser, SharedParser, ArgParseResults, BorrowedResults, ParseResult, ParseError,
+    ArgType, ArgGetter, FromRadix, DictNode, DuplicateKeys, PathOptions, ResultsIter, ArgDiff, ArgMut, ParseLines, ParseEvent, ParseEvents, ConfigError, GetError, Warning,
+    ParseOptions, Inherit, TooMany, ListValues, NegativeNumbers, KebabCase,
+    Interpolate};
+pub use argparser::{vec_parser, try_vec_parser, lenient_vec_parser, int_parser, bool_parser, hashset_parser, hashmap_parser, try_hashmap_parser, btreemap_parser,
+    multimap_parser, ordered_dict_parser, tuple_parser, triple_parser, duration_parser, bytesize_parser, percentage_parser, host_port_parser,
+    cidr_parser};
+pub use argparser::{percentage_validator, ip_validator, socket_addr_validator, host_port_validator, cidr_validator};
+#[cfg(feature = "url")]
+pub use argparser::{url_parser, url_validator, url_scheme_validator};
+#[cfg(feature = "chrono")]
+pub use argparser::{date_parser, time_parser, datetime_parser, naive_datetime_parser, date_validator,
+    datetime_validator};
+pub use color::{ColorChoice, Rgb, rgb_parser, rgb_validator};
+pub use messages::Messages;
\ No newline at end of file
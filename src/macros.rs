@@ -0,0 +1,67 @@
+//! This module defines macros for testing the definitions of a parser
+//! against example command lines, as read by `ArgParser::parse_line`.
+
+/// Asserts that a parser accepts a line of arguments, evaluating to the
+/// results. The line doesn't start with the program name.
+///
+/// # Example
+/// ```
+/// #[macro_use]
+/// extern crate argparse;
+///
+/// use argparse::{ArgParser, ArgType};
+///
+/// fn main() {
+///     let mut parser = ArgParser::new("runner".into());
+///     parser.add_opt("name", None, 'n', true,
+///         "Name of user", ArgType::Option);
+///
+///     let p_res = assert_parse_ok!(parser, "--name Johnny");
+///     assert_eq!(p_res.get::<String>("name"), Some("Johnny".into()));
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_parse_ok {
+    ($parser:expr, $line:expr) => {
+        match $parser.parse_line($line) {
+            Ok(results) => results,
+            Err(e) => panic!("expected `{}` to parse, but it failed with: {}", $line, e),
+        }
+    };
+}
+
+/// Asserts that a parser rejects a line of arguments, optionally with
+/// an error message containing the given text. The line doesn't start
+/// with the program name.
+///
+/// # Example
+/// ```
+/// #[macro_use]
+/// extern crate argparse;
+///
+/// use argparse::{ArgParser, ArgType};
+///
+/// fn main() {
+///     let mut parser = ArgParser::new("runner".into());
+///     parser.add_opt("name", None, 'n', true,
+///         "Name of user", ArgType::Option);
+///
+///     assert_parse_err!(parser, "--name");
+///     assert_parse_err!(parser, "--help", "required");
+/// }
+/// ```
+#[macro_export]
+macro_rules! assert_parse_err {
+    ($parser:expr, $line:expr) => {
+        if $parser.parse_line($line).is_ok() {
+            panic!("expected `{}` to fail to parse, but it parsed", $line);
+        }
+    };
+    ($parser:expr, $line:expr, $needle:expr) => {
+        match $parser.parse_line($line) {
+            Ok(_) => panic!("expected `{}` to fail to parse, but it parsed", $line),
+            Err(e) => assert!(e.message().contains($needle),
+                "expected the error for `{}` to contain `{}`, but it was: {}", $line, $needle, e),
+        }
+    };
+}
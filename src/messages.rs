@@ -0,0 +1,126 @@
+//! This module defines the catalog of user-facing strings used in help
+//! and error output, so that applications can supply their own
+//! translations in place of the built-in English ones.
+
+use std::borrow::Cow;
+
+/// The set of user-facing strings a parser uses. Error templates may
+/// contain a `{name}` placeholder, which is replaced with the name of
+/// the argument concerned, and some a `{value}` placeholder for the
+/// value at fault or a `{reason}` one explaining what is wrong with it.
+/// The built-in strings are borrowed rather than allocated, so that a
+/// parser costs nothing for help it may never show.
+///
+/// # Example
+/// ```
+/// use argparse::{ArgParser, Messages};
+///
+/// let mut parser = ArgParser::new("lanceur".into());
+/// parser.messages(Messages {
+///     usage: "Utilisation :".into(),
+///     options: "Options :".into(),
+///     help: "Afficher ce message d'aide".into(),
+///     missing_required: "Certains arguments obligatoires sont absents".into(),
+///     ..Messages::default()
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Messages {
+    /// Heading of the usage line
+    pub usage: Cow<'static, str>,
+    /// Heading of the options section of help
+    pub options: Cow<'static, str>,
+    /// Heading of the examples section of help
+    pub examples: Cow<'static, str>,
+    /// Label preceding whether an option is required
+    pub required: Cow<'static, str>,
+    /// Label preceding the type of an option
+    pub type_: Cow<'static, str>,
+    /// Description of the built-in `--help` flag
+    pub help: Cow<'static, str>,
+    /// Description of the `--version` flag added by `App::version`
+    pub version: Cow<'static, str>,
+    /// Label that rendered errors start with
+    pub error: Cow<'static, str>,
+    /// Error when an option is given without the value it needs
+    pub missing_value: Cow<'static, str>,
+    /// Error when a `Dict` value is not a `key:value` pair, with the
+    /// `{value}` placeholder
+    pub malformed_pair: Cow<'static, str>,
+    /// Error when a value is rejected by the argument's validator, with
+    /// the `{value}` and `{reason}` placeholders
+    pub invalid_value: Cow<'static, str>,
+    /// Error when an argument can't be read at all, before it is known
+    /// which argument it is for, with the `{value}` and `{reason}`
+    /// placeholders
+    pub invalid_argument: Cow<'static, str>,
+    /// Error when a `Dict` key is given twice and the argument forbids
+    /// it, with the `{value}` placeholder for the key
+    pub duplicate_key: Cow<'static, str>,
+    /// Error when a flag no argument has is given and parsing is
+    /// strict, with `{name}` as the flag
+    pub unknown_flag: Cow<'static, str>,
+    /// Error when a flag matches several arguments, such as an
+    /// abbreviation of more than one long name, with the `{value}`
+    /// placeholder for the flags it could be
+    pub ambiguous_flag: Cow<'static, str>,
+    /// Error when an argument is given more often than
+    /// `max_occurrences` allows, with the `{value}` placeholder for how
+    /// often it may be
+    pub too_many: Cow<'static, str>,
+    /// Error when an argument marked with `exclusive` is given along
+    /// with others
+    pub exclusive: Cow<'static, str>,
+    /// Error when too few of a group set up with `require_at_least` were
+    /// given, with the `{min}` placeholder for how many are needed,
+    /// `{names}` for the group, `{count}` for how many were given and
+    /// `{given}` for a list of those in parentheses, if there are any
+    pub too_few_in_group: Cow<'static, str>,
+    /// Error when a required argument was not given
+    pub missing_required: Cow<'static, str>,
+    /// Error when there is nothing to parse
+    pub no_arguments: Cow<'static, str>,
+    /// Error when `parse_borrowed` or `parse_iter` is used on a parser
+    /// with `before_parse` or `after_parse` middleware, which they can't run
+    pub middleware: Cow<'static, str>,
+}
+
+impl Messages {
+    /// Fills in the `{name}` placeholder of one of the templates
+    pub fn fill(template: &str, name: &str) -> String {
+        template.replace("{name}", name)
+    }
+    
+    /// Fills in both the `{name}` and `{value}` placeholders of a template
+    pub fn fill_value(template: &str, name: &str, value: &str) -> String {
+        Messages::fill(template, name).replace("{value}", value)
+    }
+}
+
+impl Default for Messages {
+    fn default() -> Messages {
+        Messages {
+            usage: "Usage:".into(),
+            options: "Options:".into(),
+            examples: "Examples:".into(),
+            required: "Required:".into(),
+            type_: "Type:".into(),
+            help: "Show this help message".into(),
+            version: "Show the version and exit".into(),
+            error: "error".into(),
+            missing_value: "This option `{name}` requires a value you have not provided".into(),
+            malformed_pair: "The option `{name}` expects `key:value` pairs, but got `{value}`".into(),
+            invalid_value: "The value `{value}` is not valid for `{name}`: {reason}".into(),
+            invalid_argument: "The argument `{value}` is not valid: {reason}".into(),
+            duplicate_key: "The key `{value}` was given more than once for `{name}`".into(),
+            unknown_flag: "Unknown flag `{name}`".into(),
+            ambiguous_flag: "The flag `{name}` is ambiguous, it could be {value}".into(),
+            too_many: "The option `{name}` can be given at most {value} times".into(),
+            exclusive: "The argument `{name}` must be the only argument given".into(),
+            too_few_in_group: "At least {min} of {names} must be given, but got {count}{given}".into(),
+            missing_required: "Not all required arguments are found".into(),
+            no_arguments: "No arguments given to parse".into(),
+            middleware: "This parser has middleware, which only `parse` and the methods built on it run".into(),
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! This module defines a facade in the shape of Python's `argparse`,
+//! with `add_argument`, `set_defaults` and `parse_args`, for porting
+//! Python programs without rethinking each argument. It builds an
+//! ordinary `ArgParser`, so whatever the facade lacks can be set up on
+//! the parser returned by `ArgumentParser::parser` instead.
+//!
+//! # Example
+//! ```
+//! use argparse::python::{Action, Argument, ArgumentParser, Nargs};
+//!
+//! let mut parser = ArgumentParser::new("copy");
+//! parser.add_argument(&["-v", "--verbose"], Argument {
+//!     action: Action::StoreTrue,
+//!     help: "Say what is copied".into(),
+//!     ..Argument::default()
+//! }).unwrap();
+//! parser.add_argument(&["--dry-run"], Argument {
+//!     action: Action::StoreTrue,
+//!     ..Argument::default()
+//! }).unwrap();
+//! parser.add_argument(&["files"], Argument {
+//!     nargs: Nargs::OneOrMore,
+//!     ..Argument::default()
+//! }).unwrap();
+//!
+//! let args = parser.parse_args(&["./copy", "--dry-run", "a.txt", "b.txt"]).unwrap();
+//! assert_eq!(args.get("verbose"), Some(false));
+//! assert_eq!(args.get("dry_run"), Some(true));
+//! assert_eq!(args.get_list::<String>("files"), Some(vec!["a.txt".into(), "b.txt".into()]));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use argparser::{ArgParseResults, ArgParser, ArgType, ListValues, ParseError};
+
+/// What to do with an argument when it is given, as `action=` does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Action {
+    /// Store its value, `"store"`
+    #[default]
+    Store,
+    /// Store `true`, and `false` when it is not given, `"store_true"`
+    StoreTrue,
+    /// Store `false`, and `true` when it is not given, `"store_false"`
+    StoreFalse,
+    /// Collect the value of each time it is given, `"append"`
+    Append,
+    /// Count how often it is given, `"count"`
+    Count,
+}
+
+/// How many values an argument takes, as `nargs=` does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Nargs {
+    /// Exactly one, the default
+    #[default]
+    One,
+    /// One, which may be left out, `"?"`
+    Optional,
+    /// Any number, `"*"`
+    ZeroOrMore,
+    /// At least one, `"+"`
+    OneOrMore,
+    /// Up to the given number, for `nargs=N`. Fewer are not an error,
+    /// as `ArgParser` has no minimum number of values.
+    Exactly(usize),
+}
+
+/// The keyword arguments of `add_argument`. Set the ones needed and
+/// take the rest from `Argument::default()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Argument {
+    /// What to do when the argument is given
+    pub action: Action,
+    /// How many values the argument takes
+    pub nargs: Nargs,
+    /// The name to read the argument by, which by default is the first
+    /// long option with `-` turned into `_`, or else the first short
+    /// one or the name of a positional argument
+    pub dest: Option<String>,
+    /// The value when the argument is not given
+    pub default: Option<String>,
+    /// Whether an option must be given. Positional arguments are
+    /// required unless their `nargs` allows none.
+    pub required: bool,
+    /// The values allowed, if not any
+    pub choices: Vec<String>,
+    /// Help text for the argument
+    pub help: String,
+}
+
+/// A parser with the interface of Python's `argparse.ArgumentParser`
+#[derive(Debug, Clone)]
+pub struct ArgumentParser {
+    prog: String,
+    description: Option<String>,
+    args: Vec<(Vec<String>, Argument)>,
+    defaults: HashMap<String, String>,
+}
+
+impl ArgumentParser {
+    /// Creates a parser for the program `prog`
+    pub fn new(prog: &str) -> ArgumentParser {
+        ArgumentParser {
+            prog: prog.into(),
+            description: None,
+            args: Vec::new(),
+            defaults: HashMap::new(),
+        }
+    }
+    
+    /// Set a description of the program, shown at the top of help
+    pub fn description(&mut self, text: &str) {
+        self.description = Some(text.into());
+    }
+    
+    /// Adds an argument, given either option strings such as `-v` and
+    /// `--verbose` or the name of a positional argument. The flags are
+    /// those of the argument's name in `ArgParser`, so of further long
+    /// options only the first is used. Options with no short one have
+    /// no short flag.
+    pub fn add_argument(&mut self, names: &[&str], arg: Argument) -> Result<(), &'static str> {
+        let positional = names.iter().filter(|n| !n.starts_with('-')).count();
+        
+        match (names.len(), positional) {
+            (0, _) => Err("No names given"),
+            (1, 1) | (_, 0) => {
+                self.args.push((names.iter().map(|&n| n.into()).collect(), arg));
+                Ok(())
+            }
+            _ => Err("A positional argument takes one name, and no option strings"),
+        }
+    }
+    
+    /// Sets defaults by `dest`, which override those of `add_argument`.
+    /// Those not belonging to an argument are still read back from the
+    /// results, as Python sets them as attributes.
+    pub fn set_defaults(&mut self, defaults: &[(&str, &str)]) {
+        for &(dest, value) in defaults {
+            self.defaults.insert(dest.into(), value.into());
+        }
+    }
+    
+    /// Builds the `ArgParser` the facade parses with, e.g. to print its
+    /// help or to set what the facade doesn't cover
+    pub fn parser(&self) -> ArgParser {
+        let mut parser = ArgParser::new(self.prog.clone());
+        if let Some(ref text) = self.description {
+            parser.description(text);
+        }
+        
+        let mut positionals = 0;
+        
+        for (names, arg) in self.args.iter() {
+            let name = name(names);
+            let flag = names.iter().filter_map(|n| short(n)).next().unwrap_or('\0');
+            
+            let is_positional = !names[0].starts_with('-');
+            let type_ = match (is_positional, arg.action, arg.nargs) {
+                (_, Action::StoreTrue, _) | (_, Action::StoreFalse, _) => ArgType::Flag,
+                (_, Action::Count, _) => ArgType::Counter,
+                (true, _, Nargs::One) | (true, _, Nargs::Optional) => {
+                    positionals += 1;
+                    ArgType::Positional(positionals - 1)
+                }
+                (true, _, _) => ArgType::PositionalList(0),
+                (false, Action::Store, Nargs::One) | (false, Action::Store, Nargs::Optional) => ArgType::Option,
+                (false, _, _) => ArgType::List,
+            };
+            let required = match (is_positional, arg.nargs) {
+                (true, Nargs::Optional) | (true, Nargs::ZeroOrMore) => false,
+                (true, _) => true,
+                (false, _) => arg.required,
+            };
+            
+            let default = self.defaults.get(&dest(names, arg)).or(arg.default.as_ref());
+            let default = match (arg.action, default) {
+                (Action::StoreTrue, None) | (Action::StoreFalse, None) => Some("false".into()),
+                (Action::StoreFalse, Some(value)) => value.parse::<bool>().ok().map(|b| (!b).to_string()),
+                (_, value) => value.cloned(),
+            };
+            
+            parser.add_opt(&name, default.as_deref(), flag, required, &arg.help, type_);
+            
+            let limit = match (arg.action, arg.nargs) {
+                (Action::Append, Nargs::One) => Some(1),
+                (_, Nargs::Exactly(n)) => Some(n),
+                _ => None,
+            };
+            if let Some(n) = limit {
+                let _ = parser.list_values(&name, ListValues::AtMost(n));
+            }
+            if !arg.choices.is_empty() {
+                let choices: Vec<&str> = arg.choices.iter().map(|s| s.as_str()).collect();
+                let _ = parser.choices(&name, &choices);
+            }
+        }
+        
+        parser
+    }
+    
+    /// Parses the arguments, the first being the program name as with
+    /// `ArgParser::parse`. Unlike in Python, errors are returned rather
+    /// than ending the program.
+    pub fn parse_args<S: AsRef<str>>(&self, args: &[S]) -> Result<Namespace, ParseError> {
+        let args: Vec<String> = args.iter().map(|s| s.as_ref().into()).collect();
+        let results = self.parser().parse(args.iter())?;
+        
+        let mut names = HashMap::new();
+        let mut inverted = HashSet::new();
+        for (given, arg) in self.args.iter() {
+            if arg.action == Action::StoreFalse {
+                inverted.insert(name(given));
+            }
+            names.insert(dest(given, arg), name(given));
+        }
+        
+        Ok(Namespace { results, names, inverted, defaults: self.defaults.clone() })
+    }
+}
+
+/// The results of `ArgumentParser::parse_args`, read by `dest`
+#[derive(Debug, Clone)]
+pub struct Namespace {
+    results: ArgParseResults,
+    names: HashMap<String, String>,
+    inverted: HashSet<String>,
+    defaults: HashMap<String, String>,
+}
+
+impl Namespace {
+    /// Gets the value of an argument, or of a default set with
+    /// `set_defaults` for no argument
+    pub fn get<T: FromStr + 'static>(&self, dest: &str) -> Option<T> {
+        match self.names.get(dest) {
+            Some(name) if self.inverted.contains(name) => {
+                self.results.get::<bool>(name).and_then(|b| (!b).to_string().parse().ok())
+            }
+            Some(name) => self.results.get(name),
+            None => self.defaults.get(dest).and_then(|v| v.parse().ok()),
+        }
+    }
+    
+    /// Gets the values of an argument taking several, as a list
+    pub fn get_list<T: FromStr + 'static>(&self, dest: &str) -> Option<Vec<T>> {
+        self.names.get(dest).and_then(|name| self.results.get_list(name))
+    }
+    
+    /// The results of the `ArgParser`, whose names are the long options
+    /// rather than the `dest`s
+    pub fn results(&self) -> &ArgParseResults {
+        &self.results
+    }
+}
+
+/// The letter of a short option string such as `-v`
+fn short(name: &str) -> Option<char> {
+    let mut chars = name.strip_prefix('-')?.chars();
+    
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c != '-' => Some(c),
+        _ => None,
+    }
+}
+
+/// The name of an argument in `ArgParser`, its first long option or
+/// else its first name without dashes
+fn name(names: &[String]) -> String {
+    names.iter().find_map(|n| n.strip_prefix("--"))
+        .unwrap_or_else(|| names[0].trim_start_matches('-'))
+        .into()
+}
+
+/// The name of an argument as Python's `argparse` reads it
+fn dest(names: &[String], arg: &Argument) -> String {
+    arg.dest.clone().unwrap_or_else(|| name(names).replace('-', "_"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Action, Argument, ArgumentParser, Nargs};
+    
+    fn setup() -> ArgumentParser {
+        let mut parser = ArgumentParser::new("copy");
+        parser.add_argument(&["-v", "--verbose"], Argument {
+            action: Action::Count,
+            ..Argument::default()
+        }).unwrap();
+        parser.add_argument(&["--no-cache"], Argument {
+            action: Action::StoreFalse,
+            dest: Some("cache".into()),
+            ..Argument::default()
+        }).unwrap();
+        parser.add_argument(&["-x", "--exclude"], Argument {
+            action: Action::Append,
+            ..Argument::default()
+        }).unwrap();
+        parser.add_argument(&["--mode"], Argument {
+            choices: vec!["fast".into(), "safe".into()],
+            default: Some("safe".into()),
+            ..Argument::default()
+        }).unwrap();
+        parser.add_argument(&["source"], Argument::default()).unwrap();
+        parser.add_argument(&["target"], Argument {
+            nargs: Nargs::Optional,
+            ..Argument::default()
+        }).unwrap();
+        parser
+    }
+    
+    #[test]
+    fn test_parse_args() {
+        let parser = setup();
+        
+        let args = parser.parse_args(&["./copy", "a.txt"]).unwrap();
+        assert_eq!(args.get::<u32>("verbose"), Some(0));
+        assert_eq!(args.get("cache"), Some(true));
+        assert_eq!(args.get::<String>("mode"), Some("safe".into()));
+        assert_eq!(args.get::<String>("source"), Some("a.txt".into()));
+        assert_eq!(args.get::<String>("target"), None);
+        
+        let args = parser.parse_args(&["./copy", "-vv", "--no-cache", "-x", "*.o", "-x", "*.a",
+            "--mode", "fast", "a.txt", "b.txt"]).unwrap();
+        assert_eq!(args.get::<u32>("verbose"), Some(2));
+        assert_eq!(args.get("cache"), Some(false));
+        assert_eq!(args.get_list::<String>("exclude"), Some(vec!["*.o".into(), "*.a".into()]));
+        assert_eq!(args.get::<String>("mode"), Some("fast".into()));
+        assert_eq!(args.get::<String>("target"), Some("b.txt".into()));
+        
+        assert!(parser.parse_args(&["./copy"]).is_err());
+        assert!(parser.parse_args(&["./copy", "--mode", "slow", "a.txt"]).is_err());
+    }
+    
+    #[test]
+    fn test_set_defaults() {
+        let mut parser = setup();
+        parser.set_defaults(&[("mode", "fast"), ("cache", "false"), ("retries", "3")]);
+        
+        let args = parser.parse_args(&["./copy", "a.txt"]).unwrap();
+        assert_eq!(args.get::<String>("mode"), Some("fast".into()));
+        assert_eq!(args.get("cache"), Some(false));
+        assert_eq!(args.get("retries"), Some(3));
+        assert_eq!(args.results().get::<String>("mode"), Some("fast".into()));
+    }
+    
+    #[test]
+    fn test_flags() {
+        let parser = setup().parser();
+        let help = parser.help_text();
+        
+        assert!(help.contains("--verbose (-v)"));
+        assert!(help.contains("--no-cache "));
+        assert!(help.contains("--mode "));
+        assert!(help.contains("--source "));
+        assert!(!help.contains("(-n)") && !help.contains("(-m)") && !help.contains("(-s)"));
+        
+        // Only the short names given are flags
+        let args = setup().parse_args(&["./copy", "-m", "fast", "a.txt"]).unwrap();
+        assert_eq!(args.get::<String>("mode"), Some("safe".into()));
+        assert_eq!(args.get::<String>("source"), Some("fast".into()));
+        
+        let mut parser = setup();
+        assert!(parser.add_argument(&[], Argument::default()).is_err());
+        assert!(parser.add_argument(&["a", "b"], Argument::default()).is_err());
+        assert!(parser.add_argument(&["-a", "b"], Argument::default()).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! This module defines `Segments`, which splits one command line into
+//! segments at a sentinel and parses each with a parser of its own, as
+//! launchers and wrappers need: in `driver -v ++ worker -j 4`, the
+//! driver's parser reads `driver -v` and the worker's `worker -j 4`.
+//! The first argument of each segment stands in for its program name.
+//!
+//! # Example
+//! ```
+//! use argparse::{ArgParser, ArgType};
+//! use argparse::segments::Segments;
+//!
+//! let mut driver = ArgParser::new("driver".into());
+//! driver.add_opt("verbose", Some("false"), 'v', false,
+//!     "Verbose output", ArgType::Flag);
+//! let mut worker = ArgParser::new("worker".into());
+//! worker.add_opt("jobs", Some("1"), 'j', false,
+//!     "Jobs to run at once", ArgType::Option);
+//!
+//! let mut segments = Segments::new("++");
+//! segments.segment(driver).segment(worker);
+//!
+//! let args = "./driver -v ++ worker -j 4".split_whitespace()
+//!     .map(|s| s.into())
+//!     .collect::<Vec<String>>();
+//!
+//! let results = segments.parse(args.iter()).unwrap();
+//! assert_eq!(results[0].get("verbose"), Some(true));
+//! assert_eq!(results[1].get("jobs"), Some(4));
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+use argparser::{ArgParseResults, ArgParser, ParseError};
+
+/// Why `Segments::parse` failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum SegmentError {
+    /// A segment, counted from `0`, failed to parse
+    Parse {
+        /// Which segment it was
+        segment: usize,
+        /// Why it failed, with positions counted within the segment
+        error: ParseError,
+    },
+    /// There were more segments than parsers
+    TooMany {
+        /// How many parsers there are
+        parsers: usize,
+        /// How many segments were given
+        given: usize,
+    },
+}
+
+impl fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SegmentError::Parse { ref error, .. } => write!(f, "{}", error),
+            SegmentError::TooMany { parsers, given } => {
+                write!(f, "Expected at most {} segments, but got {}", parsers, given)
+            }
+        }
+    }
+}
+
+impl Error for SegmentError {}
+
+/// Parsers for consecutive segments of a command line, see the module
+/// documentation
+#[derive(Debug, Clone)]
+pub struct Segments {
+    sentinel: String,
+    parsers: Vec<ArgParser>,
+}
+
+impl Segments {
+    /// Creates an empty set of segments, which are separated by
+    /// `sentinel` on the command line
+    pub fn new(sentinel: &str) -> Segments {
+        Segments { sentinel: sentinel.into(), parsers: Vec::new() }
+    }
+    
+    /// Adds the parser for the next segment
+    pub fn segment(&mut self, parser: ArgParser) -> &mut Segments {
+        self.parsers.push(parser);
+        self
+    }
+    
+    /// The parsers of the segments, in order
+    pub fn parsers(&self) -> &[ArgParser] {
+        &self.parsers
+    }
+    
+    /// Splits the arguments, the first being the program name as with
+    /// `ArgParser::parse`, at each sentinel, and parses every segment
+    /// with its parser. Every sentinel ends a segment, even after `--`.
+    /// Segments may be left off the end, so there is a result for each
+    /// segment given rather than for each parser.
+    pub fn parse<'a, I: Iterator<Item = &'a String>>(&self, args: I) -> Result<Vec<ArgParseResults>, SegmentError> {
+        let mut split: Vec<Vec<String>> = vec![Vec::new()];
+        for arg in args {
+            if *arg == self.sentinel {
+                split.push(Vec::new());
+            } else if let Some(last) = split.last_mut() {
+                last.push(arg.clone());
+            }
+        }
+        
+        if split.len() > self.parsers.len() {
+            return Err(SegmentError::TooMany { parsers: self.parsers.len(), given: split.len() });
+        }
+        
+        split.iter().zip(self.parsers.iter()).enumerate()
+            .map(|(segment, (args, parser))| {
+                parser.parse(args.iter()).map_err(|error| SegmentError::Parse { segment, error })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SegmentError, Segments};
+    use argparser::{ArgParser, ArgType};
+    
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|s| s.into()).collect()
+    }
+    
+    #[test]
+    fn test_segments() {
+        let mut driver = ArgParser::new("driver".into());
+        driver.add_opt("verbose", Some("false"), 'v', false, "Verbose output", ArgType::Flag);
+        let mut worker = ArgParser::new("worker".into());
+        worker.add_opt("jobs", None, 'j', true, "Jobs to run at once", ArgType::Option);
+        worker.add_opt("rest", None, 'r', false, "The rest", ArgType::PositionalList(0));
+        
+        let mut segments = Segments::new("++");
+        segments.segment(driver).segment(worker);
+        assert_eq!(segments.parsers().len(), 2);
+        
+        let results = segments.parse(args("./driver ++ worker -j 2 in.txt").iter()).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].get("verbose"), Some(false));
+        assert_eq!(results[1].get_list::<String>("rest"), Some(vec!["in.txt".into()]));
+        
+        let results = segments.parse(args("./driver -v").iter()).unwrap();
+        assert_eq!(results.len(), 1);
+        
+        match segments.parse(args("./driver -v ++ worker -j").iter()) {
+            Err(SegmentError::Parse { segment, error }) => {
+                assert_eq!(segment, 1);
+                assert_eq!(error.position(), Some(1));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        
+        let err = segments.parse(args("./driver ++ worker -j 1 ++ other").iter()).unwrap_err();
+        assert_eq!(err, SegmentError::TooMany { parsers: 2, given: 3 });
+        assert_eq!(err.to_string(), "Expected at most 2 segments, but got 3");
+    }
+}
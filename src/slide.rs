@@ -1,4 +1,13 @@
-//! This module defines a  `Slide` iterator over `Vector`s and slices
+//! This module defines a `Slide` iterator over `Vec`s and slices, which
+//! yields each element together with the elements that follow it. This
+//! is the shape of most command line scanning: look at an argument, and
+//! at whatever comes after it to find its values.
+//!
+//! A `Windows` iterator, obtained from `Slide::windows`, does the same
+//! but looks ahead a fixed number of elements only.
+
+use std::cmp;
+use std::iter::FusedIterator;
 
 /// Immutable iterator that returns both an element, and slice
 /// representing the remaining elements
@@ -6,6 +15,11 @@
 /// This iterator will not return an empty slice upon reaching the
 /// last element, but will instead return a `None` instead of a
 /// `Some(&[..])`
+///
+/// It can also be iterated from the back, e.g. to find the last
+/// occurrence of an argument. The slice returned with an element is
+/// always everything after it, however the iterator was advanced.
+///
 /// # Example
 /// ```
 /// use argparse::slide::{Slide, Slider};
@@ -21,6 +35,61 @@
 pub struct Slide<'a, T: 'a> {
     v: &'a [T],
     pos: usize,
+    end: usize,
+}
+
+impl<'a, T> Slide<'a, T> {
+    /// Returns what the next call to `next` would, without advancing
+    /// the iterator
+    ///
+    /// # Example
+    /// ```
+    /// use argparse::slide::Slider;
+    ///
+    /// let v = vec![1, 2];
+    /// let mut it = v.slide();
+    ///
+    /// assert_eq!(it.peek(), Some((&1, Some(&[2][..]))));
+    /// assert_eq!(it.next(), Some((&1, Some(&[2][..]))));
+    /// assert_eq!(it.peek(), Some((&2, None)));
+    /// ```
+    #[inline]
+    pub fn peek(&self) -> Option<(&'a T, Option<&'a [T]>)> {
+        if self.pos < self.end {
+            Some(self.at(self.pos))
+        } else {
+            None
+        }
+    }
+    
+    #[inline]
+    fn at(&self, i: usize) -> (&'a T, Option<&'a [T]>) {
+        let v = self.v;
+        let rest = &v[i + 1..];
+        
+        (&v[i], if rest.is_empty() { None } else { Some(rest) })
+    }
+    
+    /// Turns the iterator into one that yields each remaining element
+    /// with exactly the `size` elements following it. Iteration stops
+    /// once fewer than `size` elements follow.
+    ///
+    /// # Example
+    /// ```
+    /// use argparse::slide::Slider;
+    ///
+    /// let v = vec!["-f", "1", "2", "-v"];
+    /// let pairs: Vec<_> = v.slide().windows(1).collect();
+    ///
+    /// assert_eq!(pairs, vec![
+    ///     (&"-f", &["1"][..]),
+    ///     (&"1", &["2"][..]),
+    ///     (&"2", &["-v"][..]),
+    /// ]);
+    /// ```
+    pub fn windows(self, size: usize) -> Windows<'a, T> {
+        Windows { v: self.v, pos: self.pos, end: self.end, size }
+    }
 }
 
 impl<'a, T: Sized> Iterator for Slide<'a, T> {
@@ -28,28 +97,80 @@ impl<'a, T: Sized> Iterator for Slide<'a, T> {
     
     #[inline]
     fn next(&mut self) -> Option<(&'a T, Option<&'a [T]>)> {
-        self.v.get(self.pos).map(|val| {
-            self.pos = self.pos + 1;
-            
-            if self.v.len() > self.pos {
-                (val, Some(&self.v[self.pos..]))
-            } else {
-                (val, None)
-            }
-        })
+        let next = self.peek();
+        
+        if next.is_some() {
+            self.pos += 1;
+        }
+        
+        next
     }
     
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let diff = self.v.len() - self.pos;
+        let diff = self.end - self.pos;
         
         (diff, Some(diff))
     }
 }
 
+impl<'a, T> DoubleEndedIterator for Slide<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(&'a T, Option<&'a [T]>)> {
+        if self.pos < self.end {
+            self.end -= 1;
+            Some(self.at(self.end))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Slide<'a, T> {}
+
+impl<'a, T> FusedIterator for Slide<'a, T> {}
+
+/// Iterator that returns an element and a slice of the fixed number of
+/// elements that follow it, created by `Slide::windows`
+pub struct Windows<'a, T: 'a> {
+    v: &'a [T],
+    pos: usize,
+    end: usize,
+    size: usize,
+}
+
+impl<'a, T> Iterator for Windows<'a, T> {
+    type Item = (&'a T, &'a [T]);
+    
+    #[inline]
+    fn next(&mut self) -> Option<(&'a T, &'a [T])> {
+        if self.len() == 0 {
+            return None;
+        }
+        
+        let start = self.pos + 1;
+        self.pos = start;
+        
+        Some((&self.v[start - 1], &self.v[start..start + self.size]))
+    }
+    
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let last = cmp::min(self.end, self.v.len().saturating_sub(self.size));
+        let remaining = last.saturating_sub(self.pos);
+        
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Windows<'a, T> {}
+
+impl<'a, T> FusedIterator for Windows<'a, T> {}
+
 /// Interface for all types that can produce a `Slide` iterator
 pub trait Slider<'a, T: Sized> {
     /// Calling this method shall produce a `Slide` iterator
+    ///
     /// # Example
     /// ```
     /// use argparse::slide::{Slide, Slider};
@@ -67,13 +188,13 @@ pub trait Slider<'a, T: Sized> {
 
 impl<'a, T> Slider<'a, T> for &'a [T] {
     fn slide(&'a self)  -> Slide<'a, T> {
-        Slide { v: self, pos: 0}
+        Slide { v: self, pos: 0, end: self.len() }
     }
 }
 
 impl<'a, T> Slider<'a, T> for Vec<T> {
     fn slide(&'a self)  -> Slide<'a, T> {
-        Slide { v: &self[..], pos: 0}
+        Slide { v: &self[..], pos: 0, end: self.len() }
     }
 }
 
@@ -81,6 +202,65 @@ impl<'a, T> Slider<'a, T> for Vec<T> {
 mod test {
     use super::{Slider};
     
+    #[test]
+    fn test_peek_and_len() {
+        let v = vec![1, 2, 3];
+        let mut it = v.slide();
+        
+        assert_eq!(it.len(), 3);
+        assert_eq!(it.peek(), it.next());
+        assert_eq!(it.len(), 2);
+        it.next();
+        it.next();
+        assert_eq!(it.len(), 0);
+        assert_eq!(it.peek(), None);
+    }
+    
+    #[test]
+    fn test_windows() {
+        let v = vec![1, 2, 3, 4];
+        
+        let mut it = v.slide().windows(2);
+        assert_eq!(it.len(), 2);
+        assert_eq!(it.next(), Some((&1, &[2, 3][..])));
+        assert_eq!(it.next(), Some((&2, &[3, 4][..])));
+        assert_eq!(it.next(), None);
+        
+        assert_eq!(v.slide().windows(0).count(), 4);
+        assert_eq!(v.slide().windows(4).count(), 0);
+        
+        let mut it = v.slide();
+        it.next();
+        assert_eq!(it.windows(1).next(), Some((&2, &[3][..])));
+        
+        let mut it = v.slide();
+        it.next_back();
+        it.next_back();
+        assert_eq!(it.windows(1).collect::<Vec<_>>(), vec![(&1, &[2][..]), (&2, &[3][..])]);
+    }
+    
+    #[test]
+    fn test_back() {
+        let v = vec![1, 2, 3];
+        let mut it = v.slide();
+        
+        assert_eq!(it.next_back(), Some((&3, None)));
+        assert_eq!(it.next(), Some((&1, Some(&[2, 3][..]))));
+        assert_eq!(it.len(), 1);
+        assert_eq!(it.next_back(), Some((&2, Some(&[3][..]))));
+        assert_eq!(it.next_back(), None);
+        assert_eq!(it.next(), None);
+        
+        // Last occurrence wins
+        let args = vec!["-n", "a", "-x", "-n", "b"];
+        let last = args.slide().rev()
+            .find(|&(arg, _)| *arg == "-n")
+            .and_then(|(_, rest)| rest)
+            .map(|rest| rest[0]);
+        
+        assert_eq!(last, Some("b"));
+    }
+    
     #[test]
     fn test_zero() {
         let v: Vec<u8> = vec![];
@@ -0,0 +1,163 @@
+//! This module defines helpers for testing the definitions of a parser
+//! against example command lines, as read by `ArgParser::parse_line`.
+//! Each check panics with a message naming the line when it fails, and
+//! returns its receiver so that checks can be chained.
+//!
+//! # Example
+//! ```
+//! use argparse::{ArgParser, ArgType};
+//! use argparse::testing::{expect_err, expect_ok};
+//!
+//! let mut parser = ArgParser::new("runner".into());
+//! parser.add_opt("name", None, 'n', true,
+//!     "Name of user", ArgType::Option);
+//! parser.add_opt("frequencies", None, 'f', false,
+//!     "User's favorite frequencies", ArgType::List);
+//!
+//! expect_ok(&parser, "-n Johnny -f 1 2 3")
+//!     .value_eq("name", "Johnny")
+//!     .values_eq("frequencies", &["1", "2", "3"]);
+//! expect_err(&parser, "-f 1 2 3").contains("required");
+//! ```
+
+use argparser::{ArgParseResults, ArgParser, ParseError};
+
+/// Parses a line, panicking if it fails to parse
+#[track_caller]
+pub fn expect_ok(parser: &ArgParser, line: &str) -> Parsed {
+    match parser.parse_line(line) {
+        Ok(results) => Parsed { line: line.into(), results },
+        Err(e) => panic!("expected `{}` to parse, but it failed with: {}", line, e),
+    }
+}
+
+/// Parses a line, panicking if it parses
+#[track_caller]
+pub fn expect_err(parser: &ArgParser, line: &str) -> Failed {
+    match parser.parse_line(line) {
+        Ok(_) => panic!("expected `{}` to fail to parse, but it parsed", line),
+        Err(error) => Failed { line: line.into(), error },
+    }
+}
+
+/// A line that parsed, see `expect_ok`
+#[derive(Debug, Clone)]
+pub struct Parsed {
+    line: String,
+    results: ArgParseResults,
+}
+
+impl Parsed {
+    /// Checks the value of an argument, as `get::<String>` reads it
+    #[track_caller]
+    pub fn value_eq(&self, name: &str, expected: &str) -> &Parsed {
+        let value = self.results.get::<String>(name);
+        
+        if value.as_deref() != Some(expected) {
+            panic!("expected `{}` to be `{}` for `{}`, but it was {:?}", name, expected, self.line, value);
+        }
+        self
+    }
+    
+    /// Checks every value given for an argument
+    #[track_caller]
+    pub fn values_eq(&self, name: &str, expected: &[&str]) -> &Parsed {
+        let values = self.results.values(name);
+        
+        if values.map(|v| v.iter().map(|s| s.as_str()).collect::<Vec<_>>()).as_deref() != Some(expected) {
+            panic!("expected `{}` to be {:?} for `{}`, but it was {:?}", name, expected, self.line, values);
+        }
+        self
+    }
+    
+    /// Checks that an argument has no value, given or default
+    #[track_caller]
+    pub fn absent(&self, name: &str) -> &Parsed {
+        if let Some(value) = self.results.get::<String>(name) {
+            panic!("expected `{}` to have no value for `{}`, but it was `{}`", name, self.line, value);
+        }
+        self
+    }
+    
+    /// The results of parsing, for any other checks
+    pub fn results(&self) -> &ArgParseResults {
+        &self.results
+    }
+}
+
+/// A line that failed to parse, see `expect_err`
+#[derive(Debug, Clone)]
+pub struct Failed {
+    line: String,
+    error: ParseError,
+}
+
+impl Failed {
+    /// Checks that the error message contains some text
+    #[track_caller]
+    pub fn contains(&self, needle: &str) -> &Failed {
+        if !self.error.message().contains(needle) {
+            panic!("expected the error for `{}` to contain `{}`, but it was: {}", self.line, needle, self.error);
+        }
+        self
+    }
+    
+    /// Checks which argument the error points at, counting from 1 as
+    /// the line is parsed after the program name
+    #[track_caller]
+    pub fn at(&self, position: usize) -> &Failed {
+        if self.error.position() != Some(position) {
+            panic!("expected the error for `{}` to be at argument {}, but it was at {:?}",
+                self.line, position, self.error.position());
+        }
+        self
+    }
+    
+    /// The error, for any other checks
+    pub fn error(&self) -> &ParseError {
+        &self.error
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{expect_err, expect_ok};
+    use argparser::{ArgParser, ArgType};
+    
+    fn setup() -> ArgParser {
+        let mut parser = ArgParser::new("runner".into());
+        parser.add_opt("name", None, 'n', true, "Name of user", ArgType::Option);
+        parser.add_opt("frequencies", None, 'f', false, "User's favorite frequencies", ArgType::List);
+        parser.add_opt("motto", None, 'm', false, "Motto", ArgType::Option);
+        parser
+    }
+    
+    #[test]
+    fn test_checks_pass() {
+        let parser = setup();
+        
+        expect_ok(&parser, "-n 'Johnny Smith' -f 1 2")
+            .value_eq("name", "Johnny Smith")
+            .values_eq("frequencies", &["1", "2"])
+            .absent("motto");
+        expect_err(&parser, "-n").contains("requires a value").at(1);
+    }
+    
+    #[test]
+    #[should_panic(expected = "expected `name` to be `Tina` for `-n Johnny`")]
+    fn test_value_mismatch() {
+        expect_ok(&setup(), "-n Johnny").value_eq("name", "Tina");
+    }
+    
+    #[test]
+    #[should_panic(expected = "expected `-n Johnny` to fail to parse")]
+    fn test_unexpected_success() {
+        expect_err(&setup(), "-n Johnny");
+    }
+    
+    #[test]
+    #[should_panic(expected = "to contain `missing`")]
+    fn test_message_mismatch() {
+        expect_err(&setup(), "-f 1").contains("missing");
+    }
+}